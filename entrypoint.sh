@@ -7,22 +7,158 @@ cd "${GITHUB_WORKSPACE:-/github/workspace}"
 # reference hyphenated var names with ${} syntax, so we use printenv.
 hosts="$(printenv INPUT_HOSTS || true)"
 after="$(printenv INPUT_AFTER || true)"
+as_of="$(printenv 'INPUT_AS-OF' || true)"
 username="$(printenv INPUT_USERNAME || true)"
 password="$(printenv INPUT_PASSWORD || true)"
+ca_cert="$(printenv 'INPUT_CA-CERT' || true)"
+insecure="$(printenv INPUT_INSECURE || true)"
 output_svg="$(printenv 'INPUT_OUTPUT-SVG' || true)"
+output_svg_light="$(printenv 'INPUT_OUTPUT-SVG-LIGHT' || true)"
+output_svg_dark="$(printenv 'INPUT_OUTPUT-SVG-DARK' || true)"
+output_svg_animated="$(printenv 'INPUT_OUTPUT-SVG-ANIMATED' || true)"
 output_md="$(printenv 'INPUT_OUTPUT-MD' || true)"
+template_file="$(printenv 'INPUT_TEMPLATE-FILE' || true)"
 svg_theme="$(printenv 'INPUT_SVG-THEME' || true)"
+svg_theme_file="$(printenv 'INPUT_SVG-THEME-FILE' || true)"
 svg_multi_color="$(printenv 'INPUT_SVG-MULTI-COLOR' || true)"
+svg_stack="$(printenv 'INPUT_SVG-STACK' || true)"
+svg_layout="$(printenv 'INPUT_SVG-LAYOUT' || true)"
+svg_foreign_object="$(printenv 'INPUT_SVG-FOREIGN-OBJECT' || true)"
+svg_highlight_peak="$(printenv 'INPUT_SVG-HIGHLIGHT-PEAK' || true)"
+svg_watermark="$(printenv 'INPUT_SVG-WATERMARK' || true)"
+svg_avatar="$(printenv 'INPUT_SVG-AVATAR' || true)"
+anonymize_owner="$(printenv 'INPUT_ANONYMIZE-OWNER' || true)"
+project_rank="$(printenv 'INPUT_PROJECT-RANK' || true)"
+notable="$(printenv 'INPUT_NOTABLE' || true)"
+recent_days="$(printenv 'INPUT_RECENT-DAYS' || true)"
+compare="$(printenv 'INPUT_COMPARE' || true)"
+with_revisions="$(printenv 'INPUT_WITH-REVISIONS' || true)"
+smooth="$(printenv 'INPUT_SMOOTH' || true)"
+heatmap_glyphs="$(printenv 'INPUT_HEATMAP-GLYPHS' || true)"
+locale="$(printenv 'INPUT_LOCALE' || true)"
+output_csv="$(printenv 'INPUT_OUTPUT-CSV' || true)"
+output_badge="$(printenv 'INPUT_OUTPUT-BADGE' || true)"
+output_digest="$(printenv 'INPUT_OUTPUT-DIGEST' || true)"
+output_mermaid="$(printenv 'INPUT_OUTPUT-MERMAID' || true)"
+output_events="$(printenv 'INPUT_OUTPUT-EVENTS' || true)"
+show_host_breakdown="$(printenv 'INPUT_SHOW-HOST-BREAKDOWN' || true)"
+badge_metric="$(printenv 'INPUT_BADGE-METRIC' || true)"
+exclude_project="$(printenv 'INPUT_EXCLUDE-PROJECT' || true)"
+include_open="$(printenv 'INPUT_INCLUDE-OPEN' || true)"
+raw_query="$(printenv 'INPUT_RAW-QUERY' || true)"
+hashtag="$(printenv 'INPUT_HASHTAG' || true)"
+owner_email="$(printenv 'INPUT_OWNER-EMAIL' || true)"
+exclude_voter="$(printenv 'INPUT_EXCLUDE-VOTER' || true)"
+review_role="$(printenv 'INPUT_REVIEW-ROLE' || true)"
+max_reviews="$(printenv 'INPUT_MAX-REVIEWS' || true)"
+reviews_in_heatmap="$(printenv 'INPUT_REVIEWS-IN-HEATMAP' || true)"
+exclude_wip="$(printenv 'INPUT_EXCLUDE-WIP' || true)"
+min_cl_size="$(printenv 'INPUT_MIN-CL-SIZE' || true)"
+max_cl_size="$(printenv 'INPUT_MAX-CL-SIZE' || true)"
+clamp_cl_lines="$(printenv 'INPUT_CLAMP-CL-LINES' || true)"
+streak_grace="$(printenv 'INPUT_STREAK-GRACE' || true)"
+week_start="$(printenv 'INPUT_WEEK-START' || true)"
+family_depth="$(printenv 'INPUT_FAMILY-DEPTH' || true)"
+merge_families="$(printenv 'INPUT_MERGE-FAMILIES' || true)"
+cache_dir="$(printenv 'INPUT_CACHE-DIR' || true)"
+rps="$(printenv 'INPUT_RPS' || true)"
+user_agent="$(printenv 'INPUT_USER-AGENT' || true)"
+contact="$(printenv 'INPUT_CONTACT' || true)"
+stream="$(printenv INPUT_STREAM || true)"
+config="$(printenv INPUT_CONFIG || true)"
+continue_on_error="$(printenv 'INPUT_CONTINUE-ON-ERROR' || true)"
+strict="$(printenv 'INPUT_STRICT' || true)"
+fail_on_empty="$(printenv 'INPUT_FAIL-ON-EMPTY' || true)"
 
-args=(--owner "${INPUT_OWNER}")
+args=()
+[[ -n "${INPUT_OWNER:-}" ]] && args+=(--owner "${INPUT_OWNER}")
 
 [[ -n "$hosts"         ]] && args+=(--hosts          "$hosts")
 [[ -n "$after"         ]] && args+=(--after           "$after")
+[[ -n "$as_of"         ]] && args+=(--as-of           "$as_of")
 [[ -n "$username"      ]] && args+=(--username        "$username")
 [[ -n "$password"      ]] && args+=(--password        "$password")
+[[ -n "$ca_cert"       ]] && args+=(--ca-cert         "$ca_cert")
+[[ "$insecure" == "true" ]] && args+=(--insecure)
 [[ -n "$output_svg"    ]] && args+=(--output-svg      "$output_svg")
+[[ -n "$output_svg_light" ]] && args+=(--output-svg-light "$output_svg_light")
+[[ -n "$output_svg_dark"  ]] && args+=(--output-svg-dark  "$output_svg_dark")
+[[ -n "$output_svg_animated" ]] && args+=(--output-svg-animated "$output_svg_animated")
 [[ -n "$output_md"     ]] && args+=(--output-md       "$output_md")
+[[ -n "$template_file" ]] && args+=(--template-file   "$template_file")
 [[ -n "$svg_theme"     ]] && args+=(--svg-theme       "$svg_theme")
+[[ -n "$svg_theme_file" ]] && args+=(--svg-theme-file  "$svg_theme_file")
 [[ "$svg_multi_color" == "true" ]] && args+=(--svg-multi-color)
+[[ -n "$svg_stack" ]] && args+=(--svg-stack "$svg_stack")
+[[ -n "$svg_layout" ]] && args+=(--svg-layout "$svg_layout")
+[[ "$svg_foreign_object" == "true" ]] && args+=(--svg-foreign-object)
+[[ "$svg_highlight_peak" == "true" ]] && args+=(--svg-highlight-peak)
+[[ "$svg_watermark" == "true" ]] && args+=(--svg-watermark)
+[[ "$svg_avatar" == "true" ]] && args+=(--svg-avatar)
+[[ "$anonymize_owner" == "true" ]] && args+=(--anonymize-owner)
+[[ "$project_rank" == "true" ]] && args+=(--project-rank)
+[[ -n "$notable" ]] && args+=(--notable "$notable")
+[[ -n "$recent_days" ]] && args+=(--recent-days "$recent_days")
+[[ -n "$compare" ]] && args+=(--compare "$compare")
+[[ "$with_revisions" == "true" ]] && args+=(--with-revisions)
+[[ "$smooth" == "true" ]] && args+=(--smooth)
+[[ -n "$heatmap_glyphs" ]] && args+=(--heatmap-glyphs "$heatmap_glyphs")
+[[ -n "$locale" ]] && args+=(--locale "$locale")
+[[ -n "$output_csv"    ]] && args+=(--output-csv      "$output_csv")
+[[ -n "$output_badge"  ]] && args+=(--output-badge    "$output_badge")
+[[ -n "$output_digest" ]] && args+=(--output-digest   "$output_digest")
+[[ -n "$output_mermaid" ]] && args+=(--output-mermaid  "$output_mermaid")
+[[ -n "$output_events" ]] && args+=(--output-events   "$output_events")
+[[ "$show_host_breakdown" == "true" ]] && args+=(--show-host-breakdown)
+[[ -n "$badge_metric"  ]] && args+=(--badge-metric    "$badge_metric")
+[[ "$include_open" == "true" ]] && args+=(--include-open)
+[[ -n "$raw_query" ]] && args+=(--raw-query "$raw_query")
+if [[ -n "$hashtag" ]]; then
+  IFS=',' read -ra hashtags <<< "$hashtag"
+  for t in "${hashtags[@]}"; do args+=(--hashtag "$t"); done
+fi
+if [[ -n "$owner_email" ]]; then
+  IFS=',' read -ra owner_emails <<< "$owner_email"
+  for e in "${owner_emails[@]}"; do args+=(--owner-email "$e"); done
+fi
+if [[ -n "$exclude_project" ]]; then
+  IFS=',' read -ra projects <<< "$exclude_project"
+  for p in "${projects[@]}"; do args+=(--exclude-project "$p"); done
+fi
+if [[ -n "$exclude_voter" ]]; then
+  IFS=',' read -ra voters <<< "$exclude_voter"
+  for v in "${voters[@]}"; do args+=(--exclude-voter "$v"); done
+fi
+[[ -n "$review_role"   ]] && args+=(--review-role     "$review_role")
+[[ -n "$max_reviews"   ]] && args+=(--max-reviews     "$max_reviews")
+[[ -n "$reviews_in_heatmap" ]] && args+=(--reviews-in-heatmap "$reviews_in_heatmap")
+[[ "$exclude_wip" == "true" ]] && args+=(--exclude-wip)
+[[ -n "$min_cl_size"   ]] && args+=(--min-cl-size     "$min_cl_size")
+[[ -n "$max_cl_size"   ]] && args+=(--max-cl-size     "$max_cl_size")
+[[ -n "$clamp_cl_lines" ]] && args+=(--clamp-cl-lines  "$clamp_cl_lines")
+[[ -n "$streak_grace"  ]] && args+=(--streak-grace    "$streak_grace")
+[[ -n "$week_start"    ]] && args+=(--week-start      "$week_start")
+[[ -n "$family_depth"  ]] && args+=(--family-depth    "$family_depth")
+if [[ -n "$merge_families" ]]; then
+  IFS=',' read -ra merges <<< "$merge_families"
+  for m in "${merges[@]}"; do args+=(--merge-families "$m"); done
+fi
+[[ -n "$cache_dir"     ]] && args+=(--cache-dir       "$cache_dir")
+[[ -n "$rps"           ]] && args+=(--rps             "$rps")
+[[ -n "$user_agent"    ]] && args+=(--user-agent      "$user_agent")
+[[ -n "$contact"       ]] && args+=(--contact         "$contact")
+[[ "$stream" == "true" ]] && args+=(--stream)
+[[ -n "$config"        ]] && args+=(--config          "$config")
+[[ "$continue_on_error" == "true" ]] && args+=(--continue-on-error)
+[[ "$strict" == "true" ]] && args+=(--strict)
+[[ "$fail_on_empty" == "true" ]] && args+=(--fail-on-empty)
 
-exec /usr/local/bin/gerritoscope "${args[@]}"
+# Exit 2 is a partial report under continue-on-error: keep the step green
+# (so later steps can commit the card) but flag it.  `strict` exits 3.
+status=0
+/usr/local/bin/gerritoscope "${args[@]}" || status=$?
+if [[ "$status" -eq 2 ]]; then
+  echo "::warning::gerritoscope skipped failed hosts; the report is partial"
+  exit 0
+fi
+exit "$status"
@@ -0,0 +1,258 @@
+//! Batch mode (`--accounts-file`): many owners' cards from one run.
+//!
+//! The accounts file lists one `--owner` value per line.  Anything after a
+//! `#` is a comment, so a line can carry a human-readable note:
+//!
+//! ```text
+//! # Platform team
+//! alice@example.com        # Alice
+//! bob@example.com
+//! corp=carol@corp.com,carol@example.com
+//! ```
+//!
+//! Each owner's card is written to `<output-dir>/<file_stem(owner)>.svg`.
+//!
+//! The owner file (`--owner-file`) is a manifest of [`JobSpec`]s instead:
+//! tab-separated owner, output path and optional theme name per line, with
+//! the same comments:
+//!
+//! ```text
+//! alice@example.com<TAB>cards/alice.svg<TAB>dracula
+//! bob@example.com<TAB>cards/bob.svg
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+/// Read the owner list from `path`; see [`parse`].
+pub fn load(path: &Path) -> Result<Vec<String>> {
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let owners = parse(&text);
+    if owners.is_empty() {
+        bail!("{} lists no accounts", path.display());
+    }
+    Ok(owners)
+}
+
+/// Owners listed in `text`, in file order, with comments and blank lines
+/// dropped.
+pub fn parse(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// One `--owner-file` line: whose card to render, where, and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobSpec {
+    /// An `--owner` value, per-host prefixes and all.
+    pub owner: String,
+    pub output: PathBuf,
+    /// Built-in theme name; `None` keeps `--svg-theme`.
+    pub theme: Option<String>,
+}
+
+/// Read the job manifest from `path`; see [`parse_jobs`].
+pub fn load_jobs(path: &Path) -> Result<Vec<JobSpec>> {
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let jobs = parse_jobs(&text).with_context(|| format!("parsing {}", path.display()))?;
+    if jobs.is_empty() {
+        bail!("{} lists no jobs", path.display());
+    }
+    Ok(jobs)
+}
+
+/// Jobs listed in `text`, in file order: `owner<TAB>output[<TAB>theme]`
+/// per line, comments and blank lines dropped.
+///
+/// Errors name the offending line: a missing owner or output, extra
+/// columns, an unknown theme, or an output another line already writes.
+pub fn parse_jobs(text: &str) -> Result<Vec<JobSpec>> {
+    let mut jobs: Vec<(usize, JobSpec)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let n = i + 1;
+        let line = line.split('#').next().unwrap_or("");
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+        let (owner, output, theme) = match fields[..] {
+            [owner, output] => (owner, output, None),
+            [owner, output, theme] => (owner, output, Some(theme).filter(|t| !t.is_empty())),
+            _ => bail!(
+                "line {n}: expected owner<TAB>output[<TAB>theme], found {} column(s)",
+                fields.len()
+            ),
+        };
+        if owner.is_empty() || output.is_empty() {
+            bail!("line {n}: owner and output must both be set");
+        }
+        if let Some(theme) = theme {
+            crate::render::svg::theme_by_name(theme).with_context(|| format!("line {n}"))?;
+        }
+        if let Some((first, _)) = jobs.iter().find(|(_, j)| j.output == Path::new(output)) {
+            bail!("line {n}: {output} is already written by line {first}");
+        }
+        jobs.push((
+            n,
+            JobSpec {
+                owner: owner.to_owned(),
+                output: PathBuf::from(output),
+                theme: theme.map(str::to_owned),
+            },
+        ));
+    }
+    Ok(jobs.into_iter().map(|(_, job)| job).collect())
+}
+
+/// A filename-safe stem for `owner`: `@` becomes `_at_`, anything other
+/// than ASCII letters, digits, `.`, `-` and `_` becomes `_`, and a leading
+/// `.` is replaced so the result is never hidden or `..`.
+///
+/// `me@x.com` → `me_at_x.com`
+pub fn file_stem(owner: &str) -> String {
+    let mut stem = String::with_capacity(owner.len());
+    for ch in owner.chars() {
+        match ch {
+            '@' => stem.push_str("_at_"),
+            c if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') => stem.push(c),
+            _ => stem.push('_'),
+        }
+    }
+    if stem.starts_with('.') {
+        stem.replace_range(..1, "_");
+    }
+    stem
+}
+
+/// Pair each owner with its output file in `dir`, one per owner.
+///
+/// Owners whose stems collide (e.g. `a+b@x.com` and `a_b@x.com`) get `-2`,
+/// `-3`, … suffixes in file order rather than overwriting each other.
+pub fn output_paths(owners: &[String], dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut used: Vec<String> = Vec::new();
+    owners
+        .iter()
+        .map(|owner| {
+            let base = file_stem(owner);
+            let mut stem = base.clone();
+            let mut n = 1;
+            while used.contains(&stem) {
+                n += 1;
+                stem = format!("{base}-{n}");
+            }
+            used.push(stem.clone());
+            (owner.clone(), dir.join(format!("{stem}.svg")))
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "# Platform team\n\
+                        alice@example.com        # Alice\n\
+                        \n\
+                        bob@example.com\n\
+                        corp=carol@corp.com,carol@example.com\n";
+
+    #[test]
+    fn parse_skips_comments_and_blanks() {
+        assert_eq!(
+            parse(FILE),
+            [
+                "alice@example.com",
+                "bob@example.com",
+                "corp=carol@corp.com,carol@example.com"
+            ]
+        );
+        assert!(parse("# nobody\n\n").is_empty());
+    }
+
+    #[test]
+    fn parse_jobs_per_line_themes() {
+        let jobs = parse_jobs(
+            "# owner\toutput\ttheme\n\
+             alice@example.com\tcards/alice.svg\tdracula\n\
+             \n\
+             corp=bob@corp.com\tcards/bob.svg\tgithub-light   # Bob\n",
+        )
+        .unwrap();
+        assert_eq!(
+            jobs,
+            [
+                JobSpec {
+                    owner: "alice@example.com".to_owned(),
+                    output: PathBuf::from("cards/alice.svg"),
+                    theme: Some("dracula".to_owned()),
+                },
+                JobSpec {
+                    owner: "corp=bob@corp.com".to_owned(),
+                    output: PathBuf::from("cards/bob.svg"),
+                    theme: Some("github-light".to_owned()),
+                },
+            ]
+        );
+        assert_eq!(parse_jobs("me@x.com\tme.svg\n").unwrap()[0].theme, None);
+    }
+
+    #[test]
+    fn parse_jobs_errors_name_the_line() {
+        let err = |text: &str| format!("{:#}", parse_jobs(text).unwrap_err());
+        assert!(err("a@x.com\ta.svg\nb@x.com\n").starts_with("line 2: expected"));
+        assert!(err("a@x.com\ta.svg\tx\ty\n").contains("found 4 column(s)"));
+        assert!(err("\ta.svg\n").starts_with("line 1: owner and output"));
+        assert!(err("\n\na@x.com\ta.svg\tneon\n").starts_with("line 3: unknown theme"));
+        assert_eq!(
+            err("a@x.com\ta.svg\nb@x.com\ta.svg\n"),
+            "line 2: a.svg is already written by line 1"
+        );
+    }
+
+    #[test]
+    fn file_stem_sanitizes() {
+        assert_eq!(file_stem("me@x.com"), "me_at_x.com");
+        assert_eq!(file_stem("a+b@x.com"), "a_b_at_x.com");
+        assert_eq!(file_stem("../etc/passwd"), "_._etc_passwd");
+        assert_eq!(file_stem("corp=me@corp.com"), "corp_me_at_corp.com");
+    }
+
+    #[test]
+    fn one_output_per_line() {
+        let owners = parse(FILE);
+        let paths = output_paths(&owners, Path::new("cards"));
+        assert_eq!(paths.len(), owners.len());
+        assert_eq!(paths[0].1, Path::new("cards/alice_at_example.com.svg"));
+        assert_eq!(
+            paths[2].1,
+            Path::new("cards/corp_carol_at_corp.com_carol_at_example.com.svg")
+        );
+    }
+
+    #[test]
+    fn colliding_stems_get_suffixes() {
+        let owners = ["a+b@x.com", "a_b@x.com", "a+b@x.com"].map(str::to_owned);
+        let names: Vec<_> = output_paths(&owners, Path::new("."))
+            .into_iter()
+            .map(|(_, p)| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "a_b_at_x.com.svg",
+                "a_b_at_x.com-2.svg",
+                "a_b_at_x.com-3.svg"
+            ]
+        );
+    }
+}
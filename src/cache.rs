@@ -0,0 +1,159 @@
+//! On-disk cache of Gerrit REST responses (`--cache-dir`).
+//!
+//! Each request URL + query maps to one JSON file holding the response body,
+//! the time it was fetched, and the `ETag` Gerrit sent (if any):
+//!
+//! - With an `ETag`, every re-fetch is a conditional request
+//!   (`If-None-Match`); `304 Not Modified` reuses the cached body.
+//! - Without one, the cached body is reused as-is until it is older than the
+//!   TTL, then fetched again in full.
+//!
+//! Cache files are an optimisation only: unreadable or corrupt entries are
+//! treated as misses.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Default lifetime of an entry without an `ETag`.
+pub const DEFAULT_TTL_MINUTES: i64 = 60;
+
+/// A directory of cached responses.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+/// One cached response body.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// `ETag` header from the response that produced `body`, verbatim.
+    pub etag: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+    pub body: String,
+}
+
+impl Cache {
+    /// Use `dir` (created if missing) with entries lacking an `ETag`
+    /// considered fresh for `ttl`.
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).with_context(|| format!("creating {}", dir.display()))?;
+        Ok(Self { dir, ttl })
+    }
+
+    /// The cached entry for `key`, if there is a readable one.
+    pub fn get(&self, key: &str) -> Option<CachedResponse> {
+        let text = std::fs::read_to_string(self.path(key)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// Store `entry` under `key`, replacing any previous one.
+    pub fn put(&self, key: &str, entry: &CachedResponse) -> Result<()> {
+        let path = self.path(key);
+        let json = serde_json::to_string(entry)?;
+        std::fs::write(&path, json).with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// Whether `entry` can be used without asking the server at all: only
+    /// entries without an `ETag` (which must be revalidated instead) that are
+    /// younger than the TTL.
+    pub fn is_fresh(&self, entry: &CachedResponse, now: DateTime<Utc>) -> bool {
+        entry.etag.is_none() && now - entry.fetched_at < self.ttl
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.json", fnv1a(key.as_bytes())))
+    }
+}
+
+/// Cache key for a GET of `url` with `query` parameters.
+pub fn key(url: &str, query: &[(&str, &str)]) -> String {
+    let mut k = url.to_owned();
+    for (i, (name, value)) in query.iter().enumerate() {
+        k.push(if i == 0 { '?' } else { '&' });
+        k.push_str(name);
+        k.push('=');
+        k.push_str(value);
+    }
+    k
+}
+
+/// 64-bit FNV-1a — stable across Rust versions, unlike `DefaultHasher`, so
+/// cache file names survive toolchain upgrades.  Also picks SVG family
+/// palettes, which must stay put for the same reason.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A fresh, empty cache directory under the system temp dir.
+    pub(crate) fn scratch_cache(name: &str, ttl: Duration) -> Cache {
+        let dir =
+            std::env::temp_dir().join(format!("gerritoscope-cache-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        Cache::new(dir, ttl).unwrap()
+    }
+
+    fn entry(etag: Option<&str>, fetched_at: DateTime<Utc>) -> CachedResponse {
+        CachedResponse {
+            etag: etag.map(str::to_owned),
+            fetched_at,
+            body: "[]".to_owned(),
+        }
+    }
+
+    #[test]
+    fn key_includes_query_in_order() {
+        assert_eq!(
+            key("https://h/changes/", &[("q", "owner:me"), ("n", "500")]),
+            "https://h/changes/?q=owner:me&n=500"
+        );
+        assert_eq!(key("https://h/changes/1/", &[]), "https://h/changes/1/");
+    }
+
+    #[test]
+    fn put_then_get_round_trips() {
+        let cache = scratch_cache("roundtrip", Duration::minutes(5));
+        let e = entry(Some("\"abc\""), Utc::now());
+        cache.put("k", &e).unwrap();
+        assert_eq!(cache.get("k"), Some(e));
+        assert_eq!(cache.get("other"), None);
+    }
+
+    #[test]
+    fn corrupt_entry_is_a_miss() {
+        let cache = scratch_cache("corrupt", Duration::minutes(5));
+        std::fs::write(cache.path("k"), "not json").unwrap();
+        assert_eq!(cache.get("k"), None);
+    }
+
+    #[test]
+    fn freshness_uses_ttl_only_without_etag() {
+        let cache = scratch_cache("fresh", Duration::minutes(60));
+        let now = Utc::now();
+        assert!(cache.is_fresh(&entry(None, now - Duration::minutes(59)), now));
+        assert!(!cache.is_fresh(&entry(None, now - Duration::minutes(61)), now));
+        // ETag entries are always revalidated.
+        assert!(!cache.is_fresh(&entry(Some("\"x\""), now), now));
+    }
+
+    #[test]
+    fn fnv1a_is_stable() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
@@ -0,0 +1,85 @@
+//! Optional user configuration file (`--config path.json`).
+//!
+//! The file is plain JSON so it can be parsed with the `serde_json` we
+//! already depend on:
+//!
+//! ```json
+//! {
+//!   "hosts": {
+//!     "corp": "https://gerrit.corp.example.com",
+//!     "corp-staging": "https://gerrit-staging.corp.example.com"
+//!   }
+//! }
+//! ```
+//!
+//! Every section is optional; an empty object is a valid config.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// Parsed contents of a config file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// User-defined host aliases: `alias → base URL`.
+    ///
+    /// Merged over [`crate::hosts::KNOWN_HOSTS`]; a user alias wins over a
+    /// built-in one with the same name.
+    #[serde(default)]
+    pub hosts: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Read and parse a config file from `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Parse config JSON from a string.
+    pub fn parse(text: &str) -> Result<Self> {
+        Ok(serde_json::from_str(text)?)
+    }
+
+    /// User host aliases as `(alias, url)` pairs, ready to pass to
+    /// [`crate::hosts::expand`].  URLs have any trailing `/` stripped.
+    pub fn host_overlay(&self) -> Vec<(String, String)> {
+        self.hosts
+            .iter()
+            .map(|(a, u)| (a.clone(), u.trim_end_matches('/').to_owned()))
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hosts_table() {
+        let cfg = Config::parse(r#"{"hosts": {"corp": "https://gerrit.corp.com/"}}"#).unwrap();
+        assert_eq!(
+            cfg.host_overlay(),
+            vec![("corp".to_owned(), "https://gerrit.corp.com".to_owned())]
+        );
+    }
+
+    #[test]
+    fn parse_empty_object() {
+        let cfg = Config::parse("{}").unwrap();
+        assert!(cfg.hosts.is_empty());
+    }
+
+    #[test]
+    fn parse_rejects_unknown_keys() {
+        assert!(Config::parse(r#"{"hostz": {}}"#).is_err());
+    }
+}
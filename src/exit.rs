@@ -0,0 +1,143 @@
+//! Process exit codes.
+//!
+//! | Code | Meaning |
+//! |-----:|:--------|
+//! | 0 | Every host (and, in batch mode, every owner) succeeded |
+//! | 1 | Any other error |
+//! | 2 | Partial success: some hosts or owners failed under `--continue-on-error` |
+//! | 3 | Partial success under `--strict` |
+//! | 4 | Authentication: a host answered 401 or 403 |
+//! | 5 | Network: a host couldn't be reached or timed out |
+
+use crate::gerrit::HttpError;
+
+pub const SUCCESS: u8 = 0;
+pub const FAILURE: u8 = 1;
+pub const PARTIAL: u8 = 2;
+pub const STRICT_PARTIAL: u8 = 3;
+pub const AUTH: u8 = 4;
+pub const NETWORK: u8 = 5;
+
+/// How a run that didn't error ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Complete,
+    /// Some hosts or owners failed and were skipped (`--continue-on-error`).
+    Partial,
+}
+
+impl Outcome {
+    /// [`Outcome::Partial`] when `failed` is non-zero.
+    pub fn from_failures(failed: usize) -> Self {
+        if failed == 0 {
+            Outcome::Complete
+        } else {
+            Outcome::Partial
+        }
+    }
+
+    /// The exit code; `strict` (`--strict`) makes a partial run a failure.
+    pub fn exit_code(self, strict: bool) -> u8 {
+        match (self, strict) {
+            (Outcome::Complete, _) => SUCCESS,
+            (Outcome::Partial, false) => PARTIAL,
+            (Outcome::Partial, true) => STRICT_PARTIAL,
+        }
+    }
+}
+
+/// What kind of error ended a run, judged from its cause chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// A Gerrit host rejected the credentials (or their absence).
+    Auth,
+    /// Connecting, sending or waiting for a response failed.
+    Network,
+    Other,
+}
+
+impl ErrorKind {
+    /// Classify `err` by the first auth or transport error in its chain, so
+    /// context added on the way up doesn't hide it.
+    pub fn classify(err: &anyhow::Error) -> Self {
+        for cause in err.chain() {
+            if let Some(http) = cause.downcast_ref::<HttpError>() {
+                if http.is_auth() {
+                    return ErrorKind::Auth;
+                }
+            }
+            if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+                if e.is_connect() || e.is_timeout() || e.is_request() {
+                    return ErrorKind::Network;
+                }
+            }
+        }
+        ErrorKind::Other
+    }
+
+    pub fn exit_code(self) -> u8 {
+        match self {
+            ErrorKind::Auth => AUTH,
+            ErrorKind::Network => NETWORK,
+            ErrorKind::Other => FAILURE,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gerrit::classify_gerrit_error;
+    use anyhow::Context;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn outcome_codes() {
+        assert_eq!(Outcome::from_failures(0), Outcome::Complete);
+        assert_eq!(Outcome::from_failures(2), Outcome::Partial);
+        assert_eq!(Outcome::Complete.exit_code(false), 0);
+        assert_eq!(Outcome::Complete.exit_code(true), 0);
+        assert_eq!(Outcome::Partial.exit_code(false), 2);
+        assert_eq!(Outcome::Partial.exit_code(true), 3);
+    }
+
+    #[test]
+    fn auth_errors_through_context() {
+        for status in [StatusCode::UNAUTHORIZED, StatusCode::FORBIDDEN] {
+            let err = Err::<(), _>(classify_gerrit_error(status, "denied"))
+                .context("GET https://r.example.com/changes/")
+                .context("fetching from corp")
+                .unwrap_err();
+            assert_eq!(ErrorKind::classify(&err), ErrorKind::Auth);
+            assert_eq!(ErrorKind::classify(&err).exit_code(), 4);
+        }
+    }
+
+    #[test]
+    fn other_http_and_plain_errors_are_generic() {
+        let err = classify_gerrit_error(StatusCode::BAD_REQUEST, "Invalid query");
+        assert_eq!(ErrorKind::classify(&err), ErrorKind::Other);
+        let err = anyhow::anyhow!("unknown theme \"neon\"");
+        assert_eq!(ErrorKind::classify(&err), ErrorKind::Other);
+        assert_eq!(ErrorKind::Other.exit_code(), 1);
+    }
+
+    #[tokio::test]
+    async fn connection_refused_is_network() {
+        // Bind then drop a listener, so the port is (almost certainly) closed.
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let err = reqwest::get(format!("http://{addr}/"))
+            .await
+            .context("GET /")
+            .unwrap_err();
+        assert_eq!(ErrorKind::classify(&err), ErrorKind::Network);
+        assert_eq!(ErrorKind::Network.exit_code(), 5);
+    }
+}
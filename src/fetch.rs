@@ -0,0 +1,152 @@
+//! Concurrent fan-out of one fetch per Gerrit host.
+
+use std::future::Future;
+
+use anyhow::{Context, Result};
+use tokio::task::JoinSet;
+
+use crate::progress::Progress;
+
+/// Outcome of [`fetch_per_host`]: successful results plus, when running with
+/// `continue_on_error`, the hosts that failed.
+pub struct HostResults<T> {
+    /// `(alias, value)` for every host that succeeded, in completion order.
+    pub ok: Vec<(String, T)>,
+    /// `(alias, error)` for every host that failed.  Always empty unless
+    /// `continue_on_error` was set.
+    pub failed: Vec<(String, anyhow::Error)>,
+}
+
+impl<T> HostResults<T> {
+    /// Aliases of the failed hosts, for display in reports.
+    pub fn failed_aliases(&self) -> Vec<String> {
+        self.failed.iter().map(|(a, _)| a.clone()).collect()
+    }
+}
+
+/// Run `fetch(alias, url)` concurrently for every `(alias, url)` in `hosts`.
+///
+/// With `continue_on_error` unset, the first failure aborts the whole run
+/// (the error is annotated with the host alias).  With it set, failures are
+/// logged via `progress` (above any per-host lines still being redrawn) and
+/// collected in [`HostResults::failed`] so the caller can proceed with
+/// whatever succeeded.
+pub async fn fetch_per_host<T, F, Fut>(
+    hosts: &[(String, String)],
+    continue_on_error: bool,
+    progress: &Progress,
+    fetch: F,
+) -> Result<HostResults<T>>
+where
+    F: Fn(String, String) -> Fut,
+    Fut: Future<Output = Result<T>> + Send + 'static,
+    T: Send + 'static,
+{
+    let mut set: JoinSet<(String, Result<T>)> = JoinSet::new();
+
+    for (alias, url) in hosts {
+        let fut = fetch(alias.clone(), url.clone());
+        let alias = alias.clone();
+        set.spawn(async move { (alias, fut.await) });
+    }
+
+    let mut out = HostResults {
+        ok: Vec::new(),
+        failed: Vec::new(),
+    };
+    while let Some(joined) = set.join_next().await {
+        let (alias, result) = joined.context("task panicked")?;
+        match result {
+            Ok(value) => out.ok.push((alias, value)),
+            Err(e) if continue_on_error => {
+                progress.println(&format!("  ⚠ {alias} failed: {e:#}"));
+                out.failed.push((alias, e));
+            }
+            Err(e) => return Err(e.context(format!("fetching from {alias}"))),
+        }
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gerrit::{ChangeInfo, ChangeStatus};
+    use anyhow::bail;
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    fn merged_cl(project: &str, submitted: &str) -> ChangeInfo {
+        let ts = NaiveDate::parse_from_str(submitted, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc();
+        ChangeInfo {
+            id: String::new(),
+            number: 1,
+            subject: String::new(),
+            project: project.to_owned(),
+            branch: "main".to_owned(),
+            status: ChangeStatus::Merged,
+            created: ts,
+            updated: ts,
+            submitted: Some(ts),
+            insertions: 1,
+            deletions: 0,
+            work_in_progress: false,
+            more_changes: None,
+            revision_count: None,
+            messages: vec![],
+            owner: None,
+            labels: Default::default(),
+        }
+    }
+
+    fn three_hosts() -> Vec<(String, String)> {
+        ["chromium", "broken", "go"]
+            .iter()
+            .map(|a| (a.to_string(), format!("https://{a}.example.com")))
+            .collect()
+    }
+
+    fn quiet() -> Progress {
+        Progress::to_writer(std::io::sink(), false)
+    }
+
+    /// Stub fetcher: every host returns one CL except `broken`, which errors.
+    async fn stub_fetch(alias: String, _url: String) -> Result<Vec<ChangeInfo>> {
+        if alias == "broken" {
+            bail!("HTTP 503");
+        }
+        Ok(vec![merged_cl(&alias, "2024-06-10")])
+    }
+
+    #[tokio::test]
+    async fn continue_on_error_keeps_successful_hosts() {
+        let results = fetch_per_host(&three_hosts(), true, &quiet(), stub_fetch)
+            .await
+            .unwrap();
+        assert_eq!(results.failed_aliases(), vec!["broken".to_owned()]);
+
+        let changes: Vec<ChangeInfo> = results.ok.into_iter().flat_map(|(_, c)| c).collect();
+        let now = Utc.with_ymd_and_hms(2024, 6, 12, 12, 0, 0).unwrap();
+        let stats = crate::stats::compute(&changes, &[], now);
+        assert_eq!(stats.total_merged, 2);
+    }
+
+    #[tokio::test]
+    async fn failure_aborts_without_continue_on_error() {
+        let err = fetch_per_host(&three_hosts(), false, &quiet(), stub_fetch)
+            .await
+            .err()
+            .expect("should fail");
+        assert!(
+            format!("{err:#}").contains("broken"),
+            "error names the host"
+        );
+    }
+}
@@ -2,12 +2,29 @@
 //!
 //! All Gerrit REST responses are prefixed with `)]}'\n` (XSSI protection).
 //! This module strips that prefix transparently before deserialising JSON.
+//! Some proxies and mirrors drop the prefix, so by default a bare JSON body
+//! is accepted too; [`GerritClientBuilder::strict_xssi`] restores the hard
+//! requirement.
+//!
+//! With [`GerritClientBuilder::cache`], responses are kept on disk and
+//! revalidated with `ETag` / `If-None-Match` (see [`crate::cache`]).
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use anyhow::{bail, Context, Result};
 use chrono::{DateTime, NaiveDateTime, Utc};
-use reqwest::Client;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::{Certificate, Client, RequestBuilder, StatusCode, Url};
 use serde::{Deserialize, Deserializer};
 
+use crate::cache::{self, Cache, CachedResponse};
+use crate::ratelimit::RateLimiter;
+
 // ---------------------------------------------------------------------------
 // Constants
 // ---------------------------------------------------------------------------
@@ -19,6 +36,18 @@ const XSSI_PREFIX: &str = ")]}'\n";
 /// 500; staying at that maximum minimises round-trips.
 const DEFAULT_PAGE_SIZE: usize = 500;
 
+/// Default for [`GerritClientBuilder::max_pages`]: 100,000 changes at
+/// [`DEFAULT_PAGE_SIZE`], far beyond any real account.
+pub const DEFAULT_MAX_PAGES: usize = 200;
+
+/// `User-Agent` sent unless [`GerritClientBuilder::user_agent`] replaces it.
+pub const DEFAULT_USER_AGENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Edge length in pixels requested from the avatar endpoint; the SVG card
+/// draws it at a smaller size, so this stays sharp on high-DPI screens.
+const AVATAR_SIZE: &str = "64";
+
 /// Gerrit timestamp format: `"2024-03-01 14:22:05.000000000"` (always UTC).
 const GERRIT_TS_FMT: &str = "%Y-%m-%d %H:%M:%S%.f";
 
@@ -26,37 +55,257 @@ const GERRIT_TS_FMT: &str = "%Y-%m-%d %H:%M:%S%.f";
 // Client
 // ---------------------------------------------------------------------------
 
-/// HTTP client bound to a single Gerrit instance.
+/// HTTP client bound to a single Gerrit instance; configure one with
+/// [`GerritClient::builder`].
+///
+/// Cloning is cheap: clones share the underlying connection pool, rate
+/// limiter and gathered warnings.
+#[derive(Clone)]
 pub struct GerritClient {
     /// Base URL with no trailing slash, e.g. `https://chromium-review.googlesource.com`.
     base_url: String,
     http: Client,
     /// Optional HTTP Basic Auth credentials `(username, http-password)`.
     auth: Option<(String, String)>,
+    /// Reject responses that lack the XSSI prefix instead of accepting bare JSON.
+    strict_xssi: bool,
+    /// On-disk response cache, if enabled.
+    cache: Option<Cache>,
+    /// Pages a single query may span before it's treated as runaway
+    /// pagination.
+    max_pages: usize,
+    /// Paces requests to this host when set; unlimited otherwise.
+    limiter: Option<RateLimiter>,
+    /// Problems that didn't fail a request, held for
+    /// [`GerritClient::take_warnings`].
+    warnings: Arc<Mutex<Vec<String>>>,
+    /// Quirks only worth mentioning when debugging, held for
+    /// [`GerritClient::take_debug_notes`].
+    debug_notes: Arc<Mutex<Vec<String>>>,
+}
+
+/// Options for a [`GerritClient`], from [`GerritClient::builder`].
+///
+/// Nothing is applied until [`GerritClientBuilder::build`], which builds the
+/// underlying HTTP client once with every option in place.
+///
+/// ```
+/// use gerritoscope::gerrit::GerritClient;
+///
+/// let client = GerritClient::builder("https://chromium-review.googlesource.com")
+///     .auth("me", "http-password")
+///     .timeout(std::time::Duration::from_secs(30))
+///     .rps(2.0)
+///     .build()?;
+/// # anyhow::Ok(())
+/// ```
+#[derive(Clone)]
+pub struct GerritClientBuilder {
+    base_url: String,
+    auth: Option<(String, String)>,
+    strict_xssi: bool,
+    cache: Option<Cache>,
+    ca_cert: Option<PathBuf>,
+    insecure: bool,
+    timeout: Option<Duration>,
+    max_pages: usize,
+    rps: Option<f64>,
+    user_agent: Option<String>,
+    contact: Option<String>,
+}
+
+impl GerritClientBuilder {
+    /// Attach HTTP Basic Auth credentials (username + Gerrit HTTP password).
+    ///
+    /// Required for private Gerrit instances or authenticated queries.
+    pub fn auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.auth = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Require the XSSI prefix on every response, failing when it's missing.
+    ///
+    /// By default a response without the prefix is accepted as long as it
+    /// looks like JSON.
+    pub fn strict_xssi(mut self, strict: bool) -> Self {
+        self.strict_xssi = strict;
+        self
+    }
+
+    /// Cache responses in `cache`, revalidating with `If-None-Match` when the
+    /// server sent an `ETag`.
+    pub fn cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Trust the PEM root certificate at `path` in addition to the system
+    /// roots, e.g. a corporate CA that signed the Gerrit host's certificate.
+    /// Read by [`build`](Self::build).
+    pub fn ca_cert(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ca_cert = Some(path.into());
+        self
+    }
+
+    /// Skip TLS certificate verification entirely.  Anyone on the network
+    /// path can then read and alter the traffic, credentials included;
+    /// prefer [`ca_cert`](Self::ca_cert).
+    pub fn insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
+    /// Give up on a request that hasn't completed within `timeout`,
+    /// connecting and reading the body included.  No limit by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fail a query that is still reporting more results after `max_pages`
+    /// pages, rather than paging forever against a misbehaving host.
+    pub fn max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    /// Send at most `rps` requests per second to this host, waiting before
+    /// each request as needed.  Cache hits that need no request aren't paced.
+    pub fn rps(mut self, rps: f64) -> Self {
+        self.rps = Some(rps);
+        self
+    }
+
+    /// Send `user_agent` instead of [`DEFAULT_USER_AGENT`].  Checked by
+    /// [`build`](Self::build): it must be non-empty printable ASCII.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Append `contact` (an email or URL) to the `User-Agent` as
+    /// `(+contact)`, so a host's admins can get in touch rather than block
+    /// the client.
+    pub fn contact(mut self, contact: impl Into<String>) -> Self {
+        self.contact = Some(contact.into());
+        self
+    }
+
+    /// The `User-Agent` header value, or an error if it isn't non-empty
+    /// printable ASCII.
+    fn user_agent_value(&self) -> Result<String> {
+        let base = self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+        if let Some(contact) = &self.contact {
+            check_user_agent_part("contact", contact)?;
+        }
+        check_user_agent_part("user agent", base)?;
+        Ok(match &self.contact {
+            Some(contact) => format!("{} (+{})", base.trim(), contact.trim()),
+            None => base.trim().to_owned(),
+        })
+    }
+
+    /// Read the CA certificate, if any, and build the client.
+    pub fn build(self) -> Result<GerritClient> {
+        let mut http = Client::builder().user_agent(self.user_agent_value()?);
+        if let Some(path) = &self.ca_cert {
+            let pem = std::fs::read(path)
+                .with_context(|| format!("reading CA certificate {}", path.display()))?;
+            let cert = Certificate::from_pem(&pem).with_context(|| {
+                format!("parsing CA certificate {} (expected PEM)", path.display())
+            })?;
+            http = http.add_root_certificate(cert);
+        }
+        if self.insecure {
+            http = http.danger_accept_invalid_certs(true);
+        }
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+        // Large accounts' change lists run to megabytes of JSON, which
+        // compresses several-fold; bodies are decoded before XSSI stripping.
+        http = http.gzip(true).brotli(true);
+        let limiter = self.rps.map(RateLimiter::new).transpose()?;
+
+        Ok(GerritClient {
+            base_url: self.base_url.trim_end_matches('/').to_owned(),
+            http: http.build().context("building HTTP client")?,
+            auth: self.auth,
+            strict_xssi: self.strict_xssi,
+            cache: self.cache,
+            max_pages: self.max_pages,
+            limiter,
+            warnings: Arc::default(),
+            debug_notes: Arc::default(),
+        })
+    }
+}
+
+/// Reject an empty or non-ASCII `User-Agent` part (`what` names it in the
+/// error); control characters would be invalid in a header anyway.
+fn check_user_agent_part(what: &str, value: &str) -> Result<()> {
+    if value.trim().is_empty() {
+        bail!("{what} is empty");
+    }
+    if let Some(c) = value.chars().find(|&c| !(c.is_ascii_graphic() || c == ' ')) {
+        bail!("{what} {value:?} contains {c:?}; only printable ASCII is allowed");
+    }
+    Ok(())
 }
 
 impl GerritClient {
-    /// Construct a client for the given base URL.
+    /// Start configuring a client for the given base URL.
     ///
     /// `base_url` may optionally end with a `/`; it is normalised away.
-    pub fn new(base_url: impl Into<String>) -> Result<Self> {
-        let http = Client::builder()
-            .user_agent(concat!(
-                env!("CARGO_PKG_NAME"),
-                "/",
-                env!("CARGO_PKG_VERSION")
-            ))
-            .build()?;
-        Ok(Self {
-            base_url: base_url.into().trim_end_matches('/').to_owned(),
-            http,
+    pub fn builder(base_url: impl Into<String>) -> GerritClientBuilder {
+        GerritClientBuilder {
+            base_url: base_url.into(),
             auth: None,
-        })
+            strict_xssi: false,
+            cache: None,
+            ca_cert: None,
+            insecure: false,
+            timeout: None,
+            max_pages: DEFAULT_MAX_PAGES,
+            rps: None,
+            user_agent: None,
+            contact: None,
+        }
     }
 
-    /// Attach HTTP Basic Auth credentials (username + Gerrit HTTP password).
-    ///
-    /// Required for private Gerrit instances or authenticated queries.
+    /// A client for `base_url` with every option at its default; shorthand
+    /// for `GerritClient::builder(base_url).build()`.
+    pub fn new(base_url: impl Into<String>) -> Result<Self> {
+        Self::builder(base_url).build()
+    }
+
+    /// Drain the warnings gathered so far, such as failed cache writes, for
+    /// the caller to report; the client never prints them itself.
+    pub fn take_warnings(&self) -> Vec<String> {
+        std::mem::take(&mut *self.warnings.lock().unwrap())
+    }
+
+    /// Drain the debug notes gathered so far, such as a host answering
+    /// without the XSSI prefix; each distinct note is kept once.
+    pub fn take_debug_notes(&self) -> Vec<String> {
+        std::mem::take(&mut *self.debug_notes.lock().unwrap())
+    }
+
+    /// [`strip_xssi`] under this client's strictness, noting a bare JSON
+    /// body (a proxy may have stripped the prefix).
+    fn strip_xssi<'a>(&self, s: &'a str) -> Result<&'a str> {
+        let json = strip_xssi(s, self.strict_xssi)?;
+        if json.len() == s.len() {
+            let note = "response is missing the Gerrit XSSI prefix; parsed as bare JSON";
+            let mut notes = self.debug_notes.lock().unwrap();
+            if !notes.iter().any(|n| n == note) {
+                notes.push(note.to_owned());
+            }
+        }
+        Ok(json)
+    }
+
+    /// Attach HTTP Basic Auth credentials; see [`GerritClientBuilder::auth`].
     pub fn with_auth(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
         self.auth = Some((username.into(), password.into()));
         self
@@ -72,73 +321,131 @@ impl GerritClient {
     /// Results are returned in newest-first order (Gerrit default).
     pub async fn fetch_changes(&self, query: &ChangeQuery) -> Result<Vec<ChangeInfo>> {
         let mut all: Vec<ChangeInfo> = Vec::new();
-        let mut start = 0usize;
-
-        loop {
-            let page = self
-                .fetch_changes_page(query, start, DEFAULT_PAGE_SIZE)
-                .await?;
-
-            // The `_more_changes` flag is set only on the *last* item of a
-            // page when there are additional results beyond this page.
-            let more = page.last().and_then(|c| c.more_changes).unwrap_or(false);
-            let n = page.len();
-            all.extend(page);
-
-            if !more || n == 0 {
-                break;
-            }
-            start += n;
-        }
-
+        self.fetch_changes_each(query, |page| all.extend(page))
+            .await?;
         Ok(all)
     }
 
+    /// Like [`GerritClient::fetch_changes`], but hands each page to
+    /// `on_page` as it arrives instead of collecting them, so callers can
+    /// fold very large result sets without holding them all in memory.
+    ///
+    /// Returns the total number of changes seen.
+    pub async fn fetch_changes_each<F>(&self, query: &ChangeQuery, mut on_page: F) -> Result<usize>
+    where
+        F: FnMut(Vec<ChangeInfo>),
+    {
+        paginate(
+            &self.base_url,
+            self.max_pages,
+            |start| self.fetch_changes_page(query, start, DEFAULT_PAGE_SIZE),
+            |page| {
+                on_page(page);
+                true
+            },
+        )
+        .await
+    }
+
     /// Fetch all changes that `query.reviewer` reviewed (but didn't author),
     /// returning one [`ReviewEvent`] per change (the earliest message from the
     /// reviewer, or `change.updated` as fallback).
-    pub async fn fetch_review_events(&self, query: &ReviewerQuery) -> Result<Vec<ReviewEvent>> {
-        let mut all: Vec<ReviewEvent> = Vec::new();
-        let mut start = 0usize;
-
-        loop {
-            let page = self
-                .fetch_review_page(query, start, DEFAULT_PAGE_SIZE)
-                .await?;
-
-            let more = page.last().and_then(|c| c.more_changes).unwrap_or(false);
-            let n = page.len();
-
-            for change in &page {
-                let ts = if query.reviewer.contains('@') {
-                    // Try to find the earliest message authored by the reviewer.
-                    let earliest = change
-                        .messages
-                        .iter()
-                        .filter(|m| {
-                            m.author.as_ref().and_then(|a| a.email.as_deref())
-                                == Some(query.reviewer.as_str())
-                        })
-                        .map(|m| m.date)
-                        .min();
-                    earliest.unwrap_or(change.updated)
-                } else {
-                    change.updated
-                };
+    ///
+    /// A change that shows up on more than one page (pagination can overlap
+    /// when `updated` shifts mid-fetch) still yields a single event.  With
+    /// [`ReviewerQuery::limit`] set, paging stops once that many events have
+    /// been collected.
+    pub async fn fetch_review_events(&self, query: &ReviewerQuery) -> Result<ReviewEvents> {
+        collect_review_events(&self.base_url, self.max_pages, query, |start| {
+            self.fetch_review_page(query, start, DEFAULT_PAGE_SIZE)
+        })
+        .await
+    }
 
-                all.push(ReviewEvent {
-                    timestamp: ts,
-                    project: change.project.clone(),
-                });
-            }
+    /// Fetch a single change by number from the change detail endpoint.
+    pub async fn fetch_change(&self, number: u64) -> Result<ChangeInfo> {
+        let url = self.changes_url(&format!("{number}/"));
+        let text = self.get_text(&url, &[("o", "DETAILED_LABELS")]).await?;
+        let json = self.strip_xssi(&text)?;
 
-            if !more || n == 0 {
-                break;
-            }
-            start += n;
-        }
+        serde_json::from_str(json).with_context(|| format!("deserialising change {number}"))
+    }
 
-        Ok(all)
+    /// Count merged changes in `project` between `after` and `before`,
+    /// per owner, to rank `owner` among the project's contributors.
+    ///
+    /// Pages are followed until `cap` changes have been counted; past that
+    /// the result is marked [`ProjectActivity::approximate`].
+    pub async fn project_activity(
+        &self,
+        project: &str,
+        after: chrono::NaiveDate,
+        before: chrono::NaiveDate,
+        owner: &str,
+        cap: usize,
+    ) -> Result<ProjectActivity> {
+        let url = self.changes_url("");
+        let q = format!(
+            "project:{project} status:merged after:{} before:{}",
+            after.format("%Y-%m-%d"),
+            before.format("%Y-%m-%d")
+        );
+        let limit = DEFAULT_PAGE_SIZE.to_string();
+        let (url, q, limit) = (&url, &q, &limit);
+        let mut activity = ProjectActivity::default();
+
+        paginate(
+            &self.base_url,
+            self.max_pages,
+            |start| async move {
+                let start_s = start.to_string();
+                let text = self
+                    .get_text(
+                        url,
+                        &[
+                            ("q", q.as_str()),
+                            ("n", limit),
+                            ("start", &start_s),
+                            ("o", "DETAILED_ACCOUNTS"),
+                        ],
+                    )
+                    .await?;
+                let json = self.strip_xssi(&text)?;
+                serde_json::from_str(json).with_context(|| {
+                    format!("deserialising /changes/ page for project {project} (start={start})")
+                })
+            },
+            |page| activity.add_page(&page, owner, cap),
+        )
+        .await?;
+
+        Ok(activity)
+    }
+
+    /// Fetch `owner`'s display name from `/accounts/<owner>/detail` and
+    /// their avatar image from `/accounts/<owner>/avatar`.
+    ///
+    /// The avatar is best-effort: hosts without an avatar plugin answer 404,
+    /// and that (or any other avatar failure) leaves `avatar_png` empty
+    /// rather than failing the whole lookup.
+    pub async fn fetch_account(&self, owner: &str) -> Result<AccountCard> {
+        let url = self.accounts_url(owner, "detail")?;
+        let text = self.get_text(&url, &[]).await?;
+        let json = self.strip_xssi(&text)?;
+        let detail: AccountDetail =
+            serde_json::from_str(json).with_context(|| format!("deserialising account {owner}"))?;
+
+        let avatar_url = self.accounts_url(owner, "avatar")?;
+        let avatar_png = self
+            .get_bytes(&avatar_url, &[("s", AVATAR_SIZE)])
+            .await
+            .ok()
+            .filter(|bytes| !bytes.is_empty());
+
+        Ok(AccountCard {
+            name: detail.name.unwrap_or_else(|| owner.to_owned()),
+            avatar_png,
+        })
     }
 
     // -----------------------------------------------------------------------
@@ -151,31 +458,21 @@ impl GerritClient {
         start: usize,
         limit: usize,
     ) -> Result<Vec<ChangeInfo>> {
-        let url = format!("{}/changes/", self.base_url);
+        let url = self.changes_url("");
         let q = query.to_query_string();
+        let (limit, start) = (limit.to_string(), start.to_string());
 
-        let mut req = self.http.get(&url).query(&[
+        let mut params = vec![
             ("q", q.as_str()),
-            ("n", &limit.to_string()),
-            ("start", &start.to_string()),
-        ]);
-
-        if let Some((user, pass)) = &self.auth {
-            req = req.basic_auth(user, Some(pass));
-        }
-
-        let response = req.send().await.with_context(|| format!("GET {url}"))?;
-
-        let status = response.status();
-        if !status.is_success() {
-            // Consume the body for a useful error message, but don't fail if
-            // reading it errors out.
-            let body = response.text().await.unwrap_or_default();
-            bail!("Gerrit returned HTTP {status} for {url}: {body}");
+            ("n", &limit),
+            ("start", &start),
+            ("o", "DETAILED_LABELS"),
+        ];
+        if query.revisions {
+            params.push(("o", "ALL_REVISIONS"));
         }
-
-        let text = response.text().await?;
-        let json = strip_xssi(&text)?;
+        let text = self.get_text(&url, &params).await?;
+        let json = self.strip_xssi(&text)?;
 
         serde_json::from_str(json)
             .with_context(|| format!("deserialising /changes/ page (start={start})"))
@@ -187,47 +484,395 @@ impl GerritClient {
         start: usize,
         limit: usize,
     ) -> Result<Vec<ChangeInfo>> {
-        let url = format!("{}/changes/", self.base_url);
+        let url = self.changes_url("");
         let q = query.to_query_string();
+        let (limit, start) = (limit.to_string(), start.to_string());
+
+        let text = self
+            .get_text(
+                &url,
+                &[
+                    ("q", q.as_str()),
+                    ("n", &limit),
+                    ("start", &start),
+                    ("o", "MESSAGES"),
+                    // Message authors then carry username and email, not
+                    // just `_account_id`.
+                    ("o", "DETAILED_ACCOUNTS"),
+                ],
+            )
+            .await?;
+        let json = self.strip_xssi(&text)?;
 
-        let mut req = self.http.get(&url).query(&[
-            ("q", q.as_str()),
-            ("n", &limit.to_string()),
-            ("start", &start.to_string()),
-            ("o", "MESSAGES"),
-        ]);
+        serde_json::from_str(json)
+            .with_context(|| format!("deserialising /changes/ (reviewer) page (start={start})"))
+    }
+
+    /// Path prefix for REST endpoints: `/a` when authenticating, which is
+    /// where Gerrit serves authenticated requests (many hosts reject
+    /// credentials, or anonymous reads, on the bare paths).  Empty otherwise.
+    fn path_prefix(&self) -> &'static str {
+        if self.auth.is_some() {
+            "/a"
+        } else {
+            ""
+        }
+    }
+
+    /// `{base}{prefix}/changes/{rest}`.
+    fn changes_url(&self, rest: &str) -> String {
+        format!("{}{}/changes/{rest}", self.base_url, self.path_prefix())
+    }
+
+    /// `{base}{prefix}/accounts/{owner}/{endpoint}`, with `owner`
+    /// percent-encoded as a single path segment.
+    fn accounts_url(&self, owner: &str, endpoint: &str) -> Result<String> {
+        let mut url = Url::parse(&self.base_url)
+            .with_context(|| format!("invalid Gerrit URL {:?}", self.base_url))?;
+        {
+            let Ok(mut segments) = url.path_segments_mut() else {
+                bail!("invalid Gerrit URL {:?}", self.base_url);
+            };
+            segments.pop_if_empty();
+            if self.auth.is_some() {
+                segments.push("a");
+            }
+            segments.extend(["accounts", owner, endpoint]);
+        }
+        Ok(url.into())
+    }
+
+    /// Wait for the rate limiter, if any, before sending a request.
+    async fn pace(&self) {
+        if let Some(limiter) = &self.limiter {
+            limiter.acquire().await;
+        }
+    }
+
+    /// Start a GET for `url` with `query`, adding Basic Auth when configured.
+    fn get(&self, url: &str, query: &[(&str, &str)]) -> RequestBuilder {
+        let req = self.http.get(url).query(query);
+        match &self.auth {
+            Some((user, pass)) => req.basic_auth(user, Some(pass)),
+            None => req,
+        }
+    }
+
+    /// GET `url` and return the raw body bytes, bypassing the cache (it only
+    /// stores text).  Redirects, e.g. to an avatar CDN, are followed.
+    async fn get_bytes(&self, url: &str, query: &[(&str, &str)]) -> Result<Vec<u8>> {
+        self.pace().await;
+        let response = self
+            .get(url, query)
+            .send()
+            .await
+            .with_context(|| format!("GET {url}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(classify_gerrit_error(status, &body).context(format!("GET {url}")));
+        }
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// GET `url` with `query` and return the raw body (XSSI prefix intact),
+    /// going through the cache when one is configured.
+    async fn get_text(&self, url: &str, query: &[(&str, &str)]) -> Result<String> {
+        let key = cache::key(url, query);
+        let cached = self.cache.as_ref().and_then(|c| c.get(&key));
+        if let (Some(cache), Some(entry)) = (&self.cache, &cached) {
+            if cache.is_fresh(entry, Utc::now()) {
+                return Ok(entry.body.clone());
+            }
+        }
 
-        if let Some((user, pass)) = &self.auth {
-            req = req.basic_auth(user, Some(pass));
+        self.pace().await;
+        let mut req = self.get(url, query);
+        if let Some(etag) = cached.as_ref().and_then(|e| e.etag.as_deref()) {
+            req = req.header(IF_NONE_MATCH, etag);
         }
 
         let response = req.send().await.with_context(|| format!("GET {url}"))?;
 
         let status = response.status();
+        if status == StatusCode::NOT_MODIFIED {
+            if let Some(entry) = cached {
+                return Ok(entry.body);
+            }
+        }
         if !status.is_success() {
+            // Consume the body for a useful error message, but don't fail if
+            // reading it errors out.
             let body = response.text().await.unwrap_or_default();
-            bail!("Gerrit returned HTTP {status} for {url}: {body}");
+            return Err(classify_gerrit_error(status, &body).context(format!("GET {url}")));
         }
 
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned);
         let text = response.text().await?;
-        let json = strip_xssi(&text)?;
 
-        serde_json::from_str(json)
-            .with_context(|| format!("deserialising /changes/ (reviewer) page (start={start})"))
+        if let Some(cache) = &self.cache {
+            let entry = CachedResponse {
+                etag,
+                fetched_at: Utc::now(),
+                body: text,
+            };
+            // A cache write failure shouldn't sink the fetch.
+            if let Err(e) = cache.put(&key, &entry) {
+                self.warnings.lock().unwrap().push(format!("{e:#}"));
+            }
+            return Ok(entry.body);
+        }
+        Ok(text)
     }
 }
 
 // ---------------------------------------------------------------------------
-// XSSI stripping
+// HTTP errors
 // ---------------------------------------------------------------------------
 
-fn strip_xssi(s: &str) -> Result<&str> {
-    s.strip_prefix(XSSI_PREFIX).with_context(|| {
-        format!(
-            "response is missing the Gerrit XSSI prefix; got {:?}",
-            s.chars().take(12).collect::<String>()
+/// Longest error detail quoted from a response body.
+const MAX_ERROR_DETAIL: usize = 200;
+
+/// A non-success response from Gerrit, as built by
+/// [`classify_gerrit_error`].  Kept as a type so callers can tell
+/// authentication failures apart (see [`crate::exit::ErrorKind`]).
+#[derive(Debug)]
+pub struct HttpError {
+    pub status: StatusCode,
+    message: String,
+}
+
+impl HttpError {
+    /// True for 401 and 403, which nearly always mean missing or wrong
+    /// credentials.
+    pub fn is_auth(&self) -> bool {
+        matches!(
+            self.status,
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
         )
+    }
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for HttpError {}
+
+/// Turn a non-success response into an error worth showing.
+///
+/// Gerrit's own errors are a plaintext line ("Invalid query: …"), so the
+/// first non-empty line of the body is quoted.  HTML error pages (proxies,
+/// login redirects) are reduced to their `<title>`.  On 401/403 the message
+/// suggests `--username`/`--password`, since that is nearly always the fix.
+pub fn classify_gerrit_error(status: StatusCode, body: &str) -> anyhow::Error {
+    let mut err = HttpError {
+        status,
+        message: format!("Gerrit returned HTTP {status}"),
+    };
+    if let Some(detail) = error_detail(body) {
+        err.message.push_str(&format!(": {detail}"));
+    }
+    if err.is_auth() {
+        err.message.push_str(
+            " (this host may need sign-in: pass --username and --password \
+             with an HTTP password from the host's settings page)",
+        );
+    }
+    anyhow::Error::new(err)
+}
+
+/// The human-readable part of an error body, if any.
+fn error_detail(body: &str) -> Option<String> {
+    let body = body.trim();
+    let line = if body.starts_with('<') {
+        let lower = body.to_ascii_lowercase();
+        let start = lower.find("<title>")? + "<title>".len();
+        let end = start + lower[start..].find("</title>")?;
+        body[start..end].trim()
+    } else {
+        body.lines().map(str::trim).find(|l| !l.is_empty())?
+    };
+    if line.is_empty() {
+        return None;
+    }
+    Some(match line.char_indices().nth(MAX_ERROR_DETAIL) {
+        Some((cut, _)) => format!("{}…", &line[..cut]),
+        None => line.to_owned(),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Pagination
+// ---------------------------------------------------------------------------
+
+/// Whether another page may follow `page`, fetched with `n=limit`.
+///
+/// Current Gerrit sets `_more_changes` on the last item only, but older
+/// versions set it on every item or on none at all, so any flagged item
+/// counts, and so does a completely full page.  Only a short, unflagged page
+/// is known to be the end.
+fn has_more(page: &[ChangeInfo], limit: usize) -> bool {
+    page.iter().any(|c| c.more_changes == Some(true)) || (limit > 0 && page.len() >= limit)
+}
+
+/// Drive a paged `/changes/` query: `fetch(start)` returns the page at
+/// offset `start`, and `on_page` consumes it, returning `false` to stop
+/// early.  Returns the number of changes seen.
+///
+/// Guards against hosts whose pagination never ends: it fails, naming
+/// `host`, when a page repeats the previous one (the server ignoring
+/// `start`), or when more results are still reported after `max_pages`.
+async fn paginate<F, Fut>(
+    host: &str,
+    max_pages: usize,
+    mut fetch: F,
+    mut on_page: impl FnMut(Vec<ChangeInfo>) -> bool,
+) -> Result<usize>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<Vec<ChangeInfo>>>,
+{
+    let mut start = 0usize;
+    let mut prev_first: Option<u64> = None;
+
+    for _ in 0..max_pages {
+        let page = fetch(start).await?;
+        let more = has_more(&page, DEFAULT_PAGE_SIZE);
+        let n = page.len();
+
+        let first = page.first().map(|c| c.number);
+        if first.is_some() && first == prev_first {
+            bail!(
+                "{host} returned the same page twice (start={start}); \
+                 it appears to ignore the `start` offset"
+            );
+        }
+        prev_first = first;
+
+        let keep_going = on_page(page);
+        start += n;
+        if !keep_going || !more || n == 0 {
+            return Ok(start);
+        }
+    }
+
+    bail!(
+        "{host} still reports more changes after {max_pages} pages ({start} changes); \
+         stopping in case its pagination never ends"
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Review events
+// ---------------------------------------------------------------------------
+
+/// Append one [`ReviewEvent`] per change in `page` to `out`.
+///
+/// `seen` maps change number → index in `out`; a change already recorded is
+/// not added again, but keeps whichever timestamp is earlier.
+fn add_review_events(
+    page: &[ChangeInfo],
+    reviewer: &str,
+    seen: &mut HashMap<u64, usize>,
+    out: &mut Vec<ReviewEvent>,
+) {
+    for change in page {
+        let ts = review_timestamp(change, reviewer);
+        match seen.get(&change.number) {
+            Some(&i) => out[i].timestamp = out[i].timestamp.min(ts),
+            None => {
+                seen.insert(change.number, out.len());
+                out.push(ReviewEvent {
+                    timestamp: ts,
+                    project: change.project.clone(),
+                    insertions: change.insertions,
+                    deletions: change.deletions,
+                    author: change
+                        .owner
+                        .as_ref()
+                        .and_then(AccountInfo::label)
+                        .map(str::to_owned),
+                });
+            }
+        }
+    }
+}
+
+/// Page through `query`'s changes with `fetch` (see [`paginate`]), turning
+/// them into review events and stopping at [`ReviewerQuery::limit`].
+async fn collect_review_events<F, Fut>(
+    host: &str,
+    max_pages: usize,
+    query: &ReviewerQuery,
+    fetch: F,
+) -> Result<ReviewEvents>
+where
+    F: FnMut(usize) -> Fut,
+    Fut: Future<Output = Result<Vec<ChangeInfo>>>,
+{
+    let mut events: Vec<ReviewEvent> = Vec::new();
+    let mut seen: HashMap<u64, usize> = HashMap::new();
+    let mut capped = false;
+
+    paginate(host, max_pages, fetch, |page| {
+        let more = has_more(&page, DEFAULT_PAGE_SIZE);
+        add_review_events(&page, &query.reviewer, &mut seen, &mut events);
+        let Some(limit) = query.limit else {
+            return true;
+        };
+        if events.len() < limit {
+            return true;
+        }
+        // Pages are newest first, so the events dropped are the oldest.
+        capped = events.len() > limit || more;
+        events.truncate(limit);
+        false
     })
+    .await?;
+
+    Ok(ReviewEvents { events, capped })
+}
+
+/// When `reviewer` first reviewed `change`: their earliest message, matched
+/// by email, username, or account id (see [`AccountInfo::matches`]), else
+/// `change.updated`.
+fn review_timestamp(change: &ChangeInfo, reviewer: &str) -> DateTime<Utc> {
+    change
+        .messages
+        .iter()
+        .filter(|m| m.author.as_ref().is_some_and(|a| a.matches(reviewer)))
+        .map(|m| m.date)
+        .min()
+        .unwrap_or(change.updated)
+}
+
+// ---------------------------------------------------------------------------
+// XSSI stripping
+// ---------------------------------------------------------------------------
+
+/// Strip the XSSI prefix from a response body.
+///
+/// In lenient mode (`strict == false`) a body without the prefix is passed
+/// through unchanged if it starts with `[` or `{`; anything else is an error.
+fn strip_xssi(s: &str, strict: bool) -> Result<&str> {
+    if let Some(json) = s.strip_prefix(XSSI_PREFIX) {
+        return Ok(json);
+    }
+    let looks_like_json = s.trim_start().starts_with(['[', '{']);
+    if !strict && looks_like_json {
+        return Ok(s);
+    }
+    bail!(
+        "response is missing the Gerrit XSSI prefix; got {:?}",
+        s.chars().take(12).collect::<String>()
+    )
 }
 
 // ---------------------------------------------------------------------------
@@ -247,10 +892,20 @@ fn strip_xssi(s: &str) -> Result<&str> {
 pub struct ChangeQuery {
     /// Account identifier: email address, username, or the special token `self`.
     pub owner: String,
+    /// More addresses of the same person, OR-combined with `owner`.
+    pub owner_emails: Vec<String>,
     /// If set, restrict results to changes with this status.
     pub status: Option<ChangeStatus>,
     /// If set, only return changes whose creation date is on or after this date.
     pub after: Option<chrono::NaiveDate>,
+    /// Also request `ALL_REVISIONS` to fill in [`ChangeInfo::revision_count`].
+    /// Enlarges every response.
+    pub revisions: bool,
+    /// If non-empty, only return changes carrying any of these hashtags
+    /// (without the leading `#`).
+    pub hashtags: Vec<String>,
+    /// Verbatim query from [`ChangeQuery::raw`]; replaces every filter above.
+    pub raw: Option<String>,
 }
 
 impl ChangeQuery {
@@ -258,8 +913,22 @@ impl ChangeQuery {
     pub fn new(owner: impl Into<String>) -> Self {
         Self {
             owner: owner.into(),
+            owner_emails: Vec::new(),
             status: None,
             after: None,
+            revisions: false,
+            hashtags: Vec::new(),
+            raw: None,
+        }
+    }
+
+    /// A query sent as `q` exactly as written (`--raw-query`), for Gerrit
+    /// search syntax the builder doesn't cover.  `owner`, `status`, `after`
+    /// and hashtags don't apply; [`ChangeQuery::with_revisions`] still does.
+    pub fn raw(query: impl Into<String>) -> Self {
+        Self {
+            raw: Some(query.into()),
+            ..Self::new("")
         }
     }
 
@@ -275,10 +944,46 @@ impl ChangeQuery {
         self
     }
 
+    /// Request every patch set so [`ChangeInfo::revision_count`] is set.
+    pub fn with_revisions(mut self, revisions: bool) -> Self {
+        self.revisions = revisions;
+        self
+    }
+
+    /// Also match changes tagged `hashtag`; several hashtags are OR-combined.
+    /// A leading `#` is dropped and duplicates are ignored.
+    pub fn with_hashtag(mut self, hashtag: &str) -> Self {
+        let tag = hashtag.strip_prefix('#').unwrap_or(hashtag);
+        if !self.hashtags.iter().any(|t| t == tag) {
+            self.hashtags.push(tag.to_owned());
+        }
+        self
+    }
+
+    /// Also match changes owned by `email` (`--owner-email`), for someone
+    /// whose CLs are split across addresses; OR-combined with `owner`.
+    /// Duplicates are ignored.
+    pub fn with_owner_email(mut self, email: &str) -> Self {
+        if email != self.owner && !self.owner_emails.iter().any(|e| e == email) {
+            self.owner_emails.push(email.to_owned());
+        }
+        self
+    }
+
     /// Encode as a Gerrit query string (space-separated predicates).
     /// `reqwest` will percent-encode the spaces when building the URL.
     fn to_query_string(&self) -> String {
-        let mut parts = vec![format!("owner:{}", self.owner)];
+        if let Some(ref raw) = self.raw {
+            return raw.clone();
+        }
+        let owners: Vec<String> = std::iter::once(&self.owner)
+            .chain(&self.owner_emails)
+            .map(|o| format!("owner:{o}"))
+            .collect();
+        let mut parts = vec![match owners.as_slice() {
+            [one] => one.clone(),
+            many => format!("({})", many.join(" OR ")),
+        }];
 
         if let Some(status) = self.status {
             parts.push(format!("is:{}", status.query_predicate()));
@@ -288,6 +993,17 @@ impl ChangeQuery {
             parts.push(format!("after:{}", date.format("%Y-%m-%d")));
         }
 
+        let hashtags: Vec<String> = self
+            .hashtags
+            .iter()
+            .map(|t| format!("hashtag:\"{t}\""))
+            .collect();
+        match hashtags.as_slice() {
+            [] => {}
+            [one] => parts.push(one.clone()),
+            many => parts.push(format!("({})", many.join(" OR "))),
+        }
+
         parts.join(" ")
     }
 }
@@ -295,19 +1011,64 @@ impl ChangeQuery {
 /// A Gerrit reviewer search query: finds CLs the user reviewed but didn't author.
 #[derive(Debug, Clone)]
 pub struct ReviewerQuery {
-    /// Account identifier: email address or username.
+    /// Account identifier: email address, username, or numeric account id.
     pub reviewer: String,
     /// If set, only return changes updated on or after this date.
     pub after: Option<chrono::NaiveDate>,
+    /// Ways of being involved that count; a change matching any of them is
+    /// returned.  Never empty.
+    pub roles: Vec<ReviewRole>,
+    /// If set, stop after this many changes.  Gerrit lists the most
+    /// recently updated first, so these are the newest.
+    pub limit: Option<usize>,
 }
 
-impl ReviewerQuery {
-    /// Create a reviewer query for `reviewer`.
-    pub fn new(reviewer: impl Into<String>) -> Self {
-        Self {
-            reviewer: reviewer.into(),
-            after: None,
-        }
+/// How a user takes part in someone else's change, selected with
+/// `--review-role`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewRole {
+    /// Added as a reviewer (`reviewer:`).
+    Reviewer,
+    /// CC'd (`cc:`).
+    Cc,
+    /// In the attention set (`attention:`).  Gerrit only tracks the current
+    /// attention set, so older changes the user has since been removed from
+    /// don't match.
+    Attention,
+}
+
+impl ReviewRole {
+    fn predicate(self) -> &'static str {
+        match self {
+            ReviewRole::Reviewer => "reviewer",
+            ReviewRole::Cc => "cc",
+            ReviewRole::Attention => "attention",
+        }
+    }
+}
+
+impl FromStr for ReviewRole {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "reviewer" => Ok(ReviewRole::Reviewer),
+            "cc" => Ok(ReviewRole::Cc),
+            "attention" => Ok(ReviewRole::Attention),
+            other => bail!("unknown review role {other:?}; valid names: reviewer, cc, attention"),
+        }
+    }
+}
+
+impl ReviewerQuery {
+    /// Create a reviewer query for `reviewer`.
+    pub fn new(reviewer: impl Into<String>) -> Self {
+        Self {
+            reviewer: reviewer.into(),
+            after: None,
+            roles: vec![ReviewRole::Reviewer],
+            limit: None,
+        }
     }
 
     /// Only return changes updated on or after `date`.
@@ -316,12 +1077,39 @@ impl ReviewerQuery {
         self
     }
 
+    /// Stop fetching after `limit` changes.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Match changes where the user holds any of `roles`, instead of only
+    /// [`ReviewRole::Reviewer`].  Duplicates are dropped; an empty list
+    /// leaves the roles unchanged.
+    pub fn with_roles(mut self, roles: &[ReviewRole]) -> Self {
+        if !roles.is_empty() {
+            self.roles.clear();
+            for &role in roles {
+                if !self.roles.contains(&role) {
+                    self.roles.push(role);
+                }
+            }
+        }
+        self
+    }
+
     /// Encode as a Gerrit query string.
     fn to_query_string(&self) -> String {
-        let mut parts = vec![
-            format!("reviewer:{}", self.reviewer),
-            format!("-owner:{}", self.reviewer),
-        ];
+        let roles: Vec<String> = self
+            .roles
+            .iter()
+            .map(|r| format!("{}:{}", r.predicate(), self.reviewer))
+            .collect();
+        let involvement = match roles.as_slice() {
+            [one] => one.clone(),
+            many => format!("({})", many.join(" OR ")),
+        };
+        let mut parts = vec![involvement, format!("-owner:{}", self.reviewer)];
 
         if let Some(date) = self.after {
             parts.push(format!("after:{}", date.format("%Y-%m-%d")));
@@ -342,6 +1130,10 @@ pub enum ChangeStatus {
     New,
     Merged,
     Abandoned,
+    /// Merged or abandoned — a query filter only; returned changes carry
+    /// their real status.
+    #[serde(skip_deserializing)]
+    Closed,
 }
 
 impl ChangeStatus {
@@ -351,14 +1143,124 @@ impl ChangeStatus {
             ChangeStatus::New => "open",
             ChangeStatus::Merged => "merged",
             ChangeStatus::Abandoned => "abandoned",
+            ChangeStatus::Closed => "closed",
         }
     }
 }
 
-/// Author information within a change message.
-#[derive(Debug, Deserialize)]
+/// Account information: a change owner, message author, or voter.
+#[derive(Debug, Default, Deserialize)]
 pub struct AccountInfo {
+    /// Numeric account ID — the only identifier Gerrit always populates.
+    #[serde(rename = "_account_id", default)]
+    pub account_id: Option<u64>,
     pub email: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+}
+
+impl AccountInfo {
+    /// Whether `ident` (an email address, username, or numeric account id)
+    /// names this account.
+    pub fn matches(&self, ident: &str) -> bool {
+        self.email.as_deref() == Some(ident)
+            || self.username.as_deref() == Some(ident)
+            || self.account_id.is_some_and(|id| ident.parse() == Ok(id))
+    }
+
+    /// How to name this account in reports: its email, else its username.
+    /// `None` for accounts that expose neither, e.g. deleted or anonymized
+    /// ones that only carry an account id.
+    pub fn label(&self) -> Option<&str> {
+        self.email
+            .as_deref()
+            .or(self.username.as_deref())
+            .filter(|s| !s.is_empty())
+    }
+}
+
+/// Merged changes in one project over a window, from
+/// [`GerritClient::project_activity`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectActivity {
+    /// Merged changes counted.
+    pub merged: usize,
+    /// Merged changes per owner account ID.
+    pub per_owner: HashMap<u64, usize>,
+    /// Account ID of the owner being ranked, once one of their changes has
+    /// been seen.
+    pub own_account: Option<u64>,
+    /// Counting stopped at the cap, so the counts are lower bounds.
+    pub approximate: bool,
+}
+
+impl ProjectActivity {
+    /// Tally one page of changes, identifying `owner`'s account along the
+    /// way.  Returns `false` once `cap` changes have been counted; the rest
+    /// of the page is dropped and the result marked approximate.
+    fn add_page(&mut self, page: &[ChangeInfo], owner: &str, cap: usize) -> bool {
+        for change in page {
+            if self.merged >= cap {
+                self.approximate = true;
+                return false;
+            }
+            let Some(account) = &change.owner else {
+                continue;
+            };
+            let Some(id) = account.account_id else {
+                continue;
+            };
+            self.merged += 1;
+            *self.per_owner.entry(id).or_insert(0) += 1;
+            if self.own_account.is_none() && account.matches(owner) {
+                self.own_account = Some(id);
+            }
+        }
+        true
+    }
+
+    /// `(rank, contributors)` for the ranked owner: 1 + the number of owners
+    /// with strictly more changes, out of all distinct owners.  `None` when
+    /// none of their changes were counted.
+    pub fn own_rank(&self) -> Option<(usize, usize)> {
+        let own = self.per_owner[&self.own_account?];
+        let ahead = self.per_owner.values().filter(|&&n| n > own).count();
+        Some((ahead + 1, self.per_owner.len()))
+    }
+}
+
+/// The `/accounts/<id>/detail` fields gerritoscope uses.
+#[derive(Debug, Deserialize)]
+struct AccountDetail {
+    #[serde(default)]
+    name: Option<String>,
+}
+
+/// Display name and avatar for the SVG card header, from
+/// [`GerritClient::fetch_account`].
+#[derive(Debug, Clone)]
+pub struct AccountCard {
+    /// The account's full name, or the queried owner when it has none.
+    pub name: String,
+    /// Raw avatar image, or `None` when the host serves no avatar.
+    pub avatar_png: Option<Vec<u8>>,
+}
+
+/// One reviewer's vote on a label, from the `DETAILED_LABELS` `all` list.
+#[derive(Debug, Deserialize)]
+pub struct ApprovalInfo {
+    #[serde(flatten)]
+    pub account: AccountInfo,
+    /// Vote value; absent or `0` when the reviewer hasn't voted.
+    #[serde(default)]
+    pub value: Option<i32>,
+}
+
+/// Per-label vote detail — only populated when `DETAILED_LABELS` is requested.
+#[derive(Debug, Default, Deserialize)]
+pub struct LabelInfo {
+    #[serde(default)]
+    pub all: Vec<ApprovalInfo>,
 }
 
 /// A single review message posted on a change.
@@ -370,11 +1272,28 @@ pub struct ChangeMessage {
     pub date: DateTime<Utc>,
 }
 
+/// Review events from [`GerritClient::fetch_review_events`].
+#[derive(Debug, Default)]
+pub struct ReviewEvents {
+    /// One event per reviewed change, newest first.
+    pub events: Vec<ReviewEvent>,
+    /// Fetching stopped at [`ReviewerQuery::limit`] with more changes left,
+    /// so the events undercount.
+    pub capped: bool,
+}
+
 /// A single review activity event: the first time a user reviewed a change.
 #[derive(Debug)]
 pub struct ReviewEvent {
     pub timestamp: DateTime<Utc>,
     pub project: String,
+    /// The reviewed change's size as fetched, i.e. its latest patch set —
+    /// not necessarily the one that was reviewed.
+    pub insertions: i32,
+    pub deletions: i32,
+    /// The reviewed change's owner (see [`AccountInfo::label`]); `None` when
+    /// the host hides it.
+    pub author: Option<String>,
 }
 
 /// A single entry from the Gerrit
@@ -386,10 +1305,22 @@ pub struct ReviewEvent {
 /// being absent — Gerrit's schema is additive and forward-compatible.
 #[derive(Debug, Deserialize)]
 pub struct ChangeInfo {
+    /// Triplet ID, e.g. `"myproject~main~I1234abcd"`.
+    pub id: String,
+    /// Numeric change number, as shown in review URLs.
+    #[serde(rename = "_number")]
+    pub number: u64,
+    /// First line of the commit message.
+    pub subject: String,
     /// Repository / project name within the Gerrit host.
     pub project: String,
+    /// Destination branch, without the `refs/heads/` prefix.
+    pub branch: String,
     /// Current lifecycle status.
     pub status: ChangeStatus,
+    /// Timestamp when the change was uploaded.
+    #[serde(deserialize_with = "de_gerrit_ts")]
+    pub created: DateTime<Utc>,
     /// Timestamp of the most recent update.
     #[serde(deserialize_with = "de_gerrit_ts")]
     pub updated: DateTime<Utc>,
@@ -402,6 +1333,9 @@ pub struct ChangeInfo {
     pub insertions: i32,
     /// Net lines removed across all patch sets.
     pub deletions: i32,
+    /// Marked work-in-progress.  Gerrit omits the field otherwise.
+    #[serde(default)]
+    pub work_in_progress: bool,
     /// Present and `true` on the last item of a page when additional results
     /// exist.  Consumed by the pagination loop; not meaningful to callers.
     #[serde(rename = "_more_changes", default)]
@@ -409,12 +1343,31 @@ pub struct ChangeInfo {
     /// Review messages — only populated when the `MESSAGES` option is requested.
     #[serde(default)]
     pub messages: Vec<ChangeMessage>,
+    /// The change owner.
+    #[serde(default)]
+    pub owner: Option<AccountInfo>,
+    /// Label name → votes — only populated when `DETAILED_LABELS` is requested.
+    #[serde(default)]
+    pub labels: HashMap<String, LabelInfo>,
+    /// Number of patch sets — only populated when `ALL_REVISIONS` is
+    /// requested (see [`ChangeQuery::with_revisions`]).
+    #[serde(rename = "revisions", default, deserialize_with = "de_revision_count")]
+    pub revision_count: Option<usize>,
 }
 
 // ---------------------------------------------------------------------------
 // Timestamp deserialization helpers
 // ---------------------------------------------------------------------------
 
+/// Count the entries of a change's `revisions` map without keeping them.
+fn de_revision_count<'de, D>(de: D) -> Result<Option<usize>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let revisions = HashMap::<String, serde::de::IgnoredAny>::deserialize(de)?;
+    Ok(Some(revisions.len()))
+}
+
 fn parse_gerrit_ts(s: &str) -> Result<DateTime<Utc>, chrono::ParseError> {
     NaiveDateTime::parse_from_str(s, GERRIT_TS_FMT).map(|ndt| ndt.and_utc())
 }
@@ -448,6 +1401,11 @@ where
 mod tests {
     use super::*;
     use chrono::{Datelike, Timelike};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
 
     // --- timestamp parsing ---
 
@@ -477,12 +1435,29 @@ mod tests {
     #[test]
     fn strip_xssi_ok() {
         let raw = ")]}'\n[{\"id\":\"foo\"}]";
-        assert_eq!(strip_xssi(raw).unwrap(), "[{\"id\":\"foo\"}]");
+        assert_eq!(strip_xssi(raw, false).unwrap(), "[{\"id\":\"foo\"}]");
+        assert_eq!(strip_xssi(raw, true).unwrap(), "[{\"id\":\"foo\"}]");
     }
 
     #[test]
     fn strip_xssi_missing_prefix() {
-        assert!(strip_xssi("[{\"id\":\"foo\"}]").is_err());
+        // Lenient by default: bare JSON passes through untouched.
+        assert_eq!(
+            strip_xssi("[{\"id\":\"foo\"}]", false).unwrap(),
+            "[{\"id\":\"foo\"}]"
+        );
+        assert_eq!(
+            strip_xssi("{\"id\":\"foo\"}", false).unwrap(),
+            "{\"id\":\"foo\"}"
+        );
+        // Strict mode keeps the old hard requirement.
+        assert!(strip_xssi("[{\"id\":\"foo\"}]", true).is_err());
+    }
+
+    #[test]
+    fn strip_xssi_garbage_errors() {
+        assert!(strip_xssi("<html>502 Bad Gateway</html>", false).is_err());
+        assert!(strip_xssi("", false).is_err());
     }
 
     // --- query string builder ---
@@ -504,12 +1479,815 @@ mod tests {
         );
     }
 
+    #[test]
+    fn query_closed_status() {
+        let q = ChangeQuery::new("bob").with_status(ChangeStatus::Closed);
+        assert_eq!(q.to_query_string(), "owner:bob is:closed");
+    }
+
+    #[test]
+    fn closed_is_not_a_change_status() {
+        let err = serde_json::from_str::<ChangeStatus>(r#""CLOSED""#);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn query_single_hashtag() {
+        let q = ChangeQuery::new("bob")
+            .with_status(ChangeStatus::Merged)
+            .with_hashtag("#perf-sprint");
+        assert_eq!(
+            q.to_query_string(),
+            r#"owner:bob is:merged hashtag:"perf-sprint""#
+        );
+    }
+
+    #[test]
+    fn query_ors_multiple_hashtags() {
+        let q = ChangeQuery::new("bob")
+            .with_status(ChangeStatus::Merged)
+            .with_hashtag("perf-sprint")
+            .with_hashtag("binary size")
+            .with_hashtag("#perf-sprint");
+        assert_eq!(
+            q.to_query_string(),
+            r#"owner:bob is:merged (hashtag:"perf-sprint" OR hashtag:"binary size")"#
+        );
+    }
+
+    #[test]
+    fn query_ors_owner_emails() {
+        let q = ChangeQuery::new("me@new.com")
+            .with_status(ChangeStatus::Merged)
+            .with_owner_email("me@old.com")
+            .with_owner_email("me@new.com")
+            .with_owner_email("me@old.com")
+            .with_owner_email("me@corp.com");
+        assert_eq!(
+            q.to_query_string(),
+            "(owner:me@new.com OR owner:me@old.com OR owner:me@corp.com) is:merged"
+        );
+        assert_eq!(
+            ChangeQuery::new("me@new.com").to_query_string(),
+            "owner:me@new.com"
+        );
+    }
+
     #[test]
     fn query_open_status() {
         let q = ChangeQuery::new("bob").with_status(ChangeStatus::New);
         assert!(q.to_query_string().contains("is:open"));
     }
 
+    #[test]
+    fn reviewer_query_defaults_to_reviewer_role() {
+        let q = ReviewerQuery::new("bob");
+        assert_eq!(q.to_query_string(), "reviewer:bob -owner:bob");
+    }
+
+    #[test]
+    fn reviewer_query_single_roles() {
+        let q = |role| {
+            ReviewerQuery::new("bob")
+                .with_roles(&[role])
+                .to_query_string()
+        };
+        assert_eq!(q(ReviewRole::Cc), "cc:bob -owner:bob");
+        assert_eq!(q(ReviewRole::Attention), "attention:bob -owner:bob");
+    }
+
+    #[test]
+    fn reviewer_query_ors_combined_roles() {
+        let q = ReviewerQuery::new("bob")
+            .with_roles(&[
+                ReviewRole::Reviewer,
+                ReviewRole::Cc,
+                ReviewRole::Attention,
+                ReviewRole::Cc,
+            ])
+            .with_after(chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+        assert_eq!(
+            q.to_query_string(),
+            "(reviewer:bob OR cc:bob OR attention:bob) -owner:bob after:2024-01-01"
+        );
+    }
+
+    #[test]
+    fn review_role_parses_names() {
+        assert_eq!("cc".parse::<ReviewRole>().unwrap(), ReviewRole::Cc);
+        assert!("owner".parse::<ReviewRole>().is_err());
+    }
+
+    // --- HTTP errors ---
+
+    fn owned_by(id: u64, email: &str) -> ChangeInfo {
+        let mut change = page(1, &[]).remove(0);
+        change.owner = Some(AccountInfo {
+            account_id: Some(id),
+            email: Some(email.to_owned()),
+            username: None,
+        });
+        change
+    }
+
+    #[test]
+    fn project_activity_tallies_pages_and_ranks_owner() {
+        let mut activity = ProjectActivity::default();
+        let first = vec![
+            owned_by(1, "a@x"),
+            owned_by(1, "a@x"),
+            owned_by(2, "me@x"),
+            owned_by(3, "c@x"),
+        ];
+        let second = vec![owned_by(2, "me@x"), owned_by(4, "d@x"), owned_by(1, "a@x")];
+        assert!(activity.add_page(&first, "me@x", 100));
+        assert!(activity.add_page(&second, "me@x", 100));
+
+        assert_eq!(activity.merged, 7);
+        assert_eq!(activity.per_owner.len(), 4);
+        assert_eq!(activity.own_account, Some(2));
+        assert!(!activity.approximate);
+        // a@x has 3, me@x 2, the others 1 each.
+        assert_eq!(activity.own_rank(), Some((2, 4)));
+    }
+
+    #[test]
+    fn project_activity_stops_at_cap() {
+        let mut activity = ProjectActivity::default();
+        let pages = [
+            vec![owned_by(1, "a@x"), owned_by(2, "b@x")],
+            vec![owned_by(1, "a@x"), owned_by(2, "b@x")],
+        ];
+        assert!(activity.add_page(&pages[0], "me@x", 3));
+        assert!(!activity.add_page(&pages[1], "me@x", 3));
+        assert_eq!(activity.merged, 3);
+        assert!(activity.approximate);
+        // The owner never showed up, so there's nothing to rank.
+        assert_eq!(activity.own_rank(), None);
+    }
+
+    #[test]
+    fn unreadable_ca_cert_names_the_path() {
+        let path = std::env::temp_dir().join("gerritoscope-missing-ca.pem");
+        let err = GerritClient::builder("https://review.example.com")
+            .ca_cert(&path)
+            .build()
+            .err()
+            .expect("missing CA file should fail");
+        let msg = format!("{err:#}");
+        assert!(msg.contains("reading CA certificate"), "{msg}");
+        assert!(msg.contains("gerritoscope-missing-ca.pem"), "{msg}");
+    }
+
+    #[test]
+    fn accounts_url_encodes_owner_and_prefixes_auth() {
+        let client = GerritClient::new("https://review.example.com/r/").unwrap();
+        assert_eq!(
+            client.accounts_url("a b@example.com", "detail").unwrap(),
+            "https://review.example.com/r/accounts/a%20b@example.com/detail"
+        );
+        let client = client.with_auth("me", "secret");
+        assert_eq!(
+            client.accounts_url("me", "avatar").unwrap(),
+            "https://review.example.com/r/a/accounts/me/avatar"
+        );
+    }
+
+    #[test]
+    fn forbidden_suggests_credentials() {
+        let err = classify_gerrit_error(StatusCode::FORBIDDEN, "Forbidden\n").to_string();
+        assert!(err.contains("HTTP 403 Forbidden: Forbidden"), "{err}");
+        assert!(
+            err.contains("--username") && err.contains("--password"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn bad_request_quotes_first_line() {
+        let body = "\n  Invalid query: line 1:6 no viable alternative\nat 'owner:'\n";
+        let err = classify_gerrit_error(StatusCode::BAD_REQUEST, body).to_string();
+        assert_eq!(
+            err,
+            "Gerrit returned HTTP 400 Bad Request: Invalid query: line 1:6 no viable alternative"
+        );
+        assert!(!err.contains("--username"));
+    }
+
+    #[test]
+    fn html_error_page_uses_title() {
+        let body = "<!DOCTYPE html><html><head><TITLE>502 Bad Gateway</TITLE></head>\
+                    <body><h1>oops</h1></body></html>";
+        let err = classify_gerrit_error(StatusCode::BAD_GATEWAY, body).to_string();
+        assert_eq!(err, "Gerrit returned HTTP 502 Bad Gateway: 502 Bad Gateway");
+
+        let err = classify_gerrit_error(StatusCode::BAD_GATEWAY, "<html></html>").to_string();
+        assert_eq!(err, "Gerrit returned HTTP 502 Bad Gateway");
+    }
+
+    #[test]
+    fn long_error_line_is_truncated() {
+        let body = "x".repeat(MAX_ERROR_DETAIL + 50);
+        let err = classify_gerrit_error(StatusCode::BAD_REQUEST, &body).to_string();
+        assert!(err.ends_with('…'));
+        assert_eq!(err.matches('x').count(), MAX_ERROR_DETAIL);
+    }
+
+    // --- pagination ---
+
+    /// A page of `n` changes, with `_more_changes` set on the items at `flagged`.
+    fn page(n: usize, flagged: &[usize]) -> Vec<ChangeInfo> {
+        (0..n)
+            .map(|i| {
+                let mut c = reviewed_change(i as u64, "2024-06-10 08:00:00.000000000");
+                c.more_changes = flagged.contains(&i).then_some(true);
+                c
+            })
+            .collect()
+    }
+
+    /// A page of `n` changes numbered from `start`, flagged as having more.
+    fn endless_page(start: usize, n: usize) -> Vec<ChangeInfo> {
+        let mut changes = page(n, &[n - 1]);
+        for (i, c) in changes.iter_mut().enumerate() {
+            c.number = (start + i) as u64;
+        }
+        changes
+    }
+
+    #[tokio::test]
+    async fn paginate_stops_at_max_pages() {
+        let mut fetched = 0;
+        let err = paginate(
+            "https://broken.example.com",
+            5,
+            |start| {
+                fetched += 1;
+                async move { Ok(endless_page(start, 3)) }
+            },
+            |_| true,
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(fetched, 5);
+        let msg = err.to_string();
+        assert!(msg.contains("https://broken.example.com"), "{msg}");
+        assert!(msg.contains("after 5 pages (15 changes)"), "{msg}");
+    }
+
+    #[tokio::test]
+    async fn paginate_rejects_a_repeated_page() {
+        let err = paginate(
+            "https://stuck.example.com",
+            DEFAULT_MAX_PAGES,
+            |_| async { Ok(endless_page(0, 3)) },
+            |_| true,
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("same page twice (start=3)"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn paginate_ends_on_last_page_or_when_told() {
+        let pages = |start| async move {
+            Ok(if start < 6 {
+                endless_page(start, 3)
+            } else {
+                page(2, &[])
+            })
+        };
+        assert_eq!(paginate("h", 10, pages, |_| true).await.unwrap(), 8);
+        assert_eq!(paginate("h", 10, pages, |_| false).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn review_fetch_stops_at_limit() {
+        let mut fetched = 0;
+        let query = ReviewerQuery::new("bob").with_limit(7);
+        let got = collect_review_events("h", 10, &query, |start| {
+            fetched += 1;
+            async move { Ok(endless_page(start, 3)) }
+        })
+        .await
+        .unwrap();
+
+        // Three pages of three reach the limit; the last page is cut short.
+        assert_eq!(fetched, 3);
+        assert_eq!(got.events.len(), 7);
+        assert!(got.capped);
+    }
+
+    #[tokio::test]
+    async fn review_fetch_under_limit_is_not_capped() {
+        // Changes 0..3, then a last page with 3 and 4.
+        let pages = |start| async move {
+            let mut changes = endless_page(start, 3 - start / 3);
+            if start > 0 {
+                changes.iter_mut().for_each(|c| c.more_changes = None);
+            }
+            Ok(changes)
+        };
+        let query = ReviewerQuery::new("bob").with_limit(5);
+        let got = collect_review_events("h", 10, &query, pages).await.unwrap();
+        assert_eq!(got.events.len(), 5);
+        assert!(!got.capped, "exactly the limit, with nothing left");
+
+        let query = ReviewerQuery::new("bob");
+        let got = collect_review_events("h", 10, &query, pages).await.unwrap();
+        assert_eq!(got.events.len(), 5);
+        assert!(!got.capped);
+    }
+
+    #[test]
+    fn full_page_without_flag_continues() {
+        assert!(has_more(&page(4, &[]), 4));
+    }
+
+    #[test]
+    fn short_page_without_flag_stops() {
+        assert!(!has_more(&page(3, &[]), 4));
+        assert!(!has_more(&[], 4));
+    }
+
+    #[test]
+    fn flag_on_any_item_continues() {
+        // Newer Gerrit: last item only.  Older: first (or every) item.
+        assert!(has_more(&page(3, &[2]), 4));
+        assert!(has_more(&page(3, &[0]), 4));
+        assert!(has_more(&page(3, &[0, 1, 2]), 4));
+    }
+
+    // --- review events ---
+
+    fn reviewed_change(number: u64, message_date: &str) -> ChangeInfo {
+        let json = format!(
+            r#"{{
+                "id": "repo~main~I{number}",
+                "_number": {number},
+                "subject": "Reviewed",
+                "project": "repo",
+                "branch": "main",
+                "status": "NEW",
+                "created": "2024-06-01 08:00:00.000000000",
+                "updated": "2024-06-20 08:00:00.000000000",
+                "insertions": 1,
+                "deletions": 0,
+                "messages": [
+                    {{"author": {{"email": "rev@example.com"}}, "date": "{message_date}"}}
+                ]
+            }}"#
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn overlapping_review_pages_dedupe_by_number() {
+        let page1 = vec![
+            reviewed_change(1, "2024-06-10 08:00:00.000000000"),
+            reviewed_change(2, "2024-06-11 08:00:00.000000000"),
+        ];
+        // Change 2 shifted onto the next page too, with an earlier message.
+        let page2 = vec![
+            reviewed_change(2, "2024-06-05 08:00:00.000000000"),
+            reviewed_change(3, "2024-06-12 08:00:00.000000000"),
+        ];
+        let mut seen = HashMap::new();
+        let mut events = Vec::new();
+        add_review_events(&page1, "rev@example.com", &mut seen, &mut events);
+        add_review_events(&page2, "rev@example.com", &mut seen, &mut events);
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[1].timestamp.day(), 5, "earliest timestamp kept");
+    }
+
+    #[test]
+    fn review_events_carry_change_size() {
+        let mut c = reviewed_change(1, "2024-06-10 08:00:00.000000000");
+        c.insertions = 120;
+        c.deletions = 7;
+        let mut events = Vec::new();
+        add_review_events(&[c], "rev@example.com", &mut HashMap::new(), &mut events);
+        assert_eq!((events[0].insertions, events[0].deletions), (120, 7));
+    }
+
+    #[test]
+    fn review_events_name_the_change_owner() {
+        let owned = |owner: &str| {
+            let mut c = reviewed_change(1, "2024-06-10 08:00:00.000000000");
+            c.owner = Some(serde_json::from_str(owner).unwrap());
+            c
+        };
+        let mut events = Vec::new();
+        let changes = [
+            owned(r#"{"_account_id": 7, "email": "alice@example.com", "username": "alice"}"#),
+            owned(r#"{"_account_id": 8, "username": "bob"}"#),
+            // Deleted or anonymized accounts only keep their id.
+            owned(r#"{"_account_id": 9}"#),
+        ];
+        for (number, mut c) in changes.into_iter().enumerate() {
+            c.number = number as u64;
+            add_review_events(&[c], "rev@example.com", &mut HashMap::new(), &mut events);
+        }
+        let mut missing = reviewed_change(4, "2024-06-10 08:00:00.000000000");
+        missing.owner = None;
+        add_review_events(
+            &[missing],
+            "rev@example.com",
+            &mut HashMap::new(),
+            &mut events,
+        );
+
+        let authors: Vec<Option<&str>> = events.iter().map(|e| e.author.as_deref()).collect();
+        assert_eq!(
+            authors,
+            [Some("alice@example.com"), Some("bob"), None, None]
+        );
+    }
+
+    #[test]
+    fn review_timestamp_falls_back_to_updated() {
+        let c = reviewed_change(1, "2024-06-10 08:00:00.000000000");
+        assert_eq!(review_timestamp(&c, "rev@example.com").day(), 10);
+        assert_eq!(review_timestamp(&c, "other@example.com"), c.updated);
+        assert_eq!(review_timestamp(&c, "rev"), c.updated);
+    }
+
+    /// A change whose messages are authored by account 1001 (`rev`,
+    /// `rev@example.com`) under different identifier forms, plus someone
+    /// else's earlier message.
+    fn change_with_detailed_messages() -> ChangeInfo {
+        serde_json::from_str(
+            r#"{
+                "id": "repo~main~I9",
+                "_number": 9,
+                "subject": "Reviewed",
+                "project": "repo",
+                "branch": "main",
+                "status": "NEW",
+                "created": "2024-06-01 08:00:00.000000000",
+                "updated": "2024-09-01 08:00:00.000000000",
+                "insertions": 1,
+                "deletions": 0,
+                "messages": [
+                    {"author": {"_account_id": 7, "username": "other"}, "date": "2024-06-02 08:00:00.000000000"},
+                    {"author": {"_account_id": 1001}, "date": "2024-06-04 08:00:00.000000000"},
+                    {"author": {"_account_id": 1001, "username": "rev", "email": "rev@example.com"}, "date": "2024-06-03 08:00:00.000000000"}
+                ]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn review_timestamp_matches_username() {
+        let c = change_with_detailed_messages();
+        assert_eq!(review_timestamp(&c, "rev").day(), 3);
+        assert_eq!(review_timestamp(&c, "rev@example.com").day(), 3);
+    }
+
+    #[test]
+    fn review_timestamp_matches_account_id() {
+        let c = change_with_detailed_messages();
+        // Earliest message from the account, whichever fields it carries.
+        assert_eq!(review_timestamp(&c, "1001").day(), 3);
+        assert_eq!(review_timestamp(&c, "1002"), c.updated);
+    }
+
+    // --- URL building ---
+
+    #[test]
+    fn anonymous_requests_use_plain_path() {
+        let client = GerritClient::new("https://review.example.com/").unwrap();
+        assert_eq!(client.path_prefix(), "");
+        assert_eq!(
+            client.changes_url(""),
+            "https://review.example.com/changes/"
+        );
+    }
+
+    #[test]
+    fn authenticated_requests_use_a_prefix() {
+        let client = GerritClient::new("https://review.example.com")
+            .unwrap()
+            .with_auth("me", "secret");
+        assert_eq!(client.path_prefix(), "/a");
+        assert_eq!(
+            client.changes_url(""),
+            "https://review.example.com/a/changes/"
+        );
+        assert_eq!(
+            client.changes_url("123/"),
+            "https://review.example.com/a/changes/123/"
+        );
+    }
+
+    // --- builder ---
+
+    #[test]
+    fn builder_auth_matches_with_auth() {
+        let chained = GerritClient::new("https://review.example.com/")
+            .unwrap()
+            .with_auth("me", "secret");
+        let built = GerritClient::builder("https://review.example.com/")
+            .auth("me", "secret")
+            .build()
+            .unwrap();
+        assert_eq!(built.base_url, chained.base_url);
+        assert_eq!(built.changes_url(""), chained.changes_url(""));
+
+        let header = |client: &GerritClient| {
+            let req = client.get(&client.changes_url(""), &[]).build().unwrap();
+            req.headers().get(reqwest::header::AUTHORIZATION).cloned()
+        };
+        assert!(header(&built).is_some());
+        assert_eq!(header(&built), header(&chained));
+    }
+
+    #[test]
+    fn builder_defaults_match_new() {
+        let built = GerritClient::builder("https://review.example.com")
+            .build()
+            .unwrap();
+        let plain = GerritClient::new("https://review.example.com").unwrap();
+        assert_eq!(built.changes_url(""), plain.changes_url(""));
+        assert_eq!(built.max_pages, DEFAULT_MAX_PAGES);
+        assert!(!built.strict_xssi && built.limiter.is_none() && built.cache.is_none());
+    }
+
+    #[tokio::test]
+    async fn builder_timeout_abandons_silent_hosts() {
+        // Accepts connections but never answers.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((sock, _)) = listener.accept().await {
+                open.push(sock);
+            }
+        });
+
+        let client = GerritClient::builder(&url)
+            .timeout(std::time::Duration::from_millis(200))
+            .build()
+            .unwrap();
+        let started = std::time::Instant::now();
+        let err = client.fetch_change(1).await.unwrap_err();
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        let timed_out = err
+            .chain()
+            .filter_map(|e| e.downcast_ref::<reqwest::Error>())
+            .any(reqwest::Error::is_timeout);
+        assert!(timed_out, "{err:#}");
+    }
+
+    // --- mock servers ---
+
+    /// Read a request up to the end of its headers, or until the client
+    /// hangs up.
+    async fn read_request_head(sock: &mut tokio::net::TcpStream) -> String {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 1024];
+        while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = sock.read(&mut chunk).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+
+    /// Serve a single request: answer with `head` (status line plus any
+    /// headers, each ending in CRLF), a `Content-Length` for `body`, then
+    /// `body`.  Returns the base URL and a task yielding the request head.
+    async fn one_shot_server(head: String, body: &[u8]) -> (String, JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let mut response = format!(
+            "{head}Content-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        let server = tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            let request = read_request_head(&mut sock).await;
+            sock.write_all(&response).await.unwrap();
+            request
+        });
+        (url, server)
+    }
+
+    // --- compression ---
+
+    /// A `/changes/7/` response ([`CHANGE_BODY`] with a different number
+    /// and subject), gzipped with `gzip.compress(body, mtime=0)`.
+    const GZIPPED_CHANGE: &[u8] = &[
+        0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x6d, 0x8d, 0x31, 0x0b, 0xc2,
+        0x30, 0x10, 0x85, 0x77, 0x7f, 0x85, 0xdc, 0x22, 0x42, 0x2a, 0xd7, 0x20, 0x2a, 0xdd, 0x1d,
+        0x5c, 0x5c, 0x1c, 0x9c, 0x44, 0xd2, 0xe4, 0xc0, 0x88, 0x4d, 0xeb, 0x25, 0x59, 0x94, 0xf6,
+        0xb7, 0x7b, 0x85, 0xba, 0x88, 0x8f, 0x5b, 0xbe, 0xe3, 0xe3, 0xbd, 0xe5, 0xa5, 0x5f, 0xcc,
+        0xde, 0xe0, 0x1d, 0x54, 0xc0, 0x43, 0x63, 0x7c, 0x18, 0x0e, 0x25, 0x28, 0xb8, 0x86, 0xdc,
+        0xd4, 0xc4, 0x50, 0x6d, 0x15, 0xc4, 0x5c, 0xdf, 0xc9, 0x26, 0x31, 0x4e, 0xcf, 0x4c, 0xf4,
+        0x22, 0x27, 0x42, 0xc7, 0xed, 0xf4, 0x64, 0xa1, 0x9a, 0x4d, 0xb0, 0x37, 0x81, 0xb1, 0x41,
+        0x38, 0x26, 0x93, 0x72, 0x14, 0x3e, 0xee, 0xcf, 0x82, 0x96, 0xc9, 0x24, 0x1a, 0x37, 0x34,
+        0xea, 0x75, 0x81, 0x9b, 0x02, 0xcb, 0x39, 0xee, 0x2a, 0x44, 0xb9, 0x15, 0x7e, 0x23, 0x66,
+        0xee, 0xdc, 0xaf, 0xa9, 0xff, 0x9b, 0x3e, 0x44, 0xe2, 0xe4, 0xdb, 0x20, 0x33, 0xa5, 0x02,
+        0x47, 0x0f, 0x9a, 0x08, 0xfb, 0x0f, 0x0e, 0x7f, 0x29, 0xaa, 0xd6, 0x00, 0x00, 0x00,
+    ];
+
+    #[tokio::test]
+    async fn requests_and_decodes_compressed_responses() {
+        let (url, server) = one_shot_server(
+            "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\n".to_owned(),
+            GZIPPED_CHANGE,
+        )
+        .await;
+
+        let client = GerritClient::new(&url).unwrap();
+        let change = client.fetch_change(7).await.unwrap();
+        assert_eq!(change.subject, "Squeezed");
+        assert_eq!(change.number, 7);
+
+        let request = server.await.unwrap().to_ascii_lowercase();
+        let accept = request
+            .lines()
+            .find_map(|l| l.strip_prefix("accept-encoding:"))
+            .unwrap_or_else(|| panic!("no Accept-Encoding in {request:?}"));
+        assert!(accept.contains("gzip") && accept.contains("br"), "{accept}");
+    }
+
+    #[tokio::test]
+    async fn raw_query_is_sent_verbatim() {
+        let (url, server) = one_shot_server("HTTP/1.1 200 OK\r\n".to_owned(), b")]}'\n[]").await;
+
+        let raw = "owner:me is:merged label:Code-Review+2 -age:1y (project:a OR project:\"b c\")";
+        let client = GerritClient::new(&url).unwrap();
+        let changes = client.fetch_changes(&ChangeQuery::raw(raw)).await.unwrap();
+        assert!(changes.is_empty());
+
+        let request = server.await.unwrap();
+        let path = request.split(' ').nth(1).unwrap();
+        let sent = Url::parse(&format!("http://host{path}"))
+            .unwrap()
+            .query_pairs()
+            .find(|(k, _)| k == "q")
+            .map(|(_, v)| v.into_owned());
+        assert_eq!(sent.as_deref(), Some(raw));
+    }
+
+    #[tokio::test]
+    async fn custom_user_agent_and_contact_are_sent() {
+        let (url, server) = one_shot_server("HTTP/1.1 200 OK\r\n".to_owned(), b")]}'\n[]").await;
+
+        let client = GerritClient::builder(&url)
+            .user_agent("team-dashboard/2")
+            .contact("ops@example.com")
+            .build()
+            .unwrap();
+        client.fetch_changes(&ChangeQuery::new("me")).await.unwrap();
+
+        let request = server.await.unwrap();
+        let sent = request
+            .lines()
+            .find(|l| l.to_ascii_lowercase().starts_with("user-agent:"))
+            .unwrap_or_else(|| panic!("no User-Agent in {request:?}"));
+        assert_eq!(&sent[12..], "team-dashboard/2 (+ops@example.com)");
+    }
+
+    #[test]
+    fn user_agent_must_be_non_empty_ascii() {
+        let ua = |b: GerritClientBuilder| b.user_agent_value();
+        let b = || GerritClient::builder("https://review.example.com");
+        assert_eq!(ua(b()).unwrap(), DEFAULT_USER_AGENT);
+        assert_eq!(
+            ua(b().contact("me@example.com")).unwrap(),
+            format!("{DEFAULT_USER_AGENT} (+me@example.com)")
+        );
+        for bad in [
+            b().user_agent("  "),
+            b().contact(""),
+            b().user_agent("bot\n"),
+        ] {
+            assert!(ua(bad).is_err());
+        }
+        let err = ua(b().user_agent("gerritoscope/1 (José)"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("printable ASCII"), "{err}");
+    }
+
+    // --- response cache / ETag revalidation ---
+
+    /// Serve one HTTP response per connection: `200` with an ETag for plain
+    /// requests, `304` for requests carrying `If-None-Match`.  Returns the
+    /// base URL and a counter of conditional requests seen.
+    async fn etag_server(body: &'static str) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let conditional = Arc::new(AtomicUsize::new(0));
+        let seen = conditional.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut sock, _) = listener.accept().await.unwrap();
+                let request = read_request_head(&mut sock).await.to_ascii_lowercase();
+                let response = if request.contains("if-none-match: \"v1\"") {
+                    seen.fetch_add(1, Ordering::SeqCst);
+                    "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n"
+                        .to_owned()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                };
+                sock.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (format!("http://{addr}"), conditional)
+    }
+
+    const CHANGE_BODY: &str = ")]}'\n{\"id\":\"r~main~I1\",\"_number\":1,\"subject\":\"Cached\",\
+        \"project\":\"r\",\"branch\":\"main\",\"status\":\"NEW\",\
+        \"created\":\"2024-06-01 08:00:00.000000000\",\"updated\":\"2024-06-02 08:00:00.000000000\",\
+        \"insertions\":1,\"deletions\":0}";
+
+    #[tokio::test]
+    async fn not_modified_serves_cached_body() {
+        let (url, conditional) = etag_server(CHANGE_BODY).await;
+        let cache = crate::cache::tests::scratch_cache("etag", chrono::Duration::minutes(60));
+        let client = GerritClient::builder(&url)
+            .cache(cache.clone())
+            .build()
+            .unwrap();
+
+        let first = client.fetch_change(1).await.unwrap();
+        assert_eq!(conditional.load(Ordering::SeqCst), 0);
+
+        // Same request again: revalidated with If-None-Match, answered by 304.
+        let second = client.fetch_change(1).await.unwrap();
+        assert_eq!(conditional.load(Ordering::SeqCst), 1);
+        assert_eq!(second.subject, "Cached");
+        assert_eq!(second.number, first.number);
+    }
+
+    #[tokio::test]
+    async fn failed_cache_write_is_a_collected_warning() {
+        let (url, _) = etag_server(CHANGE_BODY).await;
+        let cache = crate::cache::tests::scratch_cache("unwritable", chrono::Duration::minutes(60));
+        // Pull the directory out from under the cache so the write fails.
+        let dir = std::env::temp_dir().join(format!(
+            "gerritoscope-cache-unwritable-{}",
+            std::process::id()
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+        let client = GerritClient::builder(&url).cache(cache).build().unwrap();
+
+        assert_eq!(client.fetch_change(1).await.unwrap().subject, "Cached");
+        let warnings = client.clone().take_warnings();
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].starts_with("writing "), "{warnings:?}");
+        assert!(client.take_warnings().is_empty());
+    }
+
+    #[tokio::test]
+    async fn bare_json_is_noted_once_for_debugging() {
+        // CHANGE_BODY without its XSSI prefix, as a stripping proxy sends it.
+        let (url, _) = etag_server(&CHANGE_BODY[XSSI_PREFIX.len()..]).await;
+        let client = GerritClient::new(&url).unwrap();
+        client.fetch_change(1).await.unwrap();
+        client.fetch_change(1).await.unwrap();
+        let notes = client.take_debug_notes();
+        assert_eq!(notes.len(), 1, "{notes:?}");
+        assert!(notes[0].contains("XSSI prefix"), "{notes:?}");
+        assert!(client.take_warnings().is_empty());
+    }
+
+    #[tokio::test]
+    async fn fresh_entry_without_etag_skips_the_network() {
+        let cache = crate::cache::tests::scratch_cache("ttl", chrono::Duration::minutes(60));
+        // Nothing listens on this URL; a network attempt would fail.
+        let url = "http://127.0.0.1:9";
+        let key = crate::cache::key(&format!("{url}/changes/1/"), &[("o", "DETAILED_LABELS")]);
+        cache
+            .put(
+                &key,
+                &CachedResponse {
+                    etag: None,
+                    fetched_at: Utc::now(),
+                    body: CHANGE_BODY.to_owned(),
+                },
+            )
+            .unwrap();
+        let client = GerritClient::builder(url).cache(cache).build().unwrap();
+        assert_eq!(client.fetch_change(1).await.unwrap().subject, "Cached");
+    }
+
     // --- ChangeInfo deserialization ---
 
     #[test]
@@ -531,12 +2309,17 @@ mod tests {
         // The raw string literal above includes a literal backslash-n, not a
         // real newline.  Replace it so the XSSI prefix is a real newline.
         let raw = raw.replace(r"\n", "\n");
-        let json = strip_xssi(&raw).unwrap();
+        let json = strip_xssi(&raw, true).unwrap();
         let changes: Vec<ChangeInfo> = serde_json::from_str(json).unwrap();
 
         assert_eq!(changes.len(), 1);
         let c = &changes[0];
+        assert_eq!(c.number, 12345);
+        assert_eq!(c.subject, "Fix the widget");
+        assert_eq!(c.id, "myproject~main~I1234abcd");
         assert_eq!(c.project, "myproject");
+        assert_eq!(c.branch, "main");
+        assert_eq!(c.created.day(), 10);
         assert_eq!(c.status, ChangeStatus::Merged);
         assert_eq!(c.insertions, 42);
         assert_eq!(c.deletions, 7);
@@ -559,10 +2342,122 @@ mod tests {
             "_number": 99
         }]"#;
         let raw = raw.replace(r"\n", "\n");
-        let json = strip_xssi(&raw).unwrap();
+        let json = strip_xssi(&raw, true).unwrap();
         let changes: Vec<ChangeInfo> = serde_json::from_str(json).unwrap();
 
         assert_eq!(changes[0].submitted, None);
         assert_eq!(changes[0].more_changes, None);
+        assert!(changes[0].created < changes[0].updated);
+        assert!(changes[0].labels.is_empty());
+        assert_eq!(changes[0].revision_count, None);
+    }
+
+    #[test]
+    fn deserialise_all_revisions_counts_patch_sets() {
+        let json = r#"{
+            "id": "repo~main~Irev",
+            "project": "repo",
+            "branch": "main",
+            "subject": "Three rounds",
+            "status": "MERGED",
+            "created": "2024-06-01 08:00:00.000000000",
+            "updated": "2024-06-03 08:00:00.000000000",
+            "insertions": 5,
+            "deletions": 0,
+            "_number": 100,
+            "revisions": {
+                "aaa111": {"_number": 1, "kind": "REWORK"},
+                "bbb222": {"_number": 2, "kind": "REWORK"},
+                "ccc333": {"_number": 3, "kind": "TRIVIAL_REBASE"}
+            }
+        }"#;
+        let change: ChangeInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(change.revision_count, Some(3));
+    }
+
+    #[test]
+    fn revisions_option_only_when_requested() {
+        let q = ChangeQuery::new("bob");
+        assert!(!q.revisions);
+        assert!(q.with_revisions(true).revisions);
+    }
+
+    #[test]
+    fn deserialise_detailed_labels() {
+        let raw = r#")]}'\n[{
+            "id": "repo~main~Ivote",
+            "project": "repo",
+            "branch": "main",
+            "subject": "Reviewed patch",
+            "status": "MERGED",
+            "created": "2024-06-01 08:00:00.000000000",
+            "updated": "2024-06-02 08:00:00.000000000",
+            "submitted": "2024-06-02 08:00:00.000000000",
+            "insertions": 5,
+            "deletions": 0,
+            "_number": 100,
+            "owner": {"_account_id": 1000, "email": "me@example.com"},
+            "labels": {
+                "Code-Review": {
+                    "all": [
+                        {"value": 2, "_account_id": 1001, "email": "rev@example.com"},
+                        {"_account_id": 1002, "username": "lurker"}
+                    ]
+                }
+            }
+        }]"#;
+        let raw = raw.replace(r"\n", "\n");
+        let json = strip_xssi(&raw, true).unwrap();
+        let changes: Vec<ChangeInfo> = serde_json::from_str(json).unwrap();
+
+        let owner = changes[0].owner.as_ref().unwrap();
+        assert_eq!(owner.account_id, Some(1000));
+        let votes = &changes[0].labels["Code-Review"].all;
+        assert_eq!(votes.len(), 2);
+        assert_eq!(votes[0].value, Some(2));
+        assert!(votes[0].account.matches("rev@example.com"));
+        assert_eq!(votes[1].value, None);
+        assert!(votes[1].account.matches("lurker"));
+    }
+
+    #[test]
+    fn deserialise_change_info_missing_identity_fails() {
+        // Gerrit always sends these; a body without them isn't a ChangeInfo.
+        let json = r#"[{
+            "project": "repo",
+            "status": "NEW",
+            "updated": "2024-06-02 08:00:00.000000000",
+            "insertions": 0,
+            "deletions": 0
+        }]"#;
+        assert!(serde_json::from_str::<Vec<ChangeInfo>>(json).is_err());
+    }
+
+    #[test]
+    fn deserialise_single_change_detail() {
+        // The detail endpoint returns a bare object, not an array.
+        let raw = r#")]}'\n{
+            "id": "repo~main~Iabcd",
+            "project": "repo",
+            "branch": "main",
+            "subject": "Teach the frobnicator to count",
+            "status": "NEW",
+            "created": "2024-06-01 08:00:00.000000000",
+            "updated": "2024-06-02 08:00:00.000000000",
+            "insertions": 12,
+            "deletions": 3,
+            "_number": 4242,
+            "owner": {"_account_id": 1000, "email": "me@example.com"}
+        }"#;
+        let raw = raw.replace(r"\n", "\n");
+        let json = strip_xssi(&raw, true).unwrap();
+        let c: ChangeInfo = serde_json::from_str(json).unwrap();
+
+        assert_eq!(c.number, 4242);
+        assert_eq!(c.subject, "Teach the frobnicator to count");
+        assert_eq!(c.status, ChangeStatus::New);
+        assert_eq!(c.branch, "main");
+        assert_eq!(c.submitted, None);
+        assert!(c.owner.unwrap().matches("me@example.com"));
     }
 }
@@ -1,5 +1,7 @@
 //! Well-known Gerrit host aliases and resolution logic.
 
+use std::collections::HashMap;
+
 use anyhow::{bail, Result};
 
 // ---------------------------------------------------------------------------
@@ -19,8 +21,27 @@ pub const KNOWN_HOSTS: &[(&str, &str)] = &[
     ("libreoffice", "https://gerrit.libreoffice.org"),
     ("onap", "https://gerrit.onap.org"),
     ("webrtc", "https://webrtc-review.googlesource.com"),
+    ("eclipse", "https://git.eclipse.org/r"),
+    ("coreboot", "https://review.coreboot.org"),
 ];
 
+/// Every known `(alias, url)` pair: user-defined `overlay` entries first,
+/// then the built-in [`KNOWN_HOSTS`] not shadowed by an overlay alias.
+///
+/// Shared by `--list-hosts` and the unknown-alias error in [`resolve`] so the
+/// two can't drift apart.
+pub fn known_host_list(overlay: &[(String, String)]) -> Vec<(&str, &str)> {
+    let builtins = KNOWN_HOSTS
+        .iter()
+        .filter(|(a, _)| !overlay.iter().any(|(o, _)| o == a))
+        .map(|&(a, u)| (a, u));
+    overlay
+        .iter()
+        .map(|(a, u)| (a.as_str(), u.as_str()))
+        .chain(builtins)
+        .collect()
+}
+
 // ---------------------------------------------------------------------------
 // Resolution
 // ---------------------------------------------------------------------------
@@ -31,13 +52,18 @@ pub const KNOWN_HOSTS: &[(&str, &str)] = &[
 /// - **Full URL** → derived alias (known short name if the URL matches the
 ///   table, otherwise the hostname) plus the URL with any trailing `/` stripped.
 ///
+/// `overlay` holds user-defined `(alias, url)` pairs (see
+/// [`crate::config::Config::host_overlay`]); they are consulted before
+/// [`KNOWN_HOSTS`] and win on conflict.  Pass `&[]` for built-ins only.
+///
 /// Returns an error for unrecognised short names (non-URL tokens not in the table).
-pub fn resolve(s: &str) -> Result<(String, String)> {
+pub fn resolve(s: &str, overlay: &[(String, String)]) -> Result<(String, String)> {
     let s = s.trim();
+    let table = known_host_list(overlay);
 
     if s.starts_with("http://") || s.starts_with("https://") {
         let url = s.trim_end_matches('/').to_owned();
-        let alias = KNOWN_HOSTS
+        let alias = table
             .iter()
             .find(|(_, u)| *u == url.as_str())
             .map(|(a, _)| (*a).to_owned())
@@ -53,14 +79,10 @@ pub fn resolve(s: &str) -> Result<(String, String)> {
         return Ok((alias, url));
     }
 
-    match KNOWN_HOSTS.iter().find(|(a, _)| *a == s) {
+    match table.iter().find(|(a, _)| *a == s) {
         Some((alias, url)) => Ok((alias.to_string(), url.to_string())),
         None => {
-            let known = KNOWN_HOSTS
-                .iter()
-                .map(|(a, _)| *a)
-                .collect::<Vec<_>>()
-                .join(", ");
+            let known = table.iter().map(|(a, _)| *a).collect::<Vec<_>>().join(", ");
             bail!("unknown host {s:?}; pass a full URL or one of: {known}")
         }
     }
@@ -73,12 +95,13 @@ pub fn resolve(s: &str) -> Result<(String, String)> {
 /// - comma-separated tokens: `"chromium,go,android"`
 ///
 /// Duplicate URLs are silently dropped (last-one-wins for the alias).
-pub fn expand(specs: &[String]) -> Result<Vec<(String, String)>> {
+/// `overlay` is forwarded to [`resolve`].
+pub fn expand(specs: &[String], overlay: &[(String, String)]) -> Result<Vec<(String, String)>> {
     let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
     let mut out = Vec::new();
     for spec in specs {
         for token in spec.split(',') {
-            let (alias, url) = resolve(token.trim())?;
+            let (alias, url) = resolve(token.trim(), overlay)?;
             if seen.insert(url.clone()) {
                 out.push((alias, url));
             }
@@ -87,6 +110,109 @@ pub fn expand(specs: &[String]) -> Result<Vec<(String, String)>> {
     Ok(out)
 }
 
+// ---------------------------------------------------------------------------
+// Per-host owners
+// ---------------------------------------------------------------------------
+
+/// Parsed `--owner` values: an optional bare owner used for every host, plus
+/// `alias=owner` overrides for individual hosts.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Owners {
+    pub default: Option<String>,
+    /// `(alias, owner)` pairs in the order given.
+    pub overrides: Vec<(String, String)>,
+}
+
+impl Owners {
+    /// Parse repeated `--owner` values.  Like `--hosts`, each value may be a
+    /// comma-separated list:
+    ///
+    /// - `"me@example.com"` — applies to every host without an override
+    /// - `"chromium=me@chromium.org,corp=me@corp.com"` — per-host overrides
+    ///
+    /// Errors on more than one bare owner, an empty alias or owner, or the
+    /// same alias given twice.
+    pub fn parse(values: &[String]) -> Result<Self> {
+        let mut owners = Self::default();
+        for token in values.iter().flat_map(|v| v.split(',')) {
+            let token = token.trim();
+            match token.split_once('=') {
+                Some((alias, owner)) => {
+                    let (alias, owner) = (alias.trim(), owner.trim());
+                    if alias.is_empty() || owner.is_empty() {
+                        bail!("--owner {token:?} should be ALIAS=OWNER");
+                    }
+                    if owners.overrides.iter().any(|(a, _)| a == alias) {
+                        bail!("--owner given twice for host {alias:?}");
+                    }
+                    owners.overrides.push((alias.to_owned(), owner.to_owned()));
+                }
+                None if token.is_empty() => bail!("empty --owner value"),
+                None => {
+                    if let Some(ref prev) = owners.default {
+                        bail!("more than one bare --owner ({prev:?} and {token:?}); use ALIAS=OWNER for per-host owners");
+                    }
+                    owners.default = Some(token.to_owned());
+                }
+            }
+        }
+        Ok(owners)
+    }
+
+    /// The owner to query on the host called `alias`: its override if there
+    /// is one, else the bare owner.
+    pub fn for_host(&self, alias: &str) -> Option<&str> {
+        self.overrides
+            .iter()
+            .find(|(a, _)| a == alias)
+            .map(|(_, o)| o.as_str())
+            .or(self.default.as_deref())
+    }
+
+    /// Map each alias in `hosts` (from [`expand`]) to its owner.
+    ///
+    /// Errors if a host has no owner, or an override names an alias that
+    /// isn't being queried (most likely a typo).
+    pub fn assign(&self, hosts: &[(String, String)]) -> Result<HashMap<String, String>> {
+        if let Some((alias, _)) = self
+            .overrides
+            .iter()
+            .find(|(a, _)| !hosts.iter().any(|(h, _)| h == a))
+        {
+            let queried = hosts
+                .iter()
+                .map(|(a, _)| a.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            bail!("--owner {alias}=… names a host that isn't queried (hosts: {queried})");
+        }
+        hosts
+            .iter()
+            .map(|(alias, _)| match self.for_host(alias) {
+                Some(owner) => Ok((alias.clone(), owner.to_owned())),
+                None => {
+                    bail!("no --owner for host {alias:?}; pass a bare --owner or {alias}=OWNER")
+                }
+            })
+            .collect()
+    }
+
+    /// Owner shown in reports: the bare owner if given, otherwise the
+    /// distinct per-host owners joined with `", "`.
+    pub fn label(&self) -> String {
+        if let Some(ref owner) = self.default {
+            return owner.clone();
+        }
+        let mut seen: Vec<&str> = Vec::new();
+        for (_, owner) in &self.overrides {
+            if !seen.contains(&owner.as_str()) {
+                seen.push(owner);
+            }
+        }
+        seen.join(", ")
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -97,49 +223,92 @@ mod tests {
 
     #[test]
     fn resolve_known_alias() {
-        let (alias, url) = resolve("chromium").unwrap();
+        let (alias, url) = resolve("chromium", &[]).unwrap();
         assert_eq!(alias, "chromium");
         assert_eq!(url, "https://chromium-review.googlesource.com");
     }
 
     #[test]
     fn resolve_full_url_unknown() {
-        let (alias, url) = resolve("https://my-gerrit.corp.com").unwrap();
+        let (alias, url) = resolve("https://my-gerrit.corp.com", &[]).unwrap();
         assert_eq!(alias, "my-gerrit.corp.com");
         assert_eq!(url, "https://my-gerrit.corp.com");
     }
 
     #[test]
     fn resolve_full_url_strips_trailing_slash() {
-        let (_, url) = resolve("https://my-gerrit.corp.com/").unwrap();
+        let (_, url) = resolve("https://my-gerrit.corp.com/", &[]).unwrap();
         assert_eq!(url, "https://my-gerrit.corp.com");
     }
 
     #[test]
     fn resolve_full_url_known_returns_short_alias() {
         // Passing the full URL of a known host should still give the short alias.
-        let (alias, _) = resolve("https://chromium-review.googlesource.com").unwrap();
+        let (alias, _) = resolve("https://chromium-review.googlesource.com", &[]).unwrap();
         assert_eq!(alias, "chromium");
     }
 
     #[test]
     fn resolve_unknown_alias_errors() {
-        assert!(resolve("notahost").is_err());
-        let err = resolve("notahost").unwrap_err().to_string();
+        assert!(resolve("notahost", &[]).is_err());
+        let err = resolve("notahost", &[]).unwrap_err().to_string();
         assert!(err.contains("notahost"));
         assert!(err.contains("chromium"), "error should list known aliases");
     }
 
+    #[test]
+    fn every_known_url_resolves_to_its_alias() {
+        for (alias, url) in known_host_list(&[]) {
+            let (resolved, _) = resolve(url, &[]).unwrap();
+            assert_eq!(resolved, alias, "{url} should resolve back to {alias}");
+        }
+    }
+
+    fn corp_overlay() -> Vec<(String, String)> {
+        vec![("corp".to_owned(), "https://gerrit.corp.com".to_owned())]
+    }
+
+    #[test]
+    fn resolve_user_alias() {
+        let (alias, url) = resolve("corp", &corp_overlay()).unwrap();
+        assert_eq!(alias, "corp");
+        assert_eq!(url, "https://gerrit.corp.com");
+    }
+
+    #[test]
+    fn resolve_user_alias_url_returns_user_alias() {
+        let (alias, _) = resolve("https://gerrit.corp.com", &corp_overlay()).unwrap();
+        assert_eq!(alias, "corp");
+    }
+
+    #[test]
+    fn user_alias_wins_over_builtin() {
+        let overlay = vec![("go".to_owned(), "https://go.corp.com".to_owned())];
+        let (_, url) = resolve("go", &overlay).unwrap();
+        assert_eq!(url, "https://go.corp.com");
+        // The shadowed built-in appears only once in the listing.
+        let list = known_host_list(&overlay);
+        assert_eq!(list.iter().filter(|(a, _)| *a == "go").count(), 1);
+    }
+
+    #[test]
+    fn unknown_alias_error_lists_user_aliases() {
+        let err = resolve("notahost", &corp_overlay())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("corp"), "error should list user aliases");
+    }
+
     #[test]
     fn expand_single() {
-        let hosts = expand(&["chromium".to_owned()]).unwrap();
+        let hosts = expand(&["chromium".to_owned()], &[]).unwrap();
         assert_eq!(hosts.len(), 1);
         assert_eq!(hosts[0].0, "chromium");
     }
 
     #[test]
     fn expand_comma_separated() {
-        let hosts = expand(&["chromium,go".to_owned()]).unwrap();
+        let hosts = expand(&["chromium,go".to_owned()], &[]).unwrap();
         assert_eq!(hosts.len(), 2);
         assert_eq!(hosts[0].0, "chromium");
         assert_eq!(hosts[1].0, "go");
@@ -147,17 +316,20 @@ mod tests {
 
     #[test]
     fn expand_multiple_flags() {
-        let hosts = expand(&["chromium".to_owned(), "go".to_owned()]).unwrap();
+        let hosts = expand(&["chromium".to_owned(), "go".to_owned()], &[]).unwrap();
         assert_eq!(hosts.len(), 2);
     }
 
     #[test]
     fn expand_deduplicates_by_url() {
         // Same URL, two ways of specifying it.
-        let hosts = expand(&[
-            "chromium".to_owned(),
-            "https://chromium-review.googlesource.com".to_owned(),
-        ])
+        let hosts = expand(
+            &[
+                "chromium".to_owned(),
+                "https://chromium-review.googlesource.com".to_owned(),
+            ],
+            &[],
+        )
         .unwrap();
         assert_eq!(hosts.len(), 1);
     }
@@ -165,11 +337,89 @@ mod tests {
     #[test]
     fn expand_empty_defaults_to_nothing() {
         // The caller (main) provides the default; expand itself doesn't inject one.
-        assert!(expand(&[]).unwrap().is_empty());
+        assert!(expand(&[], &[]).unwrap().is_empty());
     }
 
     #[test]
     fn expand_propagates_unknown_alias_error() {
-        assert!(expand(&["chromium,badhost".to_owned()]).is_err());
+        assert!(expand(&["chromium,badhost".to_owned()], &[]).is_err());
+    }
+
+    // -----------------------------------------------------------------------
+    // Per-host owners
+    // -----------------------------------------------------------------------
+
+    fn owners(values: &[&str]) -> Result<Owners> {
+        Owners::parse(&values.iter().map(|v| v.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn owners_parse_bare_and_pairs() {
+        let o = owners(&[
+            "me@example.com",
+            "chromium=me@chromium.org,corp = me@corp.com",
+        ])
+        .unwrap();
+        assert_eq!(o.default.as_deref(), Some("me@example.com"));
+        assert_eq!(
+            o.overrides,
+            vec![
+                ("chromium".to_owned(), "me@chromium.org".to_owned()),
+                ("corp".to_owned(), "me@corp.com".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn owners_parse_rejects_bad_values() {
+        assert!(owners(&["a@x.com", "b@x.com"]).is_err());
+        assert!(owners(&["=me@x.com"]).is_err());
+        assert!(owners(&["chromium="]).is_err());
+        assert!(owners(&["go=a@x.com", "go=b@x.com"]).is_err());
+        assert!(owners(&[""]).is_err());
+    }
+
+    #[test]
+    fn owners_assign_per_host() {
+        let hosts = expand(&["chromium,go,corp".to_owned()], &corp_overlay()).unwrap();
+
+        let o = owners(&[
+            "chromium=me@chromium.org",
+            "corp=me@corp.com",
+            "me@gmail.com",
+        ])
+        .unwrap();
+        let by_host = o.assign(&hosts).unwrap();
+        assert_eq!(by_host["chromium"], "me@chromium.org");
+        assert_eq!(by_host["corp"], "me@corp.com");
+        assert_eq!(by_host["go"], "me@gmail.com");
+        assert_eq!(o.label(), "me@gmail.com");
+
+        // A bare owner alone still applies everywhere.
+        let by_host = owners(&["me"]).unwrap().assign(&hosts).unwrap();
+        assert!(by_host.values().all(|o| o == "me"));
+    }
+
+    #[test]
+    fn owners_assign_errors() {
+        let hosts = expand(&["chromium,go".to_owned()], &[]).unwrap();
+        // "go" has no owner.
+        let err = owners(&["chromium=a@x.com"])
+            .unwrap()
+            .assign(&hosts)
+            .unwrap_err();
+        assert!(err.to_string().contains("\"go\""), "{err}");
+        // Override for a host that isn't queried.
+        let err = owners(&["me", "android=a@x.com"])
+            .unwrap()
+            .assign(&hosts)
+            .unwrap_err();
+        assert!(err.to_string().contains("android"), "{err}");
+    }
+
+    #[test]
+    fn owners_label_without_default_lists_distinct() {
+        let o = owners(&["chromium=a@x.com", "go=a@x.com", "corp=b@corp.com"]).unwrap();
+        assert_eq!(o.label(), "a@x.com, b@corp.com");
     }
 }
@@ -1,4 +1,11 @@
+pub mod accounts;
+pub mod cache;
+pub mod config;
+pub mod exit;
+pub mod fetch;
 pub mod gerrit;
 pub mod hosts;
+pub mod progress;
+pub mod ratelimit;
 pub mod render;
 pub mod stats;
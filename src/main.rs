@@ -1,16 +1,32 @@
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use chrono::NaiveDate;
 use clap::Parser;
-use tokio::task::JoinSet;
 
+use gerritoscope::cache::Cache;
+use gerritoscope::config::Config;
+use gerritoscope::exit::{ErrorKind, Outcome};
+use gerritoscope::fetch::fetch_per_host;
 use gerritoscope::gerrit::{
-    ChangeInfo, ChangeQuery, ChangeStatus, GerritClient, ReviewEvent, ReviewerQuery,
+    AccountCard, ChangeInfo, ChangeQuery, ChangeStatus, GerritClient, ReviewEvent, ReviewRole,
+    ReviewerQuery,
 };
-use gerritoscope::render::{fmt_count, heatmap_body, heatmap_header};
-use gerritoscope::stats::{Heatmap, Stats};
-use gerritoscope::{hosts, render, stats};
+use gerritoscope::progress::Progress;
+use gerritoscope::render::badge::BadgeMetric;
+use gerritoscope::render::svg::{ColorMode, Palette, SvgLayout, SvgOptions, SvgStack, Theme};
+use gerritoscope::render::{
+    fmt_count, fmt_score_histogram, heatmap_body_colored, heatmap_header, levels_body, peak_label,
+};
+use gerritoscope::render::{FormatOptions, Locale, OutputFormat};
+use gerritoscope::stats::{
+    ComputeOptions, Heatmap, HostCount, ProjectRank, Stats, StatsAccumulator, WeekStart,
+    SMOOTH_WEEKS,
+};
+use gerritoscope::{accounts, hosts, render, stats};
 
 // ---------------------------------------------------------------------------
 // CLI
@@ -22,20 +38,89 @@ use gerritoscope::{hosts, render, stats};
     about = "Fetch Gerrit contribution stats and render a profile heatmap"
 )]
 struct Args {
-    /// Gerrit host(s) to query.  Accepts short aliases (chromium, go, android,
-    /// fuchsia, skia, gerrit, wikimedia, qt, libreoffice, onap), full URLs, or
-    /// comma-separated lists.  May be repeated.  Defaults to "chromium".
+    /// Gerrit host(s) to query.  Accepts short aliases (see --list-hosts), full
+    /// URLs, or comma-separated lists.  May be repeated.  Defaults to "chromium".
     #[arg(long, default_value = "chromium")]
     hosts: Vec<String>,
 
-    /// Account to query — email address, username, or `self`.
+    /// Account to query — email address, username, or `self`.  Prefix with
+    /// `ALIAS=` to set the owner for one host only (e.g.
+    /// `--owner corp=me@corp.com`); a bare value applies to every other host.
+    /// May be repeated or comma-separated.
+    #[arg(
+        long,
+        required_unless_present_any = ["list_hosts", "change", "accounts_file", "owner_file", "raw_query", "theme_list", "theme_preview", "validate_theme"]
+    )]
+    owner: Vec<String>,
+
+    /// Write a card for every owner listed in this file (one --owner value
+    /// per line, `#` starts a comment) into --output-dir, reusing one client
+    /// per host.  Replaces the single-owner report and --output-* files.
+    #[arg(
+        long,
+        conflicts_with_all = ["owner", "change", "summary", "format", "compare", "output_events"]
+    )]
+    accounts_file: Option<PathBuf>,
+
+    /// Like --accounts-file, but each line is `owner<TAB>output.svg[<TAB>theme]`,
+    /// so every card picks its own path and theme (default: --svg-theme).
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "owner", "change", "summary", "format", "accounts_file", "compare", "output_events"
+        ]
+    )]
+    owner_file: Option<PathBuf>,
+
+    /// Directory for --accounts-file cards (created if missing); each is
+    /// named after its owner, e.g. `me_at_example.com.svg`.
+    #[arg(long, default_value = ".")]
+    output_dir: PathBuf,
+
+    /// Fetch and print a single change by number, skipping the owner query.
+    /// Requires exactly one host.
+    #[arg(long)]
+    change: Option<u64>,
+
+    /// Print every known host alias (built-in and from --config) and its URL,
+    /// then exit.
+    #[arg(long)]
+    list_hosts: bool,
+
+    /// Print the built-in theme names, one per line, then exit.
+    #[arg(long)]
+    theme_list: bool,
+
+    /// Render a sample card (synthetic data, no network) for every built-in
+    /// theme into DIR/<theme>.svg, then exit.  The cards end on --as-of if
+    /// given.
+    #[arg(long, value_name = "DIR")]
+    theme_preview: Option<PathBuf>,
+
+    /// Check a built-in theme name or a theme file for valid colours,
+    /// readable text contrast and heatmap levels that stand out from empty
+    /// cells, print the result, then exit.  No network access.
+    #[arg(long, value_name = "NAME_OR_FILE")]
+    validate_theme: Option<String>,
+
+    /// JSON config file.  Its `hosts` map adds custom host aliases, which win
+    /// over built-in aliases of the same name.
     #[arg(long)]
-    owner: String,
+    config: Option<PathBuf>,
 
-    /// Only include changes submitted on or after this date (YYYY-MM-DD).
+    /// Only include changes submitted on or after this date: YYYY-MM-DD, or
+    /// a duration back from today such as 90d, 12w, 6mo or 2y.  Reviews are
+    /// fetched for the last 54 weeks, or from this date if it's earlier.
     #[arg(long)]
     after: Option<String>,
 
+    /// Compute everything as of the end of this day (YYYY-MM-DD) instead of
+    /// now: the heatmap, streaks and the recent window all end here, and later
+    /// changes and reviews are ignored.  For year-in-review cards.
+    #[arg(long, value_name = "DATE")]
+    as_of: Option<String>,
+
     /// HTTP Basic Auth username (for private Gerrit instances).
     #[arg(long)]
     username: Option<String>,
@@ -48,22 +133,395 @@ struct Args {
     #[arg(long)]
     output_md: Option<PathBuf>,
 
+    /// minijinja template to use for --output-md instead of the built-in
+    /// layout.  See the README for the available variables.
+    #[arg(long)]
+    template_file: Option<PathBuf>,
+
     /// Write an SVG heatmap card to this file.
     #[arg(long)]
     output_svg: Option<PathBuf>,
 
-    /// Theme for the SVG card (github, github-light, github-dark, solarized-light,
-    /// solarized-dark, gruvbox-dark, gruvbox-light, tokyo-night, dracula, catppuccin-mocha).
+    /// Also write the card pinned to the theme's light palette (no
+    /// `prefers-color-scheme` switching), e.g. for a `<picture>` source.
+    #[arg(long)]
+    output_svg_light: Option<PathBuf>,
+
+    /// Also write the card pinned to the theme's dark palette.
+    #[arg(long)]
+    output_svg_dark: Option<PathBuf>,
+
+    /// Also write a copy of the card whose cells fade in column by column.
+    /// Plays when the SVG is shown as an image (e.g. `<img>` in a README);
+    /// viewers preferring reduced motion see it static.
+    #[arg(long)]
+    output_svg_animated: Option<PathBuf>,
+
+    /// Theme for the SVG card: a built-in name (see --theme-list), or
+    /// `random` for a built-in picked by the --as-of date, or today's (UTC).
     #[arg(long, default_value = "github")]
     svg_theme: String,
 
+    /// JSON palette file for a custom SVG theme (overrides --svg-theme).  See
+    /// the README for the expected keys.
+    #[arg(long)]
+    svg_theme_file: Option<PathBuf>,
+
     /// Colour each heatmap cell by the dominant Gerrit host/project family.
     #[arg(long)]
     svg_multi_color: bool,
 
+    /// How SVG cells are drawn: none (one square per week) or cls-reviews
+    /// (each week split into a merged-CL segment below and a lighter review
+    /// segment above, sized by share).
+    #[arg(long, default_value = "none")]
+    svg_stack: SvgStack,
+
+    /// SVG card layout: full (title, month labels, stats line) or
+    /// heatmap-only (just the grid, in a card sized to fit it).
+    #[arg(long, default_value = "full")]
+    svg_layout: SvgLayout,
+
+    /// Also carry the SVG stats line as HTML in a `<foreignObject>`, for
+    /// clients that render HTML in SVG; others keep the plain text.  GitHub
+    /// strips `<foreignObject>`, so only useful for self-hosted cards.
+    #[arg(long)]
+    svg_foreign_object: bool,
+
+    /// Print the report to stdout in this format (markdown, svg, csv, badge,
+    /// json, digest, mermaid) instead of the terminal report.  Takes a single value; use the
+    /// --output-* flags to write several formats at once.
+    #[arg(long)]
+    format: Option<OutputFormat>,
+
+    /// Print a single summary line (e.g. for a shell prompt) instead of the
+    /// full report, and skip every --output-* file.
+    #[arg(long)]
+    summary: bool,
+
+    /// Outline the busiest (top-level) weeks in the SVG card.
+    #[arg(long)]
+    svg_highlight_peak: bool,
+
+    /// Show the account's display name and avatar in the SVG card header.
+    /// Costs two extra requests to the first host; hosts that hide account
+    /// details may need --username/--password.  Ignored under
+    /// --anonymize-owner.
+    #[arg(long)]
+    svg_avatar: bool,
+
+    /// Show the owner masked as j***@chromium.org in the terminal report,
+    /// the SVG title and the markdown/JSON output, for cards shared in
+    /// public.  Queries still use the real --owner.  Markdown footer links
+    /// then point at the host rather than an owner: query.  A bare
+    /// --raw-query is shown as "raw query" instead.
+    #[arg(long)]
+    anonymize_owner: bool,
+
+    /// Add a small, muted "gerritoscope vX.Y.Z" to the SVG card's
+    /// bottom-right corner.
+    #[arg(long)]
+    svg_watermark: bool,
+
+    /// Rank the owner among each top project's contributors by merged CLs
+    /// over the heatmap window ("top 5% of 120 contributors").  Costs extra
+    /// queries per project; counting stops at 5,000 CLs and is then marked
+    /// approximate.
+    #[arg(long)]
+    project_rank: bool,
+
+    /// A second owner to compare against: the report (and --output-md)
+    /// becomes a side-by-side markdown table with both heatmaps stacked.
+    /// Other outputs still describe --owner only.
+    #[arg(long, value_name = "OWNER")]
+    compare: Option<String>,
+
+    /// List the N largest merged CLs of the recent window (--recent-days),
+    /// by lines changed, with their subjects and links in the markdown
+    /// report.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    notable: usize,
+
+    /// Length in days of the "recent" window behind the "last 90d" figures
+    /// (and --notable), relabelled to match, e.g. 30 for monthly reports.
+    /// The terminal delta compares against the same length before it.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = stats::DEFAULT_RECENT_DAYS,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    recent_days: u32,
+
+    /// Count patch sets per merged CL and report the average — a measure of
+    /// review rounds.  Requests every revision, which enlarges responses.
+    #[arg(long)]
+    with_revisions: bool,
+
+    /// Shade heatmap cells (terminal and SVG) by a 4-week trailing average
+    /// of the weekly count.  Counts, tooltips and streaks stay raw.
+    #[arg(long)]
+    smooth: bool,
+
+    /// Width of the terminal report's header box and project column.
+    /// Defaults to the terminal's width, then $COLUMNS, then 60.
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u16).range(render::MIN_REPORT_WIDTH as i64..)
+    )]
+    width: Option<u16>,
+
+    /// Colour the terminal heatmap with the active theme's level colours
+    /// (24-bit ANSI).  Ignored when stdout isn't a terminal or NO_COLOR is set.
+    #[arg(long)]
+    color_heatmap: bool,
+
+    /// Glyphs for the text heatmap (terminal and markdown): `blocks`
+    /// (░▒▓█), `ascii` ( .:+#), or exactly five characters for levels 0-4,
+    /// e.g. " .oO@".
+    #[arg(long, default_value = "blocks", value_parser = render::parse_glyphs)]
+    heatmap_glyphs: [char; 5],
+
+    /// Locale for digit grouping and month names in the terminal report,
+    /// markdown, SVG, badge and digest: en (1,234 · Mar), de (1.234 · Mär)
+    /// or fr (1 234 · mar).  CSV and JSON stay locale-independent.
+    #[arg(long, default_value = "en")]
+    locale: Locale,
+
+    /// Write per-week activity as CSV to this file.
+    #[arg(long)]
+    output_csv: Option<PathBuf>,
+
+    /// Write a shields.io endpoint-badge JSON file to this path.
+    #[arg(long)]
+    output_badge: Option<PathBuf>,
+
+    /// Write a short plaintext digest of the latest week to this file.
+    #[arg(long)]
+    output_digest: Option<PathBuf>,
+
+    /// Write the weekly contribution trend as a Mermaid line chart
+    /// (a fenced `xychart-beta` block) to this file, e.g. `trend.md`.
+    #[arg(long)]
+    output_mermaid: Option<PathBuf>,
+
+    /// Write every merged CL and review event as JSON Lines to this file,
+    /// oldest first, for downstream pipelines.  Needs the raw changes, so it
+    /// can't be combined with --stream.
+    #[arg(long, conflicts_with = "stream")]
+    output_events: Option<PathBuf>,
+
+    /// Show how many merged CLs and reviews came from each host in the
+    /// markdown footer and under the SVG stats line.
+    #[arg(long)]
+    show_host_breakdown: bool,
+
+    /// Compute the report from a file written by --output-events instead of
+    /// fetching from Gerrit, e.g. to work on renderers offline or to
+    /// reproduce a bug report.  Votes aren't in the export, so the received
+    /// score histogram is empty.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["stream", "compare", "accounts_file", "owner_file"])]
+    from_json: Option<PathBuf>,
+
+    /// Statistic shown on the badge (merged, reviews, streak).
+    #[arg(long, default_value = "merged")]
+    badge_metric: BadgeMetric,
+
     /// Skip fetching code review activity (faster, but omits review stats).
     #[arg(long)]
     skip_reviews: bool,
+
+    /// Like --skip-reviews, but also leave review rows out of the terminal
+    /// report, markdown and SVG instead of showing them as zero.
+    #[arg(long)]
+    owner_activity_only: bool,
+
+    /// Also fetch open CLs and report how long they've been open, in a
+    /// markdown "Open CLs by age" table.  They never count towards the
+    /// heatmap or totals.
+    #[arg(long)]
+    include_open: bool,
+
+    /// Whether reviews count towards the heatmap cells.  With `false` the
+    /// grid shows only authored CLs while review totals are still reported
+    /// (unlike --skip-reviews, which doesn't fetch them at all).
+    #[arg(long, value_name = "BOOL", default_value_t = true, action = clap::ArgAction::Set)]
+    reviews_in_heatmap: bool,
+
+    /// Which involvement in others' changes counts as review activity:
+    /// reviewer, cc, attention, or several, comma-separated
+    /// (`reviewer,cc`), which match any of them.
+    #[arg(long, value_delimiter = ',', default_value = "reviewer")]
+    review_role: Vec<ReviewRole>,
+
+    /// Stop fetching reviews from a host after this many, keeping the most
+    /// recent.  Bounds the review pass for prolific reviewers; the review
+    /// total is then reported as a lower bound (`5,000+`).
+    #[arg(long, value_name = "N")]
+    max_reviews: Option<usize>,
+
+    /// Only count CLs tagged with this Gerrit hashtag (`perf-sprint` or
+    /// `#perf-sprint`).  Repeatable; a CL with any of them counts.  Reviews
+    /// aren't filtered.
+    #[arg(long, value_name = "TAG")]
+    hashtag: Vec<String>,
+
+    /// Another address the --owner account's CLs were sent from.
+    /// Repeatable; CLs owned by --owner or any of these count once each, on
+    /// every host.  Reviews still use --owner only.
+    #[arg(long, value_name = "EMAIL")]
+    owner_email: Vec<String>,
+
+    /// Send this Gerrit search verbatim instead of the built `owner:`
+    /// query, e.g. `'owner:me is:merged label:Code-Review+2 -age:1y'`.
+    /// Results still paginate and count as usual.  Reviews are only fetched
+    /// when --owner is also given; without it the card is titled with the
+    /// query.
+    #[arg(
+        long,
+        value_name = "QUERY",
+        conflicts_with_all = [
+            "hashtag", "owner_email", "include_open", "after", "compare", "from_json", "accounts_file", "owner_file"
+        ]
+    )]
+    raw_query: Option<String>,
+
+    /// Drop changes and reviews in projects matching this pattern.  `*` is a
+    /// wildcard (`chromium/third_party/*`); a plain path also matches its
+    /// sub-repos.  Repeatable.
+    #[arg(long)]
+    exclude_project: Vec<String>,
+
+    /// Ignore votes from this account (email or username) when counting votes
+    /// received on your CLs.  Repeatable; useful for bots.
+    #[arg(long)]
+    exclude_voter: Vec<String>,
+
+    /// Fail on responses missing Gerrit's `)]}'` XSSI prefix instead of
+    /// accepting bare JSON.
+    #[arg(long)]
+    strict_xssi: bool,
+
+    /// Also print debug notes, such as a host answering without Gerrit's
+    /// XSSI prefix.
+    #[arg(long)]
+    verbose: bool,
+
+    /// Trust this PEM root certificate in addition to the system roots,
+    /// e.g. for a Gerrit behind a corporate CA.
+    #[arg(long, value_name = "PATH")]
+    ca_cert: Option<PathBuf>,
+
+    /// Skip TLS certificate verification (self-signed Gerrits).  Traffic,
+    /// credentials included, can then be intercepted; prefer --ca-cert.
+    #[arg(long, conflicts_with = "ca_cert")]
+    insecure: bool,
+
+    /// Drop changes marked work-in-progress (even merged ones) before
+    /// computing any stats.
+    #[arg(long)]
+    exclude_wip: bool,
+
+    /// Drop merged CLs smaller than this many lines (insertions + deletions)
+    /// before computing any stats.
+    #[arg(long)]
+    min_cl_size: Option<i64>,
+
+    /// Drop merged CLs larger than this many lines (insertions + deletions)
+    /// before computing any stats, e.g. mechanical mass renames.
+    #[arg(long)]
+    max_cl_size: Option<i64>,
+
+    /// Count at most this many insertions and this many deletions per
+    /// merged CL in line totals, so generated vendor bumps still count as
+    /// merges without dominating churn.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    clamp_cl_lines: Option<u32>,
+
+    /// Let a streak skip up to N empty weeks in a row without breaking.
+    /// Skipped weeks don't count towards its length.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    streak_grace: u32,
+
+    /// Day the heatmap weeks begin on: monday (ISO weeks) or sunday, to line
+    /// up with GitHub's contribution graph.
+    #[arg(long, default_value = "monday")]
+    week_start: WeekStart,
+
+    /// Path segments that form a project family for heatmap colouring: 1
+    /// rolls `chromium/src` and `chromium/tools/build` into `chromium`, 2
+    /// keeps `chromium/src` and `chromium/tools` apart.  Multi-host runs
+    /// always colour by host.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    family_depth: u32,
+
+    /// Colour project family FROM as family TO in the heatmap, e.g.
+    /// `webrtc=chromium`.  Applied after --family-depth; top projects keep
+    /// their real names.  Repeatable.
+    #[arg(long, value_name = "FROM=TO", value_parser = parse_family_merge)]
+    merge_families: Vec<(String, String)>,
+
+    /// Cache Gerrit responses in this directory.  Responses with an ETag are
+    /// revalidated on every run (a 304 reuses the cached copy); others are
+    /// reused until --cache-ttl-minutes has passed.
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+
+    /// How long a cached response without an ETag stays valid.
+    #[arg(long, default_value_t = gerritoscope::cache::DEFAULT_TTL_MINUTES)]
+    cache_ttl_minutes: i64,
+
+    /// Give up on a query that still reports more results after this many
+    /// pages of 500, in case a host's pagination never ends.
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = gerritoscope::gerrit::DEFAULT_MAX_PAGES as u32,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    max_pages: u32,
+
+    /// Send at most this many requests per second to each host (e.g. 2.5),
+    /// for hosts that throttle aggressive clients.  Unlimited by default.
+    #[arg(long)]
+    rps: Option<f64>,
+
+    /// Send this User-Agent instead of `gerritoscope/VERSION`.  Must be
+    /// printable ASCII.
+    #[arg(long, value_name = "STRING")]
+    user_agent: Option<String>,
+
+    /// Append a contact (email or URL) to the User-Agent, so a public
+    /// host's admins can reach you instead of blocking the client.
+    #[arg(long, value_name = "EMAIL_OR_URL")]
+    contact: Option<String>,
+
+    /// Fold each page of changes into the stats as it arrives instead of
+    /// holding every change in memory.  Use for very large accounts.
+    #[arg(long)]
+    stream: bool,
+
+    /// Keep going when a host fails: log the error and build the report from
+    /// the hosts that succeeded, with a "⚠ N hosts failed" note.
+    #[arg(long)]
+    continue_on_error: bool,
+
+    /// Exit 3 instead of 2 when --continue-on-error skipped a host or an
+    /// account, so CI can treat a partial report as a failure.
+    #[arg(long)]
+    strict: bool,
+
+    /// Exit with an error, before printing or writing anything, when the
+    /// owner has no merged CLs and no reviews — e.g. expired credentials in
+    /// CI — instead of producing an all-zero card.
+    #[arg(long)]
+    fail_on_empty: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -71,100 +529,712 @@ struct Args {
 // ---------------------------------------------------------------------------
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
     let args = Args::parse();
+    let code = match run(&args).await {
+        Ok(outcome) => outcome.exit_code(args.strict),
+        Err(e) => {
+            // The same report `main() -> Result` would print.
+            eprintln!("Error: {e:?}");
+            ErrorKind::classify(&e).exit_code()
+        }
+    };
+    ExitCode::from(code)
+}
 
-    let resolved = hosts::expand(&args.hosts)?;
-    let query = build_query(&args)?;
-    let prefix_projects = resolved.len() > 1;
+/// The whole run, minus exit-code translation.
+async fn run(args: &Args) -> Result<Outcome> {
+    let config = match args.config {
+        Some(ref path) => Config::load(path)?,
+        None => Config::default(),
+    };
+    let overlay = config.host_overlay();
 
-    let host_list: String = resolved
-        .iter()
-        .map(|(a, _)| a.as_str())
-        .collect::<Vec<_>>()
-        .join(", ");
-    eprintln!("fetching changes for {} from [{}] …", args.owner, host_list);
+    if args.list_hosts {
+        for (alias, url) in hosts::known_host_list(&overlay) {
+            println!("{alias:<12} {url}");
+        }
+        return Ok(Outcome::Complete);
+    }
+    if args.theme_list {
+        for name in render::svg::theme_names() {
+            println!("{name}");
+        }
+        return Ok(Outcome::Complete);
+    }
+    if let Some(ref dir) = args.theme_preview {
+        for path in render::svg::write_theme_previews(dir, reference_now(args)?.date_naive())? {
+            eprintln!("wrote {}", path.display());
+        }
+        return Ok(Outcome::Complete);
+    }
+    if let Some(ref theme) = args.validate_theme {
+        validate_theme(theme)?;
+        return Ok(Outcome::Complete);
+    }
+    if let Some(number) = args.change {
+        let resolved = hosts::expand(&args.hosts, &overlay)?;
+        show_change(&resolved, number, args).await?;
+        return Ok(Outcome::Complete);
+    }
+    // Resolve the theme up front so a bad name or palette file fails before
+    // any network work.
+    let theme_text;
+    let file_theme;
+    let theme = match args.svg_theme_file {
+        Some(ref theme_path) => {
+            theme_text = std::fs::read_to_string(theme_path)
+                .with_context(|| format!("reading {}", theme_path.display()))?;
+            file_theme = render::svg::parse_theme(&theme_text)
+                .with_context(|| format!("loading {}", theme_path.display()))?;
+            &file_theme
+        }
+        None => render::svg::theme_on(&args.svg_theme, reference_now(args)?.date_naive())?,
+    };
+    let svg_opts = SvgOptions {
+        theme,
+        multi_color: args.svg_multi_color,
+        highlight_peak: args.svg_highlight_peak,
+        watermark: args.svg_watermark,
+        stack: args.svg_stack,
+        layout: args.svg_layout,
+        animate: false,
+        foreign_object: args.svg_foreign_object,
+        smooth: args.smooth,
+        mode: ColorMode::Auto,
+        locale: args.locale,
+        account: None,
+        anonymize: args.anonymize_owner,
+    };
+    if args.svg_avatar && args.anonymize_owner {
+        eprintln!("note: --svg-avatar is ignored under --anonymize-owner");
+    }
+
+    let resolved = hosts::expand(&args.hosts, &overlay)?;
+    let clients = ClientSettings::from_args(args)?.clients(&resolved)?;
+
+    if let Some(after) = parse_after(args)? {
+        if after < stats::heatmap_start(reference_now(args)?.date_naive(), args.week_start) {
+            eprintln!(
+                "note: --after {after} predates the heatmap; only the last {} weeks appear in the grid",
+                stats::HEATMAP_WEEKS
+            );
+        }
+    }
 
-    let mut changes = fetch_all(&resolved, &query, &args, prefix_projects).await?;
-    eprintln!("  {} CLs fetched total", changes.len());
+    if let Some(ref path) = args.accounts_file {
+        return run_batch(path, &clients, &resolved, &svg_opts, args).await;
+    }
+    if let Some(ref path) = args.owner_file {
+        return run_owner_file(path, &clients, &resolved, &svg_opts, args).await;
+    }
 
-    // When combining multiple hosts, sort by submitted date so the heatmap
-    // and stats reflect chronological order correctly.
-    if prefix_projects {
-        changes.sort_by_key(|c| c.submitted.unwrap_or(c.updated));
+    let owners = hosts::Owners::parse(&args.owner)?;
+    let owner = &display_owner(&owners, args);
+    let now = reference_now(args)?;
+    let OwnerRun {
+        mut stats,
+        changes,
+        reviews,
+    } = run_for_owner(&clients, &resolved, &owners, now, args).await?;
+    // Same `now` as the main owner, so both heatmaps cover the same weeks.
+    let compared = match args.compare {
+        Some(ref other) => {
+            let others = hosts::Owners::parse(std::slice::from_ref(other))?;
+            let other_stats = run_for_owner(&clients, &resolved, &others, now, args)
+                .await?
+                .stats;
+            Some((display_owner(&others, args), other_stats))
+        }
+        None => None,
+    };
+    if args.project_rank {
+        rank_top_projects(&mut stats, &clients, &resolved, &owners, args).await?;
+    }
+    // Before anything is printed or written.
+    render::check_not_empty(owner, &resolved, &stats, args.fail_on_empty)?;
+    // On stderr so it never ends up in `--format` or `--summary` output.
+    if let Some(note) = render::no_activity_note(owner, &resolved, &stats) {
+        eprintln!("note: {note}");
     }
 
-    let heatmap_after = (chrono::Utc::now() - chrono::Duration::weeks(54)).date_naive();
+    let outcome = Outcome::from_failures(
+        stats.failed_hosts.len() + compared.as_ref().map_or(0, |(_, s)| s.failed_hosts.len()),
+    );
+
+    if args.summary {
+        print_summary(owner, &stats, &args.locale);
+        return Ok(outcome);
+    }
+    let template = match args.template_file {
+        Some(ref template_path) => Some(
+            std::fs::read_to_string(template_path)
+                .with_context(|| format!("reading {}", template_path.display()))?,
+        ),
+        None => None,
+    };
 
-    let reviews: Vec<ReviewEvent> = if args.skip_reviews {
-        vec![]
+    let card = if args.svg_avatar && !args.anonymize_owner {
+        fetch_account_card(&clients, &resolved, &owners, args.verbose).await
     } else {
-        eprintln!("fetching reviews for {} …", args.owner);
-        fetch_all_reviews(&resolved, &args, heatmap_after, prefix_projects).await?
+        None
+    };
+    let opts = FormatOptions {
+        svg: SvgOptions {
+            account: card.as_ref(),
+            ..svg_opts
+        },
+        badge_metric: args.badge_metric,
+        template: template.as_deref(),
+        glyphs: args.heatmap_glyphs,
+        locale: args.locale,
+        anonymize_owner: args.anonymize_owner,
+    };
+    // Under --compare, markdown is the side-by-side comparison.
+    let render_out = |format| match (&compared, format) {
+        (Some((other, other_stats)), OutputFormat::Markdown) => {
+            render::markdown::render_comparison(
+                (owner, &stats),
+                (other, other_stats),
+                &opts.glyphs,
+                &opts.locale,
+            )
+        }
+        _ => render::render_format(format, owner, &resolved, &stats, &opts),
     };
-    eprintln!("  {} review events fetched total", reviews.len());
 
-    let stats = stats::compute(&changes, &reviews, chrono::Utc::now());
-    print_report(&args.owner, &resolved, &stats);
+    match args.format {
+        // Stdout carries the rendered output, so the report is skipped.
+        Some(format) => {
+            let out = render_out(format)?;
+            print!("{out}");
+            if !out.ends_with('\n') {
+                println!();
+            }
+        }
+        None if compared.is_some() => print!("{}", render_out(OutputFormat::Markdown)?),
+        None => {
+            let style = HeatmapStyle {
+                palette: (args.color_heatmap && color_enabled()).then(|| theme.terminal_palette()),
+                glyphs: &args.heatmap_glyphs,
+                smooth: args.smooth,
+                locale: &args.locale,
+                width: report_width(args),
+            };
+            print_report(owner, &resolved, &stats, &style);
+        }
+    }
 
-    if let Some(ref path) = args.output_md {
-        let md = render::markdown::render(&args.owner, &resolved, &stats)?;
-        std::fs::write(path, &md).with_context(|| format!("writing {}", path.display()))?;
+    let outputs = [
+        (&args.output_md, OutputFormat::Markdown),
+        (&args.output_svg, OutputFormat::Svg),
+        (&args.output_csv, OutputFormat::Csv),
+        (&args.output_badge, OutputFormat::Badge),
+        (&args.output_digest, OutputFormat::Digest),
+        (&args.output_mermaid, OutputFormat::Mermaid),
+    ];
+    for (path, format) in outputs {
+        let Some(path) = path else { continue };
+        let out = render_out(format)?;
+        std::fs::write(path, &out).with_context(|| format!("writing {}", path.display()))?;
         eprintln!("wrote {}", path.display());
+        if format == OutputFormat::Svg {
+            warn_svg_size(path, &out, &opts.svg);
+        }
     }
 
-    if let Some(ref path) = args.output_svg {
-        let opts = render::svg::SvgOptions {
-            theme: &args.svg_theme,
-            multi_color: args.svg_multi_color,
-        };
-        let svg = render::svg::render(&args.owner, &resolved, &stats, &opts)?;
-        std::fs::write(path, &svg).with_context(|| format!("writing {}", path.display()))?;
+    if let Some(ref path) = args.output_events {
+        let out = render::events::render(&changes, &reviews)?;
+        std::fs::write(path, &out).with_context(|| format!("writing {}", path.display()))?;
         eprintln!("wrote {}", path.display());
     }
 
-    Ok(())
+    let variants = [
+        (
+            &args.output_svg_light,
+            SvgOptions {
+                mode: ColorMode::Light,
+                ..opts.svg
+            },
+        ),
+        (
+            &args.output_svg_dark,
+            SvgOptions {
+                mode: ColorMode::Dark,
+                ..opts.svg
+            },
+        ),
+        (
+            &args.output_svg_animated,
+            SvgOptions {
+                animate: true,
+                ..opts.svg
+            },
+        ),
+    ];
+    for (path, svg_opts) in variants {
+        let Some(path) = path else { continue };
+        let out = render::svg::render(owner, &resolved, &stats, &svg_opts)?;
+        std::fs::write(path, &out).with_context(|| format!("writing {}", path.display()))?;
+        eprintln!("wrote {}", path.display());
+        warn_svg_size(path, &out, &svg_opts);
+    }
+
+    Ok(outcome)
+}
+
+// ---------------------------------------------------------------------------
+// Pipeline
+// ---------------------------------------------------------------------------
+
+/// What [`run_for_owner`] fetched, and the stats computed from it.
+struct OwnerRun {
+    stats: Stats,
+    /// Every fetched change; empty under `--stream`, which folds pages into
+    /// the stats as they arrive.
+    changes: Vec<ChangeInfo>,
+    reviews: Vec<ReviewEvent>,
+}
+
+/// Fetch `owners`' changes and reviews from every host in `resolved` and
+/// compute their stats as of `now`.  Shared by the single-owner report,
+/// `--compare` and `--accounts-file`, which call it once per owner with the
+/// same `clients` and `now`.
+async fn run_for_owner(
+    clients: &Clients,
+    resolved: &[(String, String)],
+    owners: &hosts::Owners,
+    now: chrono::DateTime<chrono::Utc>,
+    args: &Args,
+) -> Result<OwnerRun> {
+    let owner = display_owner(owners, args);
+    // A raw query needs no owner; without one there are no reviews to fetch.
+    let owner_by_host = if args.raw_query.is_some() && *owners == hosts::Owners::default() {
+        HashMap::new()
+    } else {
+        owners.assign(resolved)?
+    };
+    let after = parse_after(args)?;
+    let queries: HashMap<String, ChangeQuery> = match args.raw_query {
+        Some(ref raw) => resolved
+            .iter()
+            .map(|(alias, _)| {
+                let query = ChangeQuery::raw(raw.as_str()).with_revisions(args.with_revisions);
+                (alias.clone(), query)
+            })
+            .collect(),
+        None => owner_by_host
+            .iter()
+            .map(|(alias, o)| {
+                let query = args
+                    .hashtag
+                    .iter()
+                    .fold(build_query(o, after, args.include_open), |q, tag| {
+                        q.with_hashtag(tag)
+                    });
+                let query = args
+                    .owner_email
+                    .iter()
+                    .fold(query, |q, email| q.with_owner_email(email))
+                    .with_revisions(args.with_revisions);
+                (alias.clone(), query)
+            })
+            .collect(),
+    };
+    let prefix_projects = resolved.len() > 1;
+
+    let compute_opts = ComputeOptions {
+        exclude_voters: args.exclude_voter.clone(),
+        exclude_projects: args.exclude_project.clone(),
+        min_cl_size: args.min_cl_size,
+        max_cl_size: args.max_cl_size,
+        clamp_cl_lines: args.clamp_cl_lines,
+        streak_grace: args.streak_grace,
+        family_depth: args.family_depth as usize,
+        week_start: args.week_start,
+        reviews_outside_heatmap: !args.reviews_in_heatmap,
+        merge_families: args.merge_families.iter().cloned().collect(),
+        notable: args.notable,
+        recent_days: args.recent_days,
+        exclude_wip: args.exclude_wip,
+    };
+
+    if let Some(ref path) = args.from_json {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let (changes, reviews) =
+            render::events::parse(&text).with_context(|| format!("parsing {}", path.display()))?;
+        eprintln!(
+            "loaded {} CLs and {} review events from {}",
+            changes.len(),
+            reviews.len(),
+            path.display()
+        );
+        let stats = stats::compute_with(&changes, &reviews, now, &compute_opts);
+        return Ok(OwnerRun {
+            stats,
+            changes,
+            reviews,
+        });
+    }
+
+    let host_list: String = resolved
+        .iter()
+        .map(|(a, _)| a.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+    eprintln!("fetching changes for {owner} from [{host_list}] …");
+
+    // With --stream, changes are folded into `streamed` page by page and
+    // `changes` stays empty.
+    let (changes, streamed, mut failed_hosts, merged_by_host) = if args.stream {
+        let (acc, failed, merged_by_host) = fetch_all_folded(
+            resolved,
+            &queries,
+            args,
+            clients,
+            prefix_projects,
+            now,
+            &compute_opts,
+        )
+        .await?;
+        (Vec::new(), Some(acc), failed, merged_by_host)
+    } else {
+        let (mut changes, failed, merged_by_host) =
+            fetch_all(resolved, &queries, args, clients, prefix_projects).await?;
+        eprintln!("  {} CLs fetched total", changes.len());
+
+        // When combining multiple hosts, sort by submitted date so the heatmap
+        // and stats reflect chronological order correctly.
+        if prefix_projects {
+            changes.sort_by_key(|c| c.submitted.unwrap_or(c.updated));
+        }
+        stats::dedup_changes(&mut changes);
+        (changes, None, failed, merged_by_host)
+    };
+
+    // Reviews reach back as far as CLs when --after predates their window.
+    let reviews_after = stats::review_lookback_start(after, now);
+
+    let (reviews, reviews_capped, reviews_by_host) =
+        if args.skip_reviews || args.owner_activity_only || owner_by_host.is_empty() {
+            (vec![], false, HashMap::new())
+        } else {
+            eprintln!("fetching reviews for {owner} …");
+            let fetched = fetch_all_reviews(
+                resolved,
+                &owner_by_host,
+                args,
+                clients,
+                reviews_after,
+                prefix_projects,
+            )
+            .await?;
+            failed_hosts.extend(fetched.failed);
+            (fetched.events, fetched.capped, fetched.by_host)
+        };
+    eprintln!("  {} review events fetched total", reviews.len());
+    failed_hosts.sort();
+    failed_hosts.dedup();
+
+    let mut stats = match streamed {
+        Some(mut acc) => {
+            for event in &reviews {
+                acc.add_review(event);
+            }
+            acc.finish()
+        }
+        None => stats::compute_with(&changes, &reviews, now, &compute_opts),
+    };
+    report_client_warnings(clients, resolved, args.verbose);
+    stats.failed_hosts = failed_hosts;
+    stats.reviews_capped = reviews_capped;
+    stats.reviews_since = after.filter(|&a| a == reviews_after);
+    stats.reviews_included = !args.owner_activity_only;
+    if args.show_host_breakdown {
+        stats.host_counts = resolved
+            .iter()
+            .filter_map(|(alias, _)| {
+                Some(HostCount {
+                    alias: alias.clone(),
+                    cls: *merged_by_host.get(alias)?,
+                    reviews: reviews_by_host.get(alias).copied().unwrap_or(0),
+                })
+            })
+            .collect();
+    }
+    Ok(OwnerRun {
+        stats,
+        changes,
+        reviews,
+    })
+}
+
+/// `--accounts-file`: write a card for each listed owner to `--output-dir`
+/// via [`run_cards`].
+async fn run_batch(
+    path: &Path,
+    clients: &Clients,
+    resolved: &[(String, String)],
+    svg_opts: &SvgOptions<'_>,
+    args: &Args,
+) -> Result<Outcome> {
+    let listed = accounts::load(path)?;
+    std::fs::create_dir_all(&args.output_dir)
+        .with_context(|| format!("creating {}", args.output_dir.display()))?;
+    let jobs = accounts::output_paths(&listed, &args.output_dir)
+        .into_iter()
+        .map(|(spec, out_path)| (spec, out_path, svg_opts.theme))
+        .collect();
+    run_cards(jobs, clients, resolved, svg_opts, args).await
+}
+
+/// `--owner-file`: write each [`accounts::JobSpec`]'s card to its own path
+/// and theme via [`run_cards`].
+async fn run_owner_file(
+    path: &Path,
+    clients: &Clients,
+    resolved: &[(String, String)],
+    svg_opts: &SvgOptions<'_>,
+    args: &Args,
+) -> Result<Outcome> {
+    let today = reference_now(args)?.date_naive();
+    let jobs = accounts::load_jobs(path)?
+        .into_iter()
+        .map(|job| {
+            let theme = match job.theme {
+                Some(ref name) => render::svg::theme_on(name, today)?,
+                None => svg_opts.theme,
+            };
+            Ok((job.owner, job.output, theme))
+        })
+        .collect::<Result<_>>()?;
+    run_cards(jobs, clients, resolved, svg_opts, args).await
+}
+
+/// Run [`run_for_owner`] for each `(owner spec, output, theme)` job,
+/// reusing `clients` (and so their connections and cache), and write its
+/// card.
+///
+/// Under `--continue-on-error` an owner that fails is logged and skipped;
+/// otherwise the first failure ends the run.  Either that or a card missing
+/// a failed host makes the run [`Outcome::Partial`].
+async fn run_cards(
+    jobs: Vec<(String, PathBuf, &Theme<'_>)>,
+    clients: &Clients,
+    resolved: &[(String, String)],
+    svg_opts: &SvgOptions<'_>,
+    args: &Args,
+) -> Result<Outcome> {
+    // One `now` for every card, so their heatmaps cover the same weeks.
+    let now = reference_now(args)?;
+    let total = jobs.len();
+    let mut failed = 0usize;
+    let mut incomplete = 0usize;
+    for (spec, out_path, theme) in jobs {
+        let result = async {
+            let owners = hosts::Owners::parse(std::slice::from_ref(&spec))?;
+            let stats = run_for_owner(clients, resolved, &owners, now, args)
+                .await?
+                .stats;
+            let owner = display_owner(&owners, args);
+            render::check_not_empty(&owner, resolved, &stats, args.fail_on_empty)?;
+            if let Some(note) = render::no_activity_note(&owner, resolved, &stats) {
+                eprintln!("note: {note}");
+            }
+            let card = if args.svg_avatar && !args.anonymize_owner {
+                fetch_account_card(clients, resolved, &owners, args.verbose).await
+            } else {
+                None
+            };
+            let svg_opts = SvgOptions {
+                theme,
+                account: card.as_ref(),
+                ..*svg_opts
+            };
+            let svg = render::svg::render(&owner, resolved, &stats, &svg_opts)?;
+            std::fs::write(&out_path, &svg)
+                .with_context(|| format!("writing {}", out_path.display()))?;
+            warn_svg_size(&out_path, &svg, &svg_opts);
+            anyhow::Ok(!stats.failed_hosts.is_empty())
+        }
+        .await
+        .with_context(|| format!("account {spec:?}"));
+
+        match result {
+            Ok(missing_hosts) => {
+                eprintln!("wrote {}", out_path.display());
+                incomplete += usize::from(missing_hosts);
+            }
+            Err(e) if args.continue_on_error => {
+                eprintln!("warning: {e:#}");
+                failed += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    if failed > 0 {
+        eprintln!("{failed} of {total} accounts failed");
+    }
+    Ok(Outcome::from_failures(failed + incomplete))
 }
 
 // ---------------------------------------------------------------------------
 // Fetching
 // ---------------------------------------------------------------------------
 
-/// Fetch changes from all hosts concurrently.
+/// One client per host alias, built once and shared by every owner fetched
+/// in the run.
+type Clients = HashMap<String, GerritClient>;
+
+/// Print the warnings each host's client gathered, in host order, plus
+/// its debug notes under `--verbose`.  Called once fetching is done so they
+/// land below the progress board.
+fn report_client_warnings(clients: &Clients, resolved: &[(String, String)], verbose: bool) {
+    for (alias, _) in resolved {
+        report_client(alias, &clients[alias], verbose);
+    }
+}
+
+/// Print one client's warnings and, under `--verbose`, its debug notes.
+fn report_client(alias: &str, client: &GerritClient, verbose: bool) {
+    for warning in client.take_warnings() {
+        eprintln!("warning: {alias}: {warning}");
+    }
+    let notes = client.take_debug_notes();
+    if verbose {
+        for note in notes {
+            eprintln!("debug: {alias}: {note}");
+        }
+    }
+}
+
+/// Per-client settings from the command line.
+struct ClientSettings {
+    auth: Option<(String, String)>,
+    strict_xssi: bool,
+    cache: Option<Cache>,
+    ca_cert: Option<PathBuf>,
+    insecure: bool,
+    max_pages: usize,
+    rps: Option<f64>,
+    user_agent: Option<String>,
+    contact: Option<String>,
+}
+
+impl ClientSettings {
+    fn from_args(args: &Args) -> Result<Self> {
+        let cache = match args.cache_dir {
+            Some(ref dir) => Some(Cache::new(
+                dir,
+                chrono::Duration::minutes(args.cache_ttl_minutes),
+            )?),
+            None => None,
+        };
+        if args.insecure {
+            eprintln!(
+                "WARNING: --insecure disables TLS certificate verification; \
+                 responses and credentials can be intercepted. Prefer --ca-cert."
+            );
+        }
+        Ok(Self {
+            auth: args.username.clone().zip(args.password.clone()),
+            strict_xssi: args.strict_xssi,
+            cache,
+            ca_cert: args.ca_cert.clone(),
+            insecure: args.insecure,
+            max_pages: args.max_pages as usize,
+            rps: args.rps,
+            user_agent: args.user_agent.clone(),
+            contact: args.contact.clone(),
+        })
+    }
+
+    fn client(&self, url: &str) -> Result<GerritClient> {
+        let mut builder = GerritClient::builder(url)
+            .strict_xssi(self.strict_xssi)
+            .insecure(self.insecure)
+            .max_pages(self.max_pages);
+        if let Some((ref u, ref p)) = self.auth {
+            builder = builder.auth(u, p);
+        }
+        if let Some(ref cache) = self.cache {
+            builder = builder.cache(cache.clone());
+        }
+        if let Some(ref path) = self.ca_cert {
+            builder = builder.ca_cert(path);
+        }
+        if let Some(rps) = self.rps {
+            builder = builder.rps(rps);
+        }
+        if let Some(ref user_agent) = self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+        if let Some(ref contact) = self.contact {
+            builder = builder.contact(contact);
+        }
+        builder.build()
+    }
+
+    fn clients(&self, resolved: &[(String, String)]) -> Result<Clients> {
+        resolved
+            .iter()
+            .map(|(alias, url)| Ok((alias.clone(), self.client(url)?)))
+            .collect()
+    }
+}
+
+/// Fetch changes from all hosts concurrently, each with its own query from
+/// `queries` (keyed by alias; see [`hosts::Owners::assign`]).
 ///
 /// When `prefix_projects` is true (i.e. more than one host), each
 /// `ChangeInfo.project` is prefixed with `"alias::"` so that
 /// `stats::project_family` can group heatmap colours by host.
+///
+/// Returns the changes plus the aliases of any hosts that failed under
+/// `--continue-on-error`.
 async fn fetch_all(
     resolved: &[(String, String)],
-    query: &ChangeQuery,
+    queries: &HashMap<String, ChangeQuery>,
     args: &Args,
+    clients: &Clients,
     prefix_projects: bool,
-) -> Result<Vec<ChangeInfo>> {
-    let mut set: JoinSet<Result<(String, Vec<ChangeInfo>)>> = JoinSet::new();
-
-    for (alias, url) in resolved {
-        let alias = alias.clone();
-        let url = url.clone();
-        let query = query.clone();
-        let username = args.username.clone();
-        let password = args.password.clone();
-
-        set.spawn(async move {
-            let client = GerritClient::new(&url)?;
-            let client = match (&username, &password) {
-                (Some(u), Some(p)) => client.with_auth(u, p),
-                _ => client,
-            };
-            let changes = client.fetch_changes(&query).await?;
-            Ok((alias, changes))
-        });
-    }
+) -> Result<(Vec<ChangeInfo>, Vec<String>, HashMap<String, usize>)> {
+    let progress = Progress::stderr();
+    let results = fetch_per_host(
+        resolved,
+        args.continue_on_error,
+        &progress,
+        |alias, _url| {
+            let query = queries[&alias].clone();
+            let client = clients[&alias].clone();
+            let bar = progress.add(&alias);
+            async move {
+                let mut changes = Vec::new();
+                let fetched = client
+                    .fetch_changes_each(&query, |page| {
+                        bar.page(page.len());
+                        changes.extend(page);
+                    })
+                    .await;
+                bar.end(&fetched);
+                fetched?;
+                Ok(changes)
+            }
+        },
+    )
+    .await?;
 
+    let failed = results.failed_aliases();
     let mut all = Vec::new();
-    while let Some(result) = set.join_next().await {
-        let (alias, mut changes) = result.context("task panicked")??;
+    let mut merged_by_host = HashMap::new();
+    for (alias, mut changes) in results.ok {
         eprintln!("  {} CLs from {alias}", changes.len());
+        let merged = changes
+            .iter()
+            .filter(|c| c.status == ChangeStatus::Merged)
+            .count();
+        merged_by_host.insert(alias.clone(), merged);
         if prefix_projects {
             for c in &mut changes {
                 c.project = format!("{alias}::{}", c.project);
@@ -172,61 +1242,342 @@ async fn fetch_all(
         }
         all.extend(changes);
     }
-    Ok(all)
+    Ok((all, failed, merged_by_host))
+}
+
+/// Like `fetch_all`, but folds each page into a per-host
+/// [`StatsAccumulator`] as it arrives instead of keeping the changes.
+///
+/// Each host gets its own accumulator, merged only once that host has
+/// finished, so a host that fails part-way under `--continue-on-error`
+/// contributes nothing rather than a partial count.  Repeats of a change
+/// are skipped as [`stats::dedup_changes`] does, at the cost of one change
+/// number per CL.
+async fn fetch_all_folded(
+    resolved: &[(String, String)],
+    queries: &HashMap<String, ChangeQuery>,
+    args: &Args,
+    clients: &Clients,
+    prefix_projects: bool,
+    now: chrono::DateTime<chrono::Utc>,
+    opts: &ComputeOptions,
+) -> Result<(StatsAccumulator, Vec<String>, HashMap<String, usize>)> {
+    let progress = Progress::stderr();
+    let results = fetch_per_host(
+        resolved,
+        args.continue_on_error,
+        &progress,
+        |alias, _url| {
+            let query = queries[&alias].clone();
+            let opts = opts.clone();
+            let client = clients[&alias].clone();
+            let bar = progress.add(&alias);
+            async move {
+                let mut acc = StatsAccumulator::new(now, &opts);
+                let mut merged = 0;
+                let mut seen = HashSet::new();
+                let n = client
+                    .fetch_changes_each(&query, |page| {
+                        bar.page(page.len());
+                        for mut c in page {
+                            if !seen.insert(c.number) {
+                                continue;
+                            }
+                            merged += usize::from(c.status == ChangeStatus::Merged);
+                            if prefix_projects {
+                                c.project = format!("{alias}::{}", c.project);
+                            }
+                            acc.add_change(&c);
+                        }
+                    })
+                    .await;
+                bar.end(&n);
+                let n = n?;
+                Ok((acc, n, merged))
+            }
+        },
+    )
+    .await?;
+
+    let failed = results.failed_aliases();
+    let mut total = StatsAccumulator::new(now, opts);
+    let mut count = 0;
+    let mut merged_by_host = HashMap::new();
+    for (alias, (acc, n, merged)) in results.ok {
+        eprintln!("  {n} CLs from {alias}");
+        count += n;
+        total.merge(acc);
+        merged_by_host.insert(alias, merged);
+    }
+    eprintln!("  {count} CLs fetched total");
+    Ok((total, failed, merged_by_host))
+}
+
+/// What [`fetch_all_reviews`] collected across hosts.
+struct ReviewFetch {
+    events: Vec<ReviewEvent>,
+    /// Aliases of hosts that failed under `--continue-on-error`.
+    failed: Vec<String>,
+    /// Some host stopped at `--max-reviews`.
+    capped: bool,
+    /// Review events per host alias.
+    by_host: HashMap<String, usize>,
 }
 
 /// Fetch review events from all hosts concurrently.
 ///
-/// Mirrors `fetch_all` but uses `ReviewerQuery` and `fetch_review_events`.
+/// Mirrors `fetch_all` but uses `ReviewerQuery` and `fetch_review_events`,
+/// with each host's reviewer taken from `reviewers` (keyed by alias).
 async fn fetch_all_reviews(
     resolved: &[(String, String)],
+    reviewers: &HashMap<String, String>,
     args: &Args,
+    clients: &Clients,
     after: chrono::NaiveDate,
     prefix_projects: bool,
-) -> Result<Vec<ReviewEvent>> {
-    let mut set: JoinSet<Result<(String, Vec<ReviewEvent>)>> = JoinSet::new();
-
-    for (alias, url) in resolved {
-        let alias = alias.clone();
-        let url = url.clone();
-        let reviewer = args.owner.clone();
-        let username = args.username.clone();
-        let password = args.password.clone();
-
-        set.spawn(async move {
-            let client = GerritClient::new(&url)?;
-            let client = match (&username, &password) {
-                (Some(u), Some(p)) => client.with_auth(u, p),
-                _ => client,
-            };
-            let query = ReviewerQuery::new(&reviewer).with_after(after);
-            let events = client.fetch_review_events(&query).await?;
-            Ok((alias, events))
-        });
-    }
+) -> Result<ReviewFetch> {
+    let progress = Progress::stderr();
+    let results = fetch_per_host(
+        resolved,
+        args.continue_on_error,
+        &progress,
+        |alias, _url| {
+            let reviewer = reviewers[&alias].clone();
+            let client = clients[&alias].clone();
+            let mut query = ReviewerQuery::new(&reviewer)
+                .with_after(after)
+                .with_roles(&args.review_role);
+            if let Some(n) = args.max_reviews {
+                query = query.with_limit(n);
+            }
+            async move { client.fetch_review_events(&query).await }
+        },
+    )
+    .await?;
 
+    let failed = results.failed_aliases();
     let mut all = Vec::new();
-    while let Some(result) = set.join_next().await {
-        let (alias, mut events) = result.context("task panicked")??;
-        eprintln!("  {} review events from {alias}", events.len());
+    let mut capped = false;
+    let mut by_host = HashMap::new();
+    for (alias, mut fetched) in results.ok {
+        if fetched.capped {
+            eprintln!(
+                "  {} review events from {alias} (stopped at --max-reviews)",
+                fetched.events.len()
+            );
+        } else {
+            eprintln!("  {} review events from {alias}", fetched.events.len());
+        }
+        capped |= fetched.capped;
+        by_host.insert(alias.clone(), fetched.events.len());
         if prefix_projects {
-            for e in &mut events {
+            for e in &mut fetched.events {
                 e.project = format!("{alias}::{}", e.project);
             }
         }
-        all.extend(events);
+        all.extend(fetched.events);
     }
-    Ok(all)
+    Ok(ReviewFetch {
+        events: all,
+        failed,
+        capped,
+        by_host,
+    })
 }
 
-fn build_query(args: &Args) -> Result<ChangeQuery> {
-    let mut q = ChangeQuery::new(&args.owner).with_status(ChangeStatus::Merged);
-    if let Some(ref s) = args.after {
-        let date = NaiveDate::parse_from_str(s, "%Y-%m-%d")
-            .with_context(|| format!("--after value {s:?} is not YYYY-MM-DD"))?;
-        q = q.with_after(date);
+/// Most merged changes counted per project for `--project-rank`.
+const PROJECT_RANK_CAP: usize = 5000;
+
+/// `--project-rank`: fill in [`ProjectStat::rank`] for each of `stats`'
+/// top projects from a count of everyone's merged changes there over the
+/// heatmap window.
+///
+/// Multi-host project names carry an `alias::` prefix, which picks the host
+/// to ask.  A project that fails to count is a warning and stays unranked.
+async fn rank_top_projects(
+    stats: &mut Stats,
+    clients: &Clients,
+    resolved: &[(String, String)],
+    owners: &hosts::Owners,
+    args: &Args,
+) -> Result<()> {
+    let now = reference_now(args)?;
+    let after = stats::heatmap_start(now.date_naive(), args.week_start);
+    let before = now.date_naive() + chrono::Duration::days(1);
+
+    for p in &mut stats.top_projects {
+        let (alias, project) = match p.name.split_once("::") {
+            Some((alias, project)) if resolved.len() > 1 => (alias, project),
+            _ => (resolved[0].0.as_str(), p.name.as_str()),
+        };
+        let (Some(client), Some(owner)) = (clients.get(alias), owners.for_host(alias)) else {
+            continue;
+        };
+        eprintln!("counting contributors to {} …", p.name);
+        match client
+            .project_activity(project, after, before, owner, PROJECT_RANK_CAP)
+            .await
+        {
+            Ok(activity) => {
+                p.rank = activity.own_rank().map(|(rank, contributors)| ProjectRank {
+                    rank,
+                    contributors,
+                    approximate: activity.approximate,
+                });
+            }
+            Err(e) => eprintln!("warning: --project-rank {}: {e:#}", p.name),
+        }
+    }
+    Ok(())
+}
+
+/// `--validate-theme`: resolve `name_or_file` as a theme file if it exists,
+/// else as a built-in name, and print `pass` or its warnings.
+fn validate_theme(name_or_file: &str) -> Result<()> {
+    let path = Path::new(name_or_file);
+    let text;
+    let file_theme;
+    let theme = if path.is_file() {
+        text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        file_theme = render::svg::parse_theme(&text)
+            .with_context(|| format!("loading {}", path.display()))?;
+        &file_theme
+    } else {
+        render::svg::theme_by_name(name_or_file)?
+    };
+    let warnings = render::svg::theme_warnings(theme);
+    if warnings.is_empty() {
+        println!("pass: {name_or_file}");
+    }
+    for warning in &warnings {
+        println!("warn: {warning}");
+    }
+    Ok(())
+}
+
+/// Warn when a written SVG card is too big for GitHub to display reliably.
+fn warn_svg_size(path: &Path, svg: &str, opts: &SvgOptions<'_>) {
+    if let Some(warning) = render::svg::size_warning(svg, opts) {
+        eprintln!("warning: {}: {warning}", path.display());
+    }
+}
+
+/// Look up the SVG header's name and avatar on the first resolved host.
+///
+/// The card is decoration, so a failed lookup is a warning and the card
+/// falls back to the bare owner.
+async fn fetch_account_card(
+    clients: &Clients,
+    resolved: &[(String, String)],
+    owners: &hosts::Owners,
+    verbose: bool,
+) -> Option<AccountCard> {
+    let (alias, _) = resolved.first()?;
+    let owner = owners.for_host(alias)?;
+    let card = clients[alias].fetch_account(owner).await;
+    report_client_warnings(clients, resolved, verbose);
+    match card {
+        Ok(card) => Some(card),
+        Err(e) => {
+            eprintln!("warning: --svg-avatar: {e:#}");
+            None
+        }
+    }
+}
+
+/// Fetch one change from the single resolved host and print its details.
+async fn show_change(resolved: &[(String, String)], number: u64, args: &Args) -> Result<()> {
+    let [(alias, url)] = resolved else {
+        bail!("--change needs exactly one host, got {}", resolved.len());
+    };
+    let client = ClientSettings::from_args(args)?.client(url)?;
+    let change = client
+        .fetch_change(number)
+        .await
+        .with_context(|| format!("fetching change {number} from {alias}"))?;
+    report_client(alias, &client, args.verbose);
+    print_change(&change);
+    Ok(())
+}
+
+/// Parse a `--merge-families FROM=TO` value.
+fn parse_family_merge(s: &str) -> Result<(String, String)> {
+    match s.split_once('=') {
+        Some((from, to)) if !from.trim().is_empty() && !to.trim().is_empty() => {
+            Ok((from.trim().to_owned(), to.trim().to_owned()))
+        }
+        _ => bail!("--merge-families {s:?} should be FROM=TO, e.g. webrtc=chromium"),
+    }
+}
+
+/// The owner as shown in reports: [`hosts::Owners::label`], with each owner
+/// masked under `--anonymize-owner`.
+fn display_owner(owners: &hosts::Owners, args: &Args) -> String {
+    // With --raw-query alone there's no owner to show; the query stands in,
+    // or a placeholder when anonymized since it may name addresses anywhere.
+    if *owners == hosts::Owners::default() {
+        if let Some(ref raw) = args.raw_query {
+            return if args.anonymize_owner {
+                "raw query".to_owned()
+            } else {
+                raw.clone()
+            };
+        }
+    }
+    let label = owners.label();
+    if !args.anonymize_owner {
+        return label;
+    }
+    label
+        .split(", ")
+        .map(render::mask_email)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `--after` as a date; relative durations count back from
+/// [`reference_now`].
+fn parse_after(args: &Args) -> Result<Option<NaiveDate>> {
+    let Some(ref s) = args.after else {
+        return Ok(None);
+    };
+    let today = reference_now(args)?.date_naive();
+    stats::parse_relative_or_date(s, today)
+        .context("--after")
+        .map(Some)
+}
+
+/// The reference instant for stats: the end of the `--as-of` day, or now.
+fn reference_now(args: &Args) -> Result<chrono::DateTime<chrono::Utc>> {
+    let now = chrono::Utc::now();
+    let Some(ref s) = args.as_of else {
+        return Ok(now);
+    };
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .with_context(|| format!("--as-of value {s:?} is not YYYY-MM-DD"))?;
+    if date > now.date_naive() {
+        bail!("--as-of {date} is in the future");
+    }
+    // One nanosecond before the next midnight, so the whole day counts.
+    let end = (date + chrono::Duration::days(1)).and_time(chrono::NaiveTime::MIN);
+    Ok(end.and_utc() - chrono::Duration::nanoseconds(1))
+}
+
+/// The change query for one host's owner: merged and abandoned CLs in one
+/// pass, plus open ones when `include_open`, told apart by their status in
+/// [`stats::compute_with`].
+fn build_query(owner: &str, after: Option<NaiveDate>, include_open: bool) -> ChangeQuery {
+    let q = ChangeQuery::new(owner);
+    let q = if include_open {
+        q
+    } else {
+        q.with_status(ChangeStatus::Closed)
+    };
+    match after {
+        Some(date) => q.with_after(date),
+        None => q,
     }
-    Ok(q)
 }
 
 // ---------------------------------------------------------------------------
@@ -237,9 +1588,22 @@ const GREEN: &str = "\x1b[32m";
 const RED: &str = "\x1b[31m";
 const RESET: &str = "\x1b[0m";
 
-fn print_report(owner: &str, hosts: &[(String, String)], s: &Stats) {
-    let width = 60;
-    let bar = "─".repeat(width);
+/// How [`print_heatmap`] draws the grid.
+struct HeatmapStyle<'a> {
+    /// Level colours for 24-bit ANSI output; `None` prints plain glyphs.
+    palette: Option<&'a Palette<'a>>,
+    glyphs: &'a [char; 5],
+    /// Shade by the trailing average instead of raw counts (`--smooth`).
+    smooth: bool,
+    /// Month names for the header; [`print_report`] also groups its counts
+    /// with it.
+    locale: &'a Locale,
+    /// Inner width of [`print_report`]'s header box; see [`report_width`].
+    width: usize,
+}
+
+fn print_report(owner: &str, hosts: &[(String, String)], s: &Stats, style: &HeatmapStyle<'_>) {
+    let loc = style.locale;
 
     let host_label: String = hosts
         .iter()
@@ -248,67 +1612,211 @@ fn print_report(owner: &str, hosts: &[(String, String)], s: &Stats) {
         .join(", ");
 
     println!();
-    println!("┌{bar}┐");
-    println!("│  gerritoscope · {owner:<width$}│", width = width - 17);
-    println!("│  hosts: {host_label:<width$}│", width = width - 9);
-    println!("└{bar}┘");
+    for line in render::report_header(owner, &host_label, style.width) {
+        println!("{line}");
+    }
 
-    print_heatmap(&s.heatmap);
+    print_heatmap(&s.heatmap, style);
+
+    if let Some(note) = render::failed_hosts_note(&s.failed_hosts) {
+        println!();
+        println!("  {note}");
+    }
 
     println!();
+    // Deltas are against the equally long window before; a capped review
+    // fetch may not reach back that far, so it gets none.
+    let days = s.recent_days;
     println!(
-        "  Merged CLs     {:>7} all time   ·  {:>7} last 90d",
-        fmt_count(s.total_merged as i64),
-        fmt_count(s.recent_merged_90d as i64),
+        "  Merged CLs     {:>7} all time   ·  {:>7} {:<6} last {days}d",
+        fmt_count(s.total_merged as i64, loc),
+        fmt_count(s.recent_merged_90d as i64, loc),
+        render::fmt_delta(s.recent_merged_90d, s.prev_merged_90d, loc),
     );
+    if s.reviews_included {
+        let delta = if s.reviews_capped {
+            String::new()
+        } else {
+            render::fmt_delta(s.recent_reviews_90d, s.prev_reviews_90d, loc)
+        };
+        let window = match s.reviews_since {
+            Some(date) => format!("since {date}"),
+            None => "last year".to_owned(),
+        };
+        println!(
+            "  Reviews done   {:>7} {window:<11}·  {:>7} {delta:<6} last {days}d",
+            render::fmt_total_reviews(s, loc),
+            fmt_count(s.recent_reviews_90d as i64, loc),
+        );
+    }
+    let wks = |n| format!("{n} wk{}", if n == 1 { "" } else { "s" });
     println!(
-        "  Reviews done   {:>7} last year  ·  {:>7} last 90d",
-        fmt_count(s.total_reviews as i64),
-        fmt_count(s.recent_reviews_90d as i64),
+        "  Streak             current {} ·    longest {}",
+        wks(s.current_streak()),
+        wks(s.longest_streak()),
     );
+    println!("  Longest break      {}", wks(s.heatmap.longest_gap()));
+    println!("  Density            {:.1}/active wk", s.heatmap.density());
+    if let Some(avg) = s.avg_revisions_per_cl {
+        println!("  Patch sets         {avg:.1}/merged CL");
+    }
+    if let Some(rate) = s.abandon_rate().filter(|_| s.total_abandoned > 0) {
+        println!(
+            "  Abandoned          {} ({:.0}% of closed)",
+            render::fmt_count(s.total_abandoned as i64, loc),
+            rate * 100.0,
+        );
+    }
+    println!("  Active             {}", render::active_range(s));
     println!(
-        "  Streak             current {} wks ·    longest {} wks",
-        s.heatmap.current_streak(),
-        s.heatmap.longest_streak(),
+        "  Projects           {}",
+        fmt_count(s.distinct_projects as i64, loc)
     );
+    if let Some(top) = s.top_projects.first() {
+        println!(
+            "  Most active        {} ({:.0}%)",
+            render::truncate(&top.name, render::project_name_width(style.width)),
+            s.concentration * 100.0,
+        );
+    }
     println!(
         "  Lines changed      {GREEN}+{}{RESET} / {RED}-{}{RESET}",
-        fmt_count(s.total_insertions),
-        fmt_count(s.total_deletions),
+        fmt_count(s.total_insertions, loc),
+        fmt_count(s.total_deletions, loc),
     );
+    if s.reviews_included {
+        println!(
+            "  Lines reviewed     {GREEN}+{}{RESET} / {RED}-{}{RESET}",
+            fmt_count(s.reviewed_insertions, loc),
+            fmt_count(s.reviewed_deletions, loc),
+        );
+    }
+    if !s.received_score_histogram.is_empty() {
+        println!(
+            "  Votes received     {}",
+            fmt_score_histogram(&s.received_score_histogram, loc),
+        );
+    }
 
     if !s.top_projects.is_empty() {
         println!();
         println!("  Top projects");
+        let name_width = render::project_name_width(style.width);
         for p in &s.top_projects {
             println!(
-                "    {:<36} {:>5} CLs  {GREEN}+{}{RESET} / {RED}-{}{RESET}",
-                truncate(&p.name, 36),
-                fmt_count(p.merged as i64),
-                fmt_count(p.insertions),
-                fmt_count(p.deletions),
+                "    {:<name_width$} {:>5} CLs  {GREEN}+{}{RESET} / {RED}-{}{RESET}",
+                render::truncate(&p.name, name_width),
+                fmt_count(p.merged as i64, loc),
+                fmt_count(p.insertions, loc),
+                fmt_count(p.deletions, loc),
             );
+            if let Some(ref rank) = p.rank {
+                println!("      {}", render::fmt_project_rank(rank, loc));
+            }
         }
     }
 
     println!();
 }
 
-fn print_heatmap(h: &Heatmap) {
+fn print_summary(owner: &str, s: &Stats, locale: &Locale) {
+    println!("{}", render::summary_line(owner, s, locale));
+}
+
+fn print_change(c: &ChangeInfo) {
+    let fmt_ts = |t: chrono::DateTime<chrono::Utc>| t.format("%Y-%m-%d %H:%M UTC").to_string();
+    println!();
+    println!("  {} {}", c.number, c.subject);
+    println!("  Project            {} ({})", c.project, c.branch);
+    println!("  Created            {}", fmt_ts(c.created));
+    println!("  Status             {:?}", c.status);
+    if let Some(owner) = &c.owner {
+        let name = owner
+            .email
+            .as_deref()
+            .or(owner.username.as_deref())
+            .unwrap_or("?");
+        println!("  Owner              {name}");
+    }
+    println!("  Updated            {}", fmt_ts(c.updated));
+    if let Some(t) = c.submitted {
+        println!("  Submitted          {}", fmt_ts(t));
+    }
+    println!(
+        "  Size               {GREEN}+{}{RESET} / {RED}-{}{RESET}",
+        c.insertions, c.deletions
+    );
+    if let Some(label) = c.labels.get(stats::REVIEW_LABEL) {
+        for vote in label.all.iter().filter(|v| v.value.unwrap_or(0) != 0) {
+            let who = vote
+                .account
+                .email
+                .as_deref()
+                .or(vote.account.username.as_deref())
+                .unwrap_or("?");
+            println!(
+                "  {:<18} {:+} {who}",
+                stats::REVIEW_LABEL,
+                vote.value.unwrap_or(0)
+            );
+        }
+    }
+    println!();
+}
+
+fn print_heatmap(h: &Heatmap, style: &HeatmapStyle<'_>) {
+    let levels = if style.smooth {
+        h.smoothed(SMOOTH_WEEKS)
+    } else {
+        h.levels()
+    };
+    let body = match style.palette {
+        Some(p) => heatmap_body_colored(&levels, style.glyphs, p),
+        None => levels_body(&levels, style.glyphs),
+    };
     println!();
-    println!("  {}", heatmap_header(h));
-    println!("  [{}]", heatmap_body(h));
-    println!("  peak: {} contributions/week", h.max_count);
+    println!("  {}", heatmap_header(h, style.locale));
+    println!("  [{body}]");
+    println!("  {}", peak_label(h));
 }
 
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
 
-fn truncate(s: &str, max: usize) -> String {
-    if s.len() <= max {
-        s.to_owned()
-    } else {
-        format!("{}…", &s[..max - 1])
+/// Whether stdout is a terminal and the user hasn't opted out via `NO_COLOR`
+/// (<https://no-color.org>).
+fn color_enabled() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+/// Inner width of the terminal report: `--width`, else the terminal's
+/// columns less the box borders, else [`render::REPORT_WIDTH`].
+fn report_width(args: &Args) -> usize {
+    args.width
+        .map(usize::from)
+        .or_else(|| terminal_columns().map(|cols| cols.saturating_sub(2)))
+        .unwrap_or(render::REPORT_WIDTH)
+        .max(render::MIN_REPORT_WIDTH)
+}
+
+/// Columns of the terminal on stdout, falling back to `$COLUMNS` when
+/// stdout isn't a terminal or the platform can't say.
+fn terminal_columns() -> Option<usize> {
+    #[cfg(unix)]
+    {
+        let mut size = libc::winsize {
+            ws_row: 0,
+            ws_col: 0,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        // SAFETY: TIOCGWINSZ only writes a `winsize` through the pointer,
+        // which is valid for the duration of the call.
+        let ok = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } == 0;
+        if ok && size.ws_col > 0 {
+            return Some(size.ws_col.into());
+        }
     }
+    std::env::var("COLUMNS").ok()?.trim().parse().ok()
 }
@@ -0,0 +1,290 @@
+//! Live per-host fetch progress on stderr.
+//!
+//! Each host gets one line (`chromium  12 pages · 5,830 CLs`), redrawn in
+//! place as pages arrive from the concurrent per-host fetches.  Drawing needs
+//! cursor movement, so it only happens when stderr is a terminal; otherwise
+//! every call is a no-op and redirected output keeps just the plain
+//! `N CLs from <host>` summary lines.  Anything else printed to stderr while
+//! the board is up must go through [`Progress::println`], or the next
+//! redraw overwrites it.
+
+use std::io::{IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+
+/// Shared set of per-host progress lines.  Cloning is cheap: clones draw
+/// to the same lines.
+#[derive(Clone)]
+pub struct Progress {
+    /// `None` when not drawing (stderr isn't a terminal).
+    board: Option<Arc<Mutex<Board>>>,
+}
+
+/// One host's line in a [`Progress`].
+pub struct HostBar {
+    board: Option<Arc<Mutex<Board>>>,
+    row: usize,
+}
+
+struct Board {
+    out: Box<dyn Write + Send>,
+    rows: Vec<Row>,
+    /// Lines drawn by the previous redraw, to move the cursor back over.
+    drawn: usize,
+}
+
+struct Row {
+    label: String,
+    pages: usize,
+    changes: usize,
+    state: RowState,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RowState {
+    Fetching,
+    Done,
+    Failed,
+}
+
+impl Progress {
+    /// Draw to stderr if it's a terminal, else do nothing.
+    pub fn stderr() -> Self {
+        Self::to_writer(std::io::stderr(), std::io::stderr().is_terminal())
+    }
+
+    /// Draw to `out` when `tty` is set; with it unset nothing is written.
+    pub fn to_writer(out: impl Write + Send + 'static, tty: bool) -> Self {
+        let board = tty.then(|| {
+            Arc::new(Mutex::new(Board {
+                out: Box::new(out),
+                rows: Vec::new(),
+                drawn: 0,
+            }))
+        });
+        Self { board }
+    }
+
+    /// Add a line for the host called `label`.
+    pub fn add(&self, label: &str) -> HostBar {
+        let row = match &self.board {
+            Some(board) => {
+                let mut board = board.lock().unwrap();
+                board.rows.push(Row {
+                    label: label.to_owned(),
+                    pages: 0,
+                    changes: 0,
+                    state: RowState::Fetching,
+                });
+                board.redraw();
+                board.rows.len() - 1
+            }
+            None => 0,
+        };
+        HostBar {
+            board: self.board.clone(),
+            row,
+        }
+    }
+
+    /// Print `line` to stderr above the board, which is then redrawn below
+    /// it.  Without a board this is a plain `eprintln!`.
+    pub fn println(&self, line: &str) {
+        match &self.board {
+            Some(board) => board.lock().unwrap().println(line),
+            None => eprintln!("{line}"),
+        }
+    }
+}
+
+impl HostBar {
+    /// Record one more page holding `changes` changes.
+    pub fn page(&self, changes: usize) {
+        self.update(|row| {
+            row.pages += 1;
+            row.changes += changes;
+        });
+    }
+
+    /// Mark the host's fetch as complete.
+    pub fn finish(&self) {
+        self.update(|row| row.state = RowState::Done);
+    }
+
+    /// Mark the host's fetch as failed.
+    pub fn fail(&self) {
+        self.update(|row| row.state = RowState::Failed);
+    }
+
+    /// [`finish`](Self::finish) or [`fail`](Self::fail) according to
+    /// `result`.
+    pub fn end<T, E>(&self, result: &Result<T, E>) {
+        match result {
+            Ok(_) => self.finish(),
+            Err(_) => self.fail(),
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Row)) {
+        let Some(board) = &self.board else { return };
+        let mut board = board.lock().unwrap();
+        f(&mut board.rows[self.row]);
+        board.redraw();
+    }
+}
+
+impl Board {
+    /// Clear the board, print `line` where it stood, and redraw below it.
+    fn println(&mut self, line: &str) {
+        let mut text = String::new();
+        if self.drawn > 0 {
+            text.push_str(&format!("\x1b[{}A", self.drawn));
+        }
+        text.push_str(&format!("\r\x1b[J{line}\n"));
+        let _ = self.out.write_all(text.as_bytes());
+        self.drawn = 0;
+        self.redraw();
+    }
+
+    fn redraw(&mut self) {
+        let width = self.rows.iter().map(|r| r.label.len()).max().unwrap_or(0);
+        let mut text = String::new();
+        if self.drawn > 0 {
+            text.push_str(&format!("\x1b[{}A", self.drawn));
+        }
+        for row in &self.rows {
+            text.push_str(&format!(
+                "\r\x1b[2K  {:<width$}  {} page{} · {} CLs{}\n",
+                row.label,
+                row.pages,
+                if row.pages == 1 { "" } else { "s" },
+                row.changes,
+                match row.state {
+                    RowState::Fetching => " …",
+                    RowState::Done => " ✓",
+                    RowState::Failed => " ✗",
+                },
+            ));
+        }
+        self.drawn = self.rows.len();
+        // Progress is cosmetic; a failed write shouldn't fail the fetch.
+        let _ = self.out.write_all(text.as_bytes());
+        let _ = self.out.flush();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A writer the test can read back after handing a clone to `Progress`.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuf {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn non_tty_writes_nothing() {
+        let buf = SharedBuf::default();
+        let progress = Progress::to_writer(buf.clone(), false);
+        let bar = progress.add("chromium");
+        bar.page(500);
+        bar.finish();
+        assert_eq!(buf.text(), "");
+    }
+
+    #[test]
+    fn tty_redraws_one_line_per_host() {
+        let buf = SharedBuf::default();
+        let progress = Progress::to_writer(buf.clone(), true);
+        let chromium = progress.add("chromium");
+        let go = progress.add("go");
+        chromium.page(500);
+        chromium.page(120);
+        go.page(3);
+        go.finish();
+
+        let text = buf.text();
+        // Each redraw after the first moves back over the lines it replaces.
+        assert!(text.contains("\x1b[2A"), "{text:?}");
+        let last = text.rsplit("\x1b[2A").next().unwrap();
+        assert!(last.contains("chromium  2 pages · 620 CLs …"), "{last:?}");
+        assert!(last.contains("go        1 page · 3 CLs ✓"), "{last:?}");
+    }
+
+    /// Replay the escape sequences the board uses onto a list of lines,
+    /// as a terminal would.
+    fn screen(text: &str) -> Vec<String> {
+        let mut lines = vec![String::new()];
+        let mut row = 0;
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            rest = &rest[c.len_utf8()..];
+            match c {
+                '\x1b' => {
+                    let end = rest.find(|c: char| c.is_ascii_alphabetic()).unwrap();
+                    let (arg, cmd) = (&rest[1..end], &rest[end..=end]);
+                    rest = &rest[end + 1..];
+                    match cmd {
+                        "A" => row -= arg.parse::<usize>().unwrap(),
+                        "K" => lines[row].clear(),
+                        // Only ever sent after `\r`, so the whole line goes.
+                        "J" => {
+                            lines.truncate(row + 1);
+                            lines[row].clear();
+                        }
+                        _ => panic!("unexpected escape {cmd:?}"),
+                    }
+                }
+                '\r' => {}
+                '\n' => {
+                    row += 1;
+                    if lines.len() == row {
+                        lines.push(String::new());
+                    }
+                }
+                c => lines[row].push(c),
+            }
+        }
+        lines.pop_if(|l| l.is_empty());
+        lines
+    }
+
+    #[test]
+    fn tty_message_survives_later_redraws() {
+        let buf = SharedBuf::default();
+        let progress = Progress::to_writer(buf.clone(), true);
+        let chromium = progress.add("chromium");
+        let go = progress.add("go");
+        chromium.page(500);
+        go.fail();
+        progress.println("  ⚠ go failed: HTTP 503");
+        chromium.page(20);
+        chromium.end(&Ok::<_, ()>(()));
+
+        assert_eq!(
+            screen(&buf.text()),
+            [
+                "  ⚠ go failed: HTTP 503",
+                "  chromium  2 pages · 520 CLs ✓",
+                "  go        0 pages · 0 CLs ✗",
+            ]
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Client-side request pacing (`--rps`).
+//!
+//! A token bucket holding a single token, refilled every `1 / rps` seconds:
+//! each request takes the token or waits for the next one, so requests to a
+//! host are spread evenly instead of arriving in bursts that busy hosts
+//! such as chromium-review throttle.  Waiters are served in the order they
+//! arrived, which keeps concurrent pagination fair.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use tokio::time::Instant;
+
+/// Paces requests to at most `rps` per second.  Clones share the bucket.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    interval: Duration,
+    /// When the next token becomes available.
+    next: Arc<Mutex<Instant>>,
+}
+
+impl RateLimiter {
+    /// A limiter allowing `rps` requests per second; `rps` must be positive
+    /// and finite.
+    pub fn new(rps: f64) -> Result<Self> {
+        if !(rps.is_finite() && rps > 0.0) {
+            bail!("requests per second must be a positive number, got {rps}");
+        }
+        let now = Instant::now();
+        // A rate this small leaves a gap no Duration or Instant can hold.
+        let interval = Duration::try_from_secs_f64(1.0 / rps)
+            .ok()
+            .filter(|&gap| now.checked_add(gap).is_some());
+        let Some(interval) = interval else {
+            bail!("requests per second is too small to pace, got {rps}");
+        };
+        Ok(Self {
+            interval,
+            next: Arc::new(Mutex::new(now)),
+        })
+    }
+
+    /// Wait until a request may be sent.
+    pub async fn acquire(&self) {
+        let at = {
+            let mut next = self.next.lock().unwrap();
+            let at = (*next).max(Instant::now());
+            *next = at + self.interval;
+            at
+        };
+        tokio::time::sleep_until(at).await;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_non_positive_rates() {
+        for rps in [0.0, -1.0, f64::NAN, f64::INFINITY, 1e-20, 1e-300] {
+            assert!(RateLimiter::new(rps).is_err(), "{rps}");
+        }
+    }
+
+    #[tokio::test]
+    async fn paces_requests_to_the_rate() {
+        let limiter = RateLimiter::new(10.0).unwrap();
+        let start = Instant::now();
+        for _ in 0..5 {
+            limiter.acquire().await;
+        }
+        // The first request goes at once, the other four 100 ms apart.
+        assert!(start.elapsed() >= Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn clones_share_the_bucket() {
+        let limiter = RateLimiter::new(10.0).unwrap();
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move { limiter.acquire().await })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+}
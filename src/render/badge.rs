@@ -0,0 +1,191 @@
+//! shields.io endpoint-badge renderer.
+//!
+//! Produces the JSON shape expected by
+//! [shields.io's endpoint badge](https://shields.io/badges/endpoint-badge):
+//!
+//! ```json
+//! {"schemaVersion":1,"label":"gerrit CLs","message":"1,234","color":"brightgreen"}
+//! ```
+
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+use crate::stats::Stats;
+
+use super::{fmt_count, fmt_total_reviews, Locale};
+
+// ---------------------------------------------------------------------------
+// Metric selection
+// ---------------------------------------------------------------------------
+
+/// Which statistic the badge displays.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BadgeMetric {
+    /// Total merged CLs (all time).
+    #[default]
+    Merged,
+    /// Reviews performed within the fetch window.
+    Reviews,
+    /// Current weekly streak.
+    Streak,
+}
+
+impl BadgeMetric {
+    fn label(self) -> &'static str {
+        match self {
+            BadgeMetric::Merged => "gerrit CLs",
+            BadgeMetric::Reviews => "gerrit reviews",
+            BadgeMetric::Streak => "gerrit streak",
+        }
+    }
+
+    fn value(self, stats: &Stats) -> i64 {
+        match self {
+            BadgeMetric::Merged => stats.total_merged as i64,
+            BadgeMetric::Reviews => stats.total_reviews as i64,
+            BadgeMetric::Streak => stats.current_streak() as i64,
+        }
+    }
+
+    /// Value at or above which the badge turns `brightgreen`.
+    fn bright_threshold(self) -> i64 {
+        match self {
+            BadgeMetric::Merged | BadgeMetric::Reviews => 100,
+            BadgeMetric::Streak => 10,
+        }
+    }
+}
+
+impl FromStr for BadgeMetric {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "merged" => Ok(BadgeMetric::Merged),
+            "reviews" => Ok(BadgeMetric::Reviews),
+            "streak" => Ok(BadgeMetric::Streak),
+            other => bail!("unknown badge metric {other:?}; valid names: merged, reviews, streak"),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Rendering
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+struct Badge {
+    #[serde(rename = "schemaVersion")]
+    schema_version: u32,
+    label: &'static str,
+    message: String,
+    color: &'static str,
+}
+
+/// Render the shields.io endpoint JSON for `metric`.
+///
+/// Colour scales with the value: `lightgrey` for zero, `green` below the
+/// metric's threshold, `brightgreen` at or above it.
+pub fn render(stats: &Stats, metric: BadgeMetric, locale: &Locale) -> Result<String> {
+    let value = metric.value(stats);
+    let color = if value == 0 {
+        "lightgrey"
+    } else if value < metric.bright_threshold() {
+        "green"
+    } else {
+        "brightgreen"
+    };
+    let message = match metric {
+        BadgeMetric::Streak => format!("{} wk", fmt_count(value, locale)),
+        BadgeMetric::Reviews => fmt_total_reviews(stats, locale),
+        BadgeMetric::Merged => fmt_count(value, locale),
+    };
+    let badge = Badge {
+        schema_version: 1,
+        label: metric.label(),
+        message,
+        color,
+    };
+    Ok(serde_json::to_string(&badge)?)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats;
+    use chrono::{TimeZone, Utc};
+
+    fn stats_with(merged: usize, reviews: usize, streak_weeks: usize) -> Stats {
+        let now = Utc.with_ymd_and_hms(2024, 6, 12, 12, 0, 0).unwrap();
+        let mut s = stats::compute(&[], &[], now);
+        s.total_merged = merged;
+        s.total_reviews = reviews;
+        let n = s.heatmap.weeks.len();
+        for b in &mut s.heatmap.weeks[n - streak_weeks..] {
+            b.count = 1;
+        }
+        s
+    }
+
+    #[test]
+    fn merged_badge_exact_shape() {
+        let json = render(&stats_with(1234, 0, 0), BadgeMetric::Merged, &Locale::EN).unwrap();
+        assert_eq!(
+            json,
+            r#"{"schemaVersion":1,"label":"gerrit CLs","message":"1,234","color":"brightgreen"}"#
+        );
+    }
+
+    #[test]
+    fn reviews_badge_uses_fmt_count() {
+        let json = render(&stats_with(0, 42, 0), BadgeMetric::Reviews, &Locale::EN).unwrap();
+        assert!(json.contains(r#""label":"gerrit reviews""#));
+        assert!(json.contains(r#""message":"42""#));
+        assert!(json.contains(r#""color":"green""#));
+    }
+
+    #[test]
+    fn capped_reviews_badge_is_marked() {
+        let mut s = stats_with(0, 5000, 0);
+        s.reviews_capped = true;
+        let json = render(&s, BadgeMetric::Reviews, &Locale::EN).unwrap();
+        assert!(json.contains(r#""message":"5,000+""#), "{json}");
+    }
+
+    #[test]
+    fn streak_badge_reports_weeks() {
+        let json = render(&stats_with(0, 0, 3), BadgeMetric::Streak, &Locale::EN).unwrap();
+        assert!(json.contains(r#""label":"gerrit streak""#));
+        assert!(json.contains(r#""message":"3 wk""#));
+        assert!(json.contains(r#""color":"green""#));
+    }
+
+    #[test]
+    fn zero_value_is_grey() {
+        let json = render(&stats_with(0, 0, 0), BadgeMetric::Merged, &Locale::EN).unwrap();
+        assert!(json.contains(r#""color":"lightgrey""#));
+    }
+
+    #[test]
+    fn metric_from_str() {
+        assert_eq!(
+            "merged".parse::<BadgeMetric>().unwrap(),
+            BadgeMetric::Merged
+        );
+        assert_eq!(
+            "reviews".parse::<BadgeMetric>().unwrap(),
+            BadgeMetric::Reviews
+        );
+        assert_eq!(
+            "streak".parse::<BadgeMetric>().unwrap(),
+            BadgeMetric::Streak
+        );
+        assert!("stars".parse::<BadgeMetric>().is_err());
+    }
+}
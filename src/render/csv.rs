@@ -0,0 +1,92 @@
+//! Per-week CSV export for spreadsheets.
+//!
+//! One header row followed by one row per [`WeekBucket`], oldest first:
+//!
+//! ```text
+//! week_start,cls,reviews,lines_changed,dominant_family
+//! 2024-06-03,2,5,340,chromium
+//! ```
+
+use crate::stats::{Heatmap, WeekBucket};
+
+/// Column header line (without trailing newline).
+pub const HEADER: &str = "week_start,cls,reviews,lines_changed,dominant_family";
+
+/// Render the heatmap as CSV with `\n` line endings.
+pub fn render(h: &Heatmap) -> String {
+    let mut out = String::from(HEADER);
+    out.push('\n');
+    for b in &h.weeks {
+        out.push_str(&row(b));
+        out.push('\n');
+    }
+    out
+}
+
+fn row(b: &WeekBucket) -> String {
+    format!(
+        "{},{},{},{},{}",
+        b.week_start.format("%Y-%m-%d"),
+        b.count - b.review_count,
+        b.review_count,
+        b.lines_changed,
+        quote(b.dominant_family().unwrap_or("")),
+    )
+}
+
+/// Quote a field per RFC 4180 when it contains a comma, quote, or newline.
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::{self, HEATMAP_WEEKS};
+    use chrono::{TimeZone, Utc};
+
+    fn empty_heatmap() -> Heatmap {
+        let now = Utc.with_ymd_and_hms(2024, 6, 12, 12, 0, 0).unwrap();
+        stats::compute(&[], &[], now).heatmap
+    }
+
+    #[test]
+    fn one_row_per_week_plus_header() {
+        let csv = render(&empty_heatmap());
+        assert_eq!(csv.lines().count(), HEATMAP_WEEKS + 1);
+        assert_eq!(csv.lines().next(), Some(HEADER));
+    }
+
+    #[test]
+    fn known_bucket_serialises() {
+        let mut h = empty_heatmap();
+        let last = h.weeks.last_mut().unwrap();
+        last.count = 7;
+        last.review_count = 5;
+        last.lines_changed = 340;
+        last.family_counts.insert("chromium".to_owned(), 7);
+        let csv = render(&h);
+        assert_eq!(csv.lines().last(), Some("2024-06-10,2,5,340,chromium"));
+    }
+
+    #[test]
+    fn empty_bucket_has_blank_family() {
+        let csv = render(&empty_heatmap());
+        assert!(csv.lines().nth(1).unwrap().ends_with(",0,0,0,"));
+    }
+
+    #[test]
+    fn quote_escapes_commas_and_quotes() {
+        assert_eq!(quote("plain"), "plain");
+        assert_eq!(quote("a,b"), "\"a,b\"");
+        assert_eq!(quote("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,158 @@
+//! Plaintext weekly digest, e.g. for a cron job that mails it out.
+//!
+//! Focuses on the most recent heatmap week and how it compares with the one
+//! before:
+//!
+//! ```text
+//! Week of 2024-06-10: you merged 4 CLs (+210/-30) and reviewed 9,
+//! keeping a 6-week streak. That's up from 7 contributions last week.
+//! ```
+
+use crate::stats::{Stats, WeekBucket};
+
+use super::{fmt_count, Locale};
+
+/// Render the digest paragraph for the latest week, ending in a newline.
+pub fn render(stats: &Stats, locale: &Locale) -> String {
+    let weeks = &stats.heatmap.weeks;
+    let Some(current) = weeks.last() else {
+        return "No activity this week.\n".to_owned();
+    };
+    let previous = weeks.len().checked_sub(2).map(|i| &weeks[i]);
+    let week = current.week_start.format("%Y-%m-%d");
+
+    if current.count == 0 {
+        let mut out = format!("Week of {week}: no activity this week.");
+        if let Some(prev) = previous.filter(|p| p.count > 0) {
+            out.push_str(&format!(" Last week: {}.", activity(prev, locale)));
+        }
+        out.push('\n');
+        return out;
+    }
+
+    let streak = stats.current_streak();
+    let streak_note = if streak > 1 {
+        format!("keeping a {streak}-week streak")
+    } else {
+        "starting a new streak".to_owned()
+    };
+    let mut out = format!(
+        "Week of {week}: you {}, {streak_note}.",
+        activity(current, locale)
+    );
+
+    if let Some(prev) = previous {
+        let delta = match current.count.cmp(&prev.count) {
+            std::cmp::Ordering::Greater => {
+                format!(" That's up from {} last week.", contributions(prev.count))
+            }
+            std::cmp::Ordering::Less => {
+                format!(" That's down from {} last week.", contributions(prev.count))
+            }
+            std::cmp::Ordering::Equal => " Same as last week.".to_owned(),
+        };
+        out.push_str(&delta);
+    }
+    out.push('\n');
+    out
+}
+
+/// `"merged 4 CLs (+210/-30) and reviewed 9"`, dropping whichever half is
+/// zero.
+fn activity(b: &WeekBucket, locale: &Locale) -> String {
+    let cls = b.count - b.review_count;
+    let merged = format!(
+        "merged {} CL{} (+{}/-{})",
+        cls,
+        if cls == 1 { "" } else { "s" },
+        fmt_count(b.insertions, locale),
+        fmt_count(b.deletions, locale),
+    );
+    let reviewed = format!("reviewed {}", fmt_count(b.review_count as i64, locale));
+    match (cls, b.review_count) {
+        (_, 0) => merged,
+        (0, _) => reviewed,
+        _ => format!("{merged} and {reviewed}"),
+    }
+}
+
+fn contributions(n: u32) -> String {
+    format!("{n} contribution{}", if n == 1 { "" } else { "s" })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats;
+    use chrono::{TimeZone, Utc};
+
+    /// Stats whose last two weeks hold `(cls, reviews, insertions, deletions)`
+    /// for the previous and current week respectively.
+    fn stats_with(prev: (u32, u32, i64, i64), current: (u32, u32, i64, i64)) -> Stats {
+        let now = Utc.with_ymd_and_hms(2024, 6, 12, 12, 0, 0).unwrap();
+        let mut s = stats::compute(&[], &[], now);
+        let n = s.heatmap.weeks.len();
+        let weeks = s.heatmap.weeks[n - 2..].iter_mut().zip([prev, current]);
+        for (b, (cls, reviews, insertions, deletions)) in weeks {
+            b.count = cls + reviews;
+            b.review_count = reviews;
+            b.lines_changed = insertions + deletions;
+            b.insertions = insertions;
+            b.deletions = deletions;
+        }
+        s
+    }
+
+    #[test]
+    fn empty_week() {
+        let out = render(&stats_with((0, 0, 0, 0), (0, 0, 0, 0)), &Locale::EN);
+        assert_eq!(out, "Week of 2024-06-10: no activity this week.\n");
+    }
+
+    #[test]
+    fn empty_week_mentions_last_week() {
+        let out = render(&stats_with((2, 1, 30, 10), (0, 0, 0, 0)), &Locale::EN);
+        assert_eq!(
+            out,
+            "Week of 2024-06-10: no activity this week. \
+             Last week: merged 2 CLs (+30/-10) and reviewed 1.\n"
+        );
+    }
+
+    #[test]
+    fn up_from_last_week() {
+        let out = render(&stats_with((3, 4, 80, 20), (4, 9, 210, 30)), &Locale::EN);
+        assert_eq!(
+            out,
+            "Week of 2024-06-10: you merged 4 CLs (+210/-30) and reviewed 9, \
+             keeping a 2-week streak. That's up from 7 contributions last week.\n"
+        );
+    }
+
+    #[test]
+    fn down_from_last_week() {
+        let out = render(&stats_with((0, 0, 0, 0), (1, 0, 1_500, 0)), &Locale::EN);
+        assert!(out.contains("merged 1 CL (+1,500/-0), starting a new streak."));
+        assert!(!out.contains("down"), "{out}");
+
+        let out = render(&stats_with((5, 0, 0, 0), (0, 2, 0, 0)), &Locale::EN);
+        assert!(out.contains("you reviewed 2, keeping a 2-week streak."));
+        assert!(out.contains("That's down from 5 contributions last week."));
+    }
+
+    #[test]
+    fn groups_large_review_counts() {
+        let out = render(&stats_with((0, 0, 0, 0), (0, 1_200, 0, 0)), &Locale::EN);
+        assert!(out.contains("you reviewed 1,200,"), "{out}");
+    }
+
+    #[test]
+    fn same_as_last_week() {
+        let out = render(&stats_with((1, 1, 10, 0), (2, 0, 10, 0)), &Locale::EN);
+        assert!(out.ends_with("Same as last week.\n"), "{out}");
+    }
+}
@@ -0,0 +1,346 @@
+//! JSON Lines export of the raw events behind the stats, for data
+//! warehouses and other downstream pipelines, and the matching import for
+//! `--from-json`.
+//!
+//! One object per merged CL, abandoned CL and review event, oldest first:
+//!
+//! ```text
+//! {"type":"merged","project":"chromium/src","date":"2024-06-03","timestamp":"2024-06-03T14:02:11Z","number":5561234,"subject":"Fix crash","insertions":120,"deletions":4}
+//! {"type":"review","project":"chromium/src","date":"2024-06-04","timestamp":"2024-06-04T09:30:00Z","author":"bob@example.com","change_insertions":37,"change_deletions":2}
+//! ```
+//!
+//! Events are exported as fetched, before `--exclude-project` and the CL-size
+//! filters.  Reviews carry the reviewed change's size as `change_insertions`
+//! and `change_deletions` rather than `insertions`/`deletions`: the size
+//! Gerrit reports is the change's latest patch set, and summing it alongside
+//! merged CLs would double-count lines.
+//!
+//! [`parse`] reads the export back into changes and review events, so the
+//! stats can be recomputed offline.  Votes aren't exported, so the received
+//! score histogram comes back empty.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::gerrit::{ChangeInfo, ChangeStatus, ReviewEvent};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Kind {
+    Merged,
+    Abandoned,
+    Review,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Event {
+    #[serde(rename = "type")]
+    kind: Kind,
+    project: String,
+    date: NaiveDate,
+    /// Exact time of the event; files written before it existed fall back
+    /// to midnight UTC on `date`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timestamp: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    number: Option<u64>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    subject: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    insertions: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deletions: Option<i32>,
+    /// Patch set count, when fetched with `--with-revisions`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    revisions: Option<usize>,
+    /// Set on changes flagged work-in-progress, so `--exclude-wip` still
+    /// applies to an import.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    work_in_progress: bool,
+    /// Owner of the reviewed change.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    change_insertions: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    change_deletions: Option<i32>,
+}
+
+impl Event {
+    fn change(kind: Kind, c: &ChangeInfo, at: DateTime<Utc>) -> Self {
+        Event {
+            kind,
+            project: c.project.clone(),
+            date: at.date_naive(),
+            timestamp: Some(at),
+            number: Some(c.number),
+            subject: c.subject.clone(),
+            insertions: Some(c.insertions),
+            deletions: Some(c.deletions),
+            revisions: c.revision_count,
+            work_in_progress: c.work_in_progress,
+            author: None,
+            change_insertions: None,
+            change_deletions: None,
+        }
+    }
+
+    fn review(r: &ReviewEvent) -> Self {
+        Event {
+            kind: Kind::Review,
+            project: r.project.clone(),
+            date: r.timestamp.date_naive(),
+            timestamp: Some(r.timestamp),
+            number: None,
+            subject: String::new(),
+            insertions: None,
+            deletions: None,
+            revisions: None,
+            work_in_progress: false,
+            author: r.author.clone(),
+            change_insertions: Some(r.insertions),
+            change_deletions: Some(r.deletions),
+        }
+    }
+
+    fn time(&self) -> DateTime<Utc> {
+        self.timestamp
+            .unwrap_or_else(|| self.date.and_time(chrono::NaiveTime::MIN).and_utc())
+    }
+
+    /// The change this event records, for merged and abandoned events.
+    fn into_change(self) -> ChangeInfo {
+        let at = self.time();
+        let (status, submitted) = match self.kind {
+            Kind::Merged => (ChangeStatus::Merged, Some(at)),
+            _ => (ChangeStatus::Abandoned, None),
+        };
+        ChangeInfo {
+            id: String::new(),
+            number: self.number.unwrap_or(0),
+            subject: self.subject,
+            project: self.project,
+            branch: String::new(),
+            status,
+            created: at,
+            updated: at,
+            submitted,
+            insertions: self.insertions.unwrap_or(0),
+            deletions: self.deletions.unwrap_or(0),
+            work_in_progress: self.work_in_progress,
+            more_changes: None,
+            messages: Vec::new(),
+            owner: None,
+            labels: Default::default(),
+            revision_count: self.revisions,
+        }
+    }
+
+    fn into_review(self) -> ReviewEvent {
+        ReviewEvent {
+            timestamp: self.time(),
+            project: self.project,
+            insertions: self.change_insertions.unwrap_or(0),
+            deletions: self.change_deletions.unwrap_or(0),
+            author: self.author,
+        }
+    }
+}
+
+/// Render merged and abandoned `changes` and `reviews` as JSON Lines,
+/// ordered by timestamp (changes first on ties), each line ending in `\n`.
+///
+/// Open changes, and merged ones lacking a `submitted` timestamp, are
+/// skipped.  Abandoned changes are dated by their last update.
+pub fn render(changes: &[ChangeInfo], reviews: &[ReviewEvent]) -> Result<String> {
+    let closed = changes.iter().filter_map(|c| match c.status {
+        ChangeStatus::Merged => Some(Event::change(Kind::Merged, c, c.submitted?)),
+        ChangeStatus::Abandoned => Some(Event::change(Kind::Abandoned, c, c.updated)),
+        _ => None,
+    });
+    let reviewed = reviews.iter().map(Event::review);
+
+    let mut events: Vec<Event> = closed.chain(reviewed).collect();
+    // Stable, so changes stay ahead of reviews with the same timestamp.
+    events.sort_by_key(Event::time);
+
+    let mut out = String::new();
+    for event in &events {
+        out.push_str(&serde_json::to_string(event)?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Read an export written by [`render`] back into changes and review
+/// events.  Blank lines are ignored.
+pub fn parse(text: &str) -> Result<(Vec<ChangeInfo>, Vec<ReviewEvent>)> {
+    let mut changes = Vec::new();
+    let mut reviews = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event: Event = serde_json::from_str(line).with_context(|| format!("line {}", i + 1))?;
+        match event.kind {
+            Kind::Review => reviews.push(event.into_review()),
+            Kind::Merged | Kind::Abandoned => changes.push(event.into_change()),
+        }
+    }
+    Ok((changes, reviews))
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn change(project: &str, status: ChangeStatus, submitted: Option<DateTime<Utc>>) -> ChangeInfo {
+        let created = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        ChangeInfo {
+            id: String::new(),
+            number: 1,
+            subject: String::new(),
+            project: project.to_owned(),
+            branch: "main".to_owned(),
+            status,
+            created,
+            updated: submitted.unwrap_or(created),
+            submitted,
+            insertions: 10,
+            deletions: 2,
+            work_in_progress: false,
+            owner: None,
+            labels: Default::default(),
+            messages: Vec::new(),
+            more_changes: None,
+            revision_count: None,
+        }
+    }
+
+    #[test]
+    fn two_cls_and_a_review_give_three_ordered_lines() {
+        let day = |d| Utc.with_ymd_and_hms(2024, 6, d, 12, 0, 0).unwrap();
+        let changes = [
+            change("b", ChangeStatus::Merged, Some(day(5))),
+            change("a", ChangeStatus::Merged, Some(day(3))),
+            change("open", ChangeStatus::New, None),
+        ];
+        let reviews = [ReviewEvent {
+            timestamp: day(4),
+            project: "c".to_owned(),
+            insertions: 7,
+            deletions: 0,
+            author: None,
+        }];
+
+        let out = render(&changes, &reviews).unwrap();
+        let lines: Vec<serde_json::Value> = out
+            .lines()
+            .map(|l| serde_json::from_str(l).expect("each line is JSON"))
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert!(out.ends_with('\n'));
+
+        let projects: Vec<&str> = lines
+            .iter()
+            .map(|v| v["project"].as_str().unwrap())
+            .collect();
+        assert_eq!(projects, ["a", "c", "b"]);
+        assert_eq!(lines[0]["type"], "merged");
+        assert_eq!(lines[0]["date"], "2024-06-03");
+        assert_eq!(lines[0]["insertions"], 10);
+        assert_eq!(lines[1]["type"], "review");
+        assert!(lines[1].get("insertions").is_none());
+    }
+
+    #[test]
+    fn no_events_is_empty_output() {
+        assert_eq!(render(&[], &[]).unwrap(), "");
+    }
+
+    #[test]
+    fn export_then_import_gives_identical_stats() {
+        use crate::stats::{compute_with, ComputeOptions};
+
+        let at = |d, h| Utc.with_ymd_and_hms(2024, 6, d, h, 30, 0).unwrap();
+        let mut merged = change("chromium/src", ChangeStatus::Merged, Some(at(3, 8)));
+        merged.number = 42;
+        merged.subject = "Fix \"quoted\" crash".to_owned();
+        merged.revision_count = Some(3);
+        let mut abandoned = change("v8/v8", ChangeStatus::Abandoned, None);
+        abandoned.updated = at(5, 23);
+        let changes = [
+            merged,
+            abandoned,
+            change("angle/angle", ChangeStatus::Merged, Some(at(10, 0))),
+            change("open", ChangeStatus::New, None),
+        ];
+        let reviews = [ReviewEvent {
+            timestamp: at(4, 17),
+            project: "chromium/src".to_owned(),
+            insertions: 7,
+            deletions: 1,
+            author: Some("bob@example.com".to_owned()),
+        }];
+        let now = at(12, 12);
+        let opts = ComputeOptions {
+            notable: 3,
+            ..Default::default()
+        };
+
+        let exported = render(&changes, &reviews).unwrap();
+        let (changes_in, reviews_in) = parse(&exported).unwrap();
+        assert_eq!(changes_in.len(), 3);
+        // Open CLs aren't events, so only their age bins are lost.
+        let mut expected = compute_with(&changes, &reviews, now, &opts);
+        expected.open_ages = [0; 4];
+        assert_eq!(compute_with(&changes_in, &reviews_in, now, &opts), expected);
+        // Re-exporting the import reproduces the file.
+        assert_eq!(render(&changes_in, &reviews_in).unwrap(), exported);
+    }
+
+    #[test]
+    fn work_in_progress_survives_the_round_trip() {
+        use crate::stats::{compute_with, ComputeOptions};
+
+        let at = Utc.with_ymd_and_hms(2024, 6, 3, 8, 0, 0).unwrap();
+        let mut wip = change("a", ChangeStatus::Merged, Some(at));
+        wip.work_in_progress = true;
+        let changes = [wip, change("b", ChangeStatus::Merged, Some(at))];
+
+        let exported = render(&changes, &[]).unwrap();
+        assert_eq!(exported.matches(r#""work_in_progress":true"#).count(), 1);
+        let (changes_in, _) = parse(&exported).unwrap();
+        let opts = ComputeOptions {
+            exclude_wip: true,
+            ..Default::default()
+        };
+        let now = at + chrono::Duration::days(1);
+        assert_eq!(compute_with(&changes_in, &[], now, &opts).total_merged, 1);
+    }
+
+    #[test]
+    fn import_accepts_date_only_lines() {
+        let text = concat!(
+            r#"{"type":"merged","project":"a","date":"2024-06-03","insertions":10,"deletions":2}"#,
+            "\n\n",
+            r#"{"type":"review","project":"c","date":"2024-06-04"}"#,
+            "\n",
+        );
+        let (changes, reviews) = parse(text).unwrap();
+        let midnight = Utc.with_ymd_and_hms(2024, 6, 3, 0, 0, 0).unwrap();
+        assert_eq!(changes[0].submitted, Some(midnight));
+        assert_eq!(changes[0].insertions, 10);
+        assert_eq!(reviews[0].project, "c");
+        assert_eq!(reviews[0].insertions, 0);
+
+        let err = parse("{\"type\":\"merged\"}\n").unwrap_err();
+        assert!(format!("{err:#}").starts_with("line 1"), "{err:#}");
+    }
+}
@@ -0,0 +1,71 @@
+//! Machine-readable JSON dump of the computed stats.
+//!
+//! Everything in [`Stats`] plus the derived streak/gap figures, so scripts
+//! don't have to recompute them:
+//!
+//! ```json
+//! {"owner":"me@example.com","hosts":["chromium"],"current_streak":3,...,"stats":{...}}
+//! ```
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::stats::Stats;
+
+#[derive(Serialize)]
+struct Report<'a> {
+    owner: &'a str,
+    hosts: Vec<&'a str>,
+    current_streak: u32,
+    longest_streak: u32,
+    longest_gap: u32,
+    stats: &'a Stats,
+}
+
+/// Render `stats` as pretty-printed JSON.
+pub fn render(owner: &str, hosts: &[(String, String)], stats: &Stats) -> Result<String> {
+    let report = Report {
+        owner,
+        hosts: hosts.iter().map(|(alias, _)| alias.as_str()).collect(),
+        current_streak: stats.current_streak(),
+        longest_streak: stats.longest_streak(),
+        longest_gap: stats.heatmap.longest_gap(),
+        stats,
+    };
+    Ok(serde_json::to_string_pretty(&report)?)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn render_round_trips_as_json() {
+        let now = Utc.with_ymd_and_hms(2024, 6, 12, 12, 0, 0).unwrap();
+        let mut s = stats::compute(&[], &[], now);
+        s.total_merged = 7;
+        s.heatmap.weeks.last_mut().unwrap().count = 2;
+        let hosts = vec![(
+            "go".to_owned(),
+            "https://go-review.googlesource.com".to_owned(),
+        )];
+
+        let json = render("me@example.com", &hosts, &s).unwrap();
+        let v: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(v["owner"], "me@example.com");
+        assert_eq!(v["hosts"][0], "go");
+        assert_eq!(v["current_streak"], 1);
+        assert_eq!(v["stats"]["total_merged"], 7);
+        assert_eq!(
+            v["stats"]["heatmap"]["weeks"][51]["week_start"],
+            "2024-06-10"
+        );
+        assert_eq!(v["stats"]["first_contribution"], serde_json::Value::Null);
+    }
+}
@@ -1,12 +1,16 @@
 //! Markdown report renderer.
 
-use anyhow::Result;
-use minijinja::Environment;
+use anyhow::{Context, Result};
+use minijinja::{Environment, UndefinedBehavior};
 use serde::Serialize;
 
-use crate::stats::Stats;
+use crate::stats::{Stats, OPEN_AGE_LABELS};
 
-use super::{fmt_count, heatmap_code_block};
+use super::{
+    active_range, failed_hosts_note, fmt_count, fmt_project_rank, fmt_score_histogram,
+    fmt_total_reviews, heatmap_code_block, host_breakdown, month_abbr, reviews_window, Locale,
+    BLOCK_GLYPHS,
+};
 
 // ---------------------------------------------------------------------------
 // Template
@@ -19,43 +23,173 @@ use super::{fmt_count, heatmap_code_block};
 ///   - Still readable when `cat`'d raw: block glyphs in a plain code fence,
 ///     pipe tables degrade gracefully in a fixed-width terminal
 ///   - No external template files — single binary, no asset path hassles
-const TEMPLATE: &str = r#"## gerritoscope · {{ owner }}
+pub(crate) const TEMPLATE: &str = r#"## gerritoscope · {{ owner }}
 
 {{ heatmap_block }}
 
 | | |
 |:--|--:|
 | Merged (all time) | **{{ total_merged }}** |
-| Last 90 days | **{{ recent_90d }}** |
-| Reviews (52 wk) | **{{ total_reviews }}** |
-| Reviews (90d) | **{{ recent_reviews_90d }}** |
+| Last {{ recent_days }} days | **{{ recent_90d }}** |
+{% if reviews_included %}
+| Reviews ({{ reviews_window }}) | **{{ total_reviews }}** |
+| Reviews ({{ recent_days }}d) | **{{ recent_reviews_90d }}** |
+{% endif %}
+| Projects | **{{ distinct_projects }}** |
+| Active | **{{ active_range }}** |
 | Lines added | **+{{ total_ins }}** |
 | Lines removed | **-{{ total_del }}** |
+{% if reviews_included %}
+| Lines reviewed | **+{{ reviewed_ins }} / -{{ reviewed_del }}** |
+{% endif %}
 | Current streak | **{{ current_streak }} wk** |
 | Longest streak | **{{ longest_streak }} wk** |
+| Density | **{{ density }}/active wk** |
+{% if votes_received %}
+| Votes received | **{{ votes_received }}** |
+{% endif %}
 
 **Top projects**
 
 | Project | CLs | +Lines | -Lines |
 |:--------|----:|-------:|-------:|
-{% for p in top_projects %}| `{{ p.name }}` | {{ p.merged }} | +{{ p.ins }} | -{{ p.del }} |
+{% for p in top_projects %}| `{{ p.name }}`{% if p.rank %} ({{ p.rank }}){% endif %} | {{ p.merged }} | +{{ p.ins }} | -{{ p.del }} |
+{% endfor %}
+{% if notable_changes %}
+
+**Notable recent CLs**
+
+{% for c in notable_changes %}- `+{{ c.ins }}/-{{ c.del }}` {% if c.url %}[{{ c.subject }}]({{ c.url }}){% else %}{{ c.subject }}{% endif %}
+
+{% endfor %}
+{% endif %}
+{% if reviewed_authors %}
+
+**Reviewed most**
+
+| Author | CLs reviewed |
+|:-------|-------------:|
+{% for a in reviewed_authors %}| {{ a.author }} | {{ a.reviews }} |
+{% endfor %}
+{% endif %}
+{% if open_ages %}
+
+**Open CLs by age**
+
+| Age | CLs |
+|:----|----:|
+{% for a in open_ages %}| {{ a.label }} | {{ a.count }} |
+{% endfor %}
+{% endif %}
+
+**Monthly**
+
+| Month | CLs |{% if reviews_included %} Reviews |{% endif %} Lines |
+|:------|----:|{% if reviews_included %}--------:|{% endif %}------:|
+{% for m in monthly %}| {{ m.month }} | {{ m.cls }} |{% if reviews_included %} {{ m.reviews }} |{% endif %} {{ m.lines }} |
 {% endfor %}
 
 ---
 
+{% if failed_hosts_note %}
+> {{ failed_hosts_note }}
+
+{% endif %}
+{% if host_breakdown %}
+_{{ host_breakdown }}_
+
+{% endif %}
 _Updated {{ generated_at }} · {{ host_links }}_
 "#;
 
+/// `--compare`: two owners' headline numbers side by side, then both
+/// heatmaps stacked.  Both [`Stats`] must share `now` so the heatmap
+/// columns line up.
+pub(crate) const COMPARISON_TEMPLATE: &str = r#"## gerritoscope · {{ a.owner }} vs {{ b.owner }}
+
+| | {{ a.owner }} | {{ b.owner }} |
+|:--|--:|--:|
+{% for r in rows %}
+| {{ r.label }} | **{{ r.a }}** | **{{ r.b }}** |
+{% endfor %}
+
+**{{ a.owner }}**
+
+{{ a.heatmap_block }}
+
+**{{ b.owner }}**
+
+{{ b.heatmap_block }}
+
+---
+
+_Updated {{ generated_at }}_
+"#;
+
 // ---------------------------------------------------------------------------
 // Context types
 // ---------------------------------------------------------------------------
 
+#[derive(Serialize)]
+struct WeekdayRow {
+    day: &'static str,
+    count: u32,
+}
+
+#[derive(Serialize)]
+struct MonthRow {
+    /// `"Jun 2024"`.
+    month: String,
+    cls: String,
+    reviews: String,
+    lines: String,
+}
+
 #[derive(Serialize)]
 struct ProjectRow {
     name: String,
     merged: String,
     ins: String,
     del: String,
+    /// `--project-rank` standing, e.g. "top 5% of 120 contributors"; empty
+    /// when not ranked.
+    rank: String,
+}
+
+#[derive(Serialize)]
+struct ComparedOwner {
+    owner: String,
+    heatmap_block: String,
+}
+
+#[derive(Serialize)]
+struct ComparisonRow {
+    label: String,
+    a: String,
+    b: String,
+}
+
+#[derive(Serialize)]
+struct AuthorRow<'a> {
+    author: &'a str,
+    reviews: String,
+}
+
+#[derive(Serialize)]
+struct OpenAgeRow {
+    label: &'static str,
+    count: String,
+}
+
+#[derive(Serialize)]
+struct NotableRow {
+    project: String,
+    /// Subject with `[` and `]` escaped so it can sit inside a link.
+    subject: String,
+    /// `{host}/c/{number}`; empty when the host isn't known.
+    url: String,
+    ins: String,
+    del: String,
 }
 
 // ---------------------------------------------------------------------------
@@ -71,57 +205,275 @@ struct ProjectRow {
 /// Returns the full markdown string.  Write it to a file with
 /// `std::fs::write(path, render(...)?)?`.
 pub fn render(owner: &str, hosts: &[(String, String)], stats: &Stats) -> Result<String> {
+    render_with_template(
+        owner,
+        hosts,
+        stats,
+        TEMPLATE,
+        &BLOCK_GLYPHS,
+        &Locale::EN,
+        Some(owner),
+    )
+}
+
+/// Like [`render`], but with a user-supplied minijinja `template` in place
+/// of the built-in one (`--template-file`) and `glyphs` for the heatmap
+/// block (`--heatmap-glyphs`).
+///
+/// Footer links open an `owner:` query for `query_owner`; with `None` — the
+/// shown `owner` is masked (`--anonymize-owner`) — they open the host's
+/// front page instead, so the real address stays out of the report.
+///
+/// The context keys are the same as the built-in template's:
+///
+/// - `owner`: the owner as shown (masked under `--anonymize-owner`)
+/// - `heatmap_block`: the text heatmap as a fenced code block
+/// - `total_merged`, `total_ins`, `total_del`: lifetime counts, formatted
+/// - `reviewed_ins`, `reviewed_del`: lines in reviewed changes, formatted
+/// - `reviews_included`: `false` under `--owner-activity-only`
+/// - `distinct_projects`: count, formatted
+/// - `active_range`: first to last contribution, as text
+/// - `first_contribution`, `last_contribution`: dates, or none
+/// - `recent_days`: length of the recent window, 90 unless `--recent-days`
+/// - `recent_90d`, `recent_reviews_90d`: counts over the recent window
+/// - `total_reviews`: count, formatted (`5,000+` when capped)
+/// - `reviews_window`: period `total_reviews` covers, `52 wk` or
+///   `since <date>`
+/// - `current_streak`, `longest_streak`: weeks
+/// - `top_projects`: rows with `name`, `merged`, `ins`, `del`, `rank`
+/// - `generated_at`: UTC date; `generated_at_utc`: RFC 3339 timestamp
+/// - `host_links`: markdown links to each host
+/// - `failed_hosts_note`, `host_breakdown`: text, or none
+/// - `votes_received`: text such as `+2 ×131 · +1 ×38`, empty without votes
+/// - `peak_week_count`: number; `longest_gap`: weeks
+/// - `density`: contributions per active week, e.g. `3.2`
+/// - `host_aliases`: list of aliases
+/// - `weekday_counts`: Monday-first rows with `day` and `count`
+/// - `monthly`: oldest-first rows with `month`, `cls`, `reviews`, `lines`
+/// - `notable_changes` (`--notable`): largest-first rows with `project`,
+///   `subject`, `url`, `ins`, `del`
+/// - `reviewed_authors`: most-reviewed-first rows with `author` and
+///   `reviews`
+/// - `open_ages` (`--include-open`): youngest-first rows with `label` and
+///   `count`; empty when there are no open CLs
+///
+/// Undefined variables are an error rather than rendering as empty, so a
+/// typo in a custom template fails loudly.
+pub fn render_with_template(
+    owner: &str,
+    hosts: &[(String, String)],
+    stats: &Stats,
+    template: &str,
+    glyphs: &[char; 5],
+    locale: &Locale,
+    query_owner: Option<&str>,
+) -> Result<String> {
     let mut env = Environment::new();
     env.set_trim_blocks(true);
     env.set_lstrip_blocks(true);
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
 
     let projects: Vec<ProjectRow> = stats
         .top_projects
         .iter()
         .map(|p| ProjectRow {
             name: p.name.clone(),
-            merged: fmt_count(p.merged as i64),
-            ins: fmt_count(p.insertions),
-            del: fmt_count(p.deletions),
+            merged: fmt_count(p.merged as i64, locale),
+            ins: fmt_count(p.insertions, locale),
+            del: fmt_count(p.deletions, locale),
+            rank: p
+                .rank
+                .map(|r| fmt_project_rank(&r, locale))
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    let monthly: Vec<MonthRow> = stats
+        .monthly
+        .iter()
+        .map(|m| MonthRow {
+            month: format!("{} {}", month_abbr(m.month, locale), m.year),
+            cls: fmt_count(m.cls as i64, locale),
+            reviews: fmt_count(m.reviews as i64, locale),
+            lines: fmt_count(m.lines, locale),
+        })
+        .collect();
+
+    let now = chrono::Utc::now();
+    let generated_at = now.format("%Y-%m-%d").to_string();
+    let generated_at_utc = now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+
+    let weekday_counts: Vec<WeekdayRow> = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+        .into_iter()
+        .zip(stats.weekday_counts)
+        .map(|(day, count)| WeekdayRow { day, count })
+        .collect();
+    let host_aliases: Vec<&str> = hosts.iter().map(|(alias, _)| alias.as_str()).collect();
+
+    let reviewed_authors: Vec<AuthorRow> = stats
+        .reviewed_authors
+        .iter()
+        .map(|(author, n)| AuthorRow {
+            author,
+            reviews: fmt_count(*n as i64, locale),
         })
         .collect();
 
-    let generated_at = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let open_ages: Vec<OpenAgeRow> = if stats.open_ages.iter().any(|&n| n > 0) {
+        OPEN_AGE_LABELS
+            .into_iter()
+            .zip(stats.open_ages)
+            .map(|(label, n)| OpenAgeRow {
+                label,
+                count: fmt_count(n as i64, locale),
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let notable_changes: Vec<NotableRow> = stats
+        .notable_changes
+        .iter()
+        .map(|c| NotableRow {
+            project: c.project.clone(),
+            subject: c.subject.replace('[', "\\[").replace(']', "\\]"),
+            url: change_url(hosts, &c.project, c.number).unwrap_or_default(),
+            ins: fmt_count(c.insertions, locale),
+            del: fmt_count(c.deletions, locale),
+        })
+        .collect();
 
     // Build footer link(s).
     // Single host: "[chromium-review.googlesource.com](url/q/owner:...)"
     // Multi-host:  "[chromium](url) · [go](url)"
+    let link = |url: &str| match query_owner {
+        Some(owner) => format!("{url}/q/owner:{owner}"),
+        None => url.to_owned(),
+    };
     let host_links = if hosts.len() == 1 {
         let (_, url) = &hosts[0];
         let display = url
             .trim_start_matches("https://")
             .trim_start_matches("http://");
-        format!("[{display}]({}/q/owner:{owner})", url)
+        format!("[{display}]({})", link(url))
     } else {
         hosts
             .iter()
-            .map(|(alias, url)| format!("[{alias}]({url}/q/owner:{owner})"))
+            .map(|(alias, url)| format!("[{alias}]({})", link(url)))
             .collect::<Vec<_>>()
             .join(" · ")
     };
 
     let ctx = minijinja::context! {
         owner               => owner,
-        heatmap_block       => heatmap_code_block(&stats.heatmap),
-        total_merged        => fmt_count(stats.total_merged as i64),
-        total_ins           => fmt_count(stats.total_insertions),
-        total_del           => fmt_count(stats.total_deletions),
-        recent_90d          => fmt_count(stats.recent_merged_90d as i64),
-        total_reviews       => fmt_count(stats.total_reviews as i64),
-        recent_reviews_90d  => fmt_count(stats.recent_reviews_90d as i64),
-        current_streak      => stats.heatmap.current_streak(),
-        longest_streak      => stats.heatmap.longest_streak(),
+        heatmap_block       => heatmap_code_block(&stats.heatmap, glyphs, locale),
+        total_merged        => fmt_count(stats.total_merged as i64, locale),
+        total_ins           => fmt_count(stats.total_insertions, locale),
+        total_del           => fmt_count(stats.total_deletions, locale),
+        reviewed_ins        => fmt_count(stats.reviewed_insertions, locale),
+        reviewed_del        => fmt_count(stats.reviewed_deletions, locale),
+        reviews_included    => stats.reviews_included,
+        distinct_projects   => fmt_count(stats.distinct_projects as i64, locale),
+        active_range        => active_range(stats),
+        first_contribution  => stats.first_contribution.map(|d| d.to_string()),
+        last_contribution   => stats.last_contribution.map(|d| d.to_string()),
+        recent_days         => stats.recent_days,
+        recent_90d          => fmt_count(stats.recent_merged_90d as i64, locale),
+        total_reviews       => fmt_total_reviews(stats, locale),
+        reviews_window      => reviews_window(stats),
+        recent_reviews_90d  => fmt_count(stats.recent_reviews_90d as i64, locale),
+        current_streak      => stats.current_streak(),
+        longest_streak      => stats.longest_streak(),
         top_projects        => projects,
         generated_at        => generated_at,
         host_links          => host_links,
+        failed_hosts_note   => failed_hosts_note(&stats.failed_hosts),
+        host_breakdown      => host_breakdown(&stats.host_counts, locale),
+        votes_received      => fmt_score_histogram(&stats.received_score_histogram, locale),
+        peak_week_count     => stats.heatmap.max_count,
+        longest_gap         => stats.heatmap.longest_gap(),
+        density             => format!("{:.1}", stats.heatmap.density()),
+        host_aliases        => host_aliases,
+        generated_at_utc    => generated_at_utc,
+        weekday_counts      => weekday_counts,
+        monthly             => monthly,
+        notable_changes     => notable_changes,
+        reviewed_authors    => reviewed_authors,
+        open_ages           => open_ages,
     };
 
-    Ok(env.render_str(TEMPLATE, ctx)?)
+    env.render_str(template, ctx)
+        .context("rendering markdown template")
+}
+
+/// Render the `--compare` report for owners `a` and `b` (see
+/// [`COMPARISON_TEMPLATE`]).
+pub fn render_comparison(
+    a: (&str, &Stats),
+    b: (&str, &Stats),
+    glyphs: &[char; 5],
+    locale: &Locale,
+) -> Result<String> {
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+
+    let row = |label: &str, f: &dyn Fn(&Stats) -> String| ComparisonRow {
+        label: label.to_owned(),
+        a: f(a.1),
+        b: f(b.1),
+    };
+    let count = |n: usize| fmt_count(n as i64, locale);
+    // Both owners are computed with the same options.
+    let days = a.1.recent_days;
+    let mut rows = vec![
+        row("Merged (all time)", &|s| count(s.total_merged)),
+        row(&format!("Last {days} days"), &|s| {
+            count(s.recent_merged_90d)
+        }),
+        row(&format!("Reviews ({})", reviews_window(a.1)), &|s| {
+            fmt_total_reviews(s, locale)
+        }),
+        row(&format!("Reviews ({days}d)"), &|s| {
+            count(s.recent_reviews_90d)
+        }),
+        row("Current streak", &|s| format!("{} wk", s.current_streak())),
+        row("Longest streak", &|s| format!("{} wk", s.longest_streak())),
+        row("Lines added", &|s| {
+            format!("+{}", fmt_count(s.total_insertions, locale))
+        }),
+        row("Lines removed", &|s| {
+            format!("-{}", fmt_count(s.total_deletions, locale))
+        }),
+    ];
+    if !a.1.reviews_included && !b.1.reviews_included {
+        rows.retain(|r| !r.label.starts_with("Reviews"));
+    }
+    let owner = |(owner, stats): (&str, &Stats)| ComparedOwner {
+        owner: owner.to_owned(),
+        heatmap_block: heatmap_code_block(&stats.heatmap, glyphs, locale),
+    };
+
+    let ctx = minijinja::context! {
+        a            => owner(a),
+        b            => owner(b),
+        rows         => rows,
+        generated_at => chrono::Utc::now().format("%Y-%m-%d").to_string(),
+    };
+    env.render_str(COMPARISON_TEMPLATE, ctx)
+        .context("rendering comparison template")
+}
+
+/// `{url}/c/{number}` on the host `project` came from: the one named by its
+/// `alias::` prefix when hosts were combined, else the only host.
+fn change_url(hosts: &[(String, String)], project: &str, number: u64) -> Option<String> {
+    let url = match project.split_once("::") {
+        Some((alias, _)) => &hosts.iter().find(|(a, _)| a == alias)?.1,
+        None => &hosts.first()?.1,
+    };
+    Some(format!("{url}/c/{number}"))
 }
 
 // ---------------------------------------------------------------------------
@@ -132,6 +484,7 @@ pub fn render(owner: &str, hosts: &[(String, String)], stats: &Stats) -> Result<
 mod tests {
     use super::*;
     use crate::gerrit::{ChangeInfo, ChangeStatus};
+    use crate::stats::HostCount;
     use chrono::{NaiveDate, Utc};
 
     fn ts(s: &str) -> chrono::DateTime<Utc> {
@@ -144,14 +497,23 @@ mod tests {
 
     fn merged_cl(project: &str, submitted: &str, ins: i32, del: i32) -> ChangeInfo {
         ChangeInfo {
+            id: String::new(),
+            number: 1,
+            subject: String::new(),
             project: project.to_owned(),
+            branch: "main".to_owned(),
             status: ChangeStatus::Merged,
+            created: ts(submitted),
             updated: ts(submitted),
             submitted: Some(ts(submitted)),
             insertions: ins,
             deletions: del,
+            work_in_progress: false,
             more_changes: None,
+            revision_count: None,
             messages: vec![],
+            owner: None,
+            labels: Default::default(),
         }
     }
 
@@ -184,6 +546,157 @@ mod tests {
         assert!(!md.is_empty());
     }
 
+    #[test]
+    fn comparison_puts_owners_side_by_side() {
+        let now = ts("2024-06-12");
+        let alice = sample_stats();
+        let bob = crate::stats::compute(
+            &[
+                merged_cl("go", "2024-06-10", 1200, 300),
+                merged_cl("go", "2023-02-06", 5, 5),
+            ],
+            &[],
+            now,
+        );
+        let md = render_comparison(
+            ("alice@example.com", &alice),
+            ("bob@example.com", &bob),
+            &BLOCK_GLYPHS,
+            &Locale::EN,
+        )
+        .unwrap();
+
+        assert!(md.starts_with("## gerritoscope · alice@example.com vs bob@example.com\n"));
+        assert!(
+            md.contains("| | alice@example.com | bob@example.com |\n|:--|--:|--:|\n"),
+            "{md}"
+        );
+        assert!(
+            md.contains("| Merged (all time) | **3** | **2** |\n"),
+            "{md}"
+        );
+        assert!(md.contains("| Last 90 days | **3** | **1** |\n"), "{md}");
+        assert!(
+            md.contains("| Lines added | **+180** | **+1,205** |\n"),
+            "{md}"
+        );
+        assert!(
+            md.contains("| Lines removed | **-35** | **-305** |\n"),
+            "{md}"
+        );
+
+        // Both heatmaps, stacked and the same width.
+        let blocks: Vec<&str> = md.split("```").skip(1).step_by(2).collect();
+        assert_eq!(blocks.len(), 2, "{md}");
+        let grid = |block: &str| {
+            block
+                .lines()
+                .find(|l| l.starts_with('['))
+                .unwrap()
+                .chars()
+                .count()
+        };
+        assert_eq!(grid(blocks[0]), grid(blocks[1]));
+        assert!(md.find("**alice@example.com**") < md.find("**bob@example.com**"));
+    }
+
+    #[test]
+    fn notable_changes_link_to_their_host() {
+        let mut stats = sample_stats();
+        let cl = |project: &str, number, subject: &str| crate::stats::ChangeRef {
+            project: project.to_owned(),
+            number,
+            subject: subject.to_owned(),
+            insertions: 820,
+            deletions: 12,
+            submitted: ts("2024-06-03"),
+        };
+        stats.notable_changes = vec![
+            cl("go::cmd/go", 4711, "Refactor frame scheduler"),
+            cl("chromium::chromium/src", 42, "[blink] Drop dead code"),
+        ];
+        let hosts = [
+            ("chromium".to_owned(), "https://c.example.com".to_owned()),
+            ("go".to_owned(), "https://go.example.com".to_owned()),
+        ];
+        let md = render("alice@example.com", &hosts, &stats).unwrap();
+        assert!(md.contains("**Notable recent CLs**"), "{md}");
+        assert!(
+            md.contains("- `+820/-12` [Refactor frame scheduler](https://go.example.com/c/4711)\n"),
+            "{md}"
+        );
+        assert!(
+            md.contains("[\\[blink\\] Drop dead code](https://c.example.com/c/42)"),
+            "{md}"
+        );
+    }
+
+    #[test]
+    fn reviewed_authors_table() {
+        let mut stats = sample_stats();
+        assert!(!render("me", &single_host("https://r.example.com"), &stats)
+            .unwrap()
+            .contains("Reviewed most"));
+
+        stats.reviewed_authors = vec![
+            ("alice@example.com".to_owned(), 1234),
+            (crate::stats::UNKNOWN_AUTHOR.to_owned(), 3),
+        ];
+        let md = render("me", &single_host("https://r.example.com"), &stats).unwrap();
+        assert!(md.contains("**Reviewed most**"), "{md}");
+        assert!(
+            md.contains("| alice@example.com | 1,234 |\n| unknown | 3 |\n"),
+            "{md}"
+        );
+    }
+
+    #[test]
+    fn recent_rows_follow_recent_days() {
+        let mut stats = sample_stats();
+        stats.recent_days = 30;
+        let md = render("me", &single_host("https://r.example.com"), &stats).unwrap();
+        assert!(md.contains("| Last 30 days | **"), "{md}");
+        assert!(md.contains("| Reviews (30d) | **"), "{md}");
+        assert!(!md.contains("90"), "{md}");
+    }
+
+    #[test]
+    fn review_total_row_follows_an_extended_window() {
+        let mut stats = sample_stats();
+        let md = render("me", &single_host("https://r.example.com"), &stats).unwrap();
+        assert!(md.contains("| Reviews (52 wk) | **"), "{md}");
+        stats.reviews_since = NaiveDate::from_ymd_opt(2019, 1, 1);
+        let md = render("me", &single_host("https://r.example.com"), &stats).unwrap();
+        assert!(md.contains("| Reviews (since 2019-01-01) | **"), "{md}");
+    }
+
+    #[test]
+    fn open_ages_table() {
+        let mut stats = sample_stats();
+        assert!(!render("me", &single_host("https://r.example.com"), &stats)
+            .unwrap()
+            .contains("Open CLs"));
+
+        stats.open_ages = [2, 0, 1, 1];
+        let md = render("me", &single_host("https://r.example.com"), &stats).unwrap();
+        assert!(md.contains("**Open CLs by age**"), "{md}");
+        assert!(
+            md.contains("| ≤ 1 week | 2 |\n| ≤ 1 month | 0 |\n| ≤ 3 months | 1 |\n| older | 1 |\n"),
+            "{md}"
+        );
+    }
+
+    #[test]
+    fn notable_section_hidden_when_empty() {
+        let md = render(
+            "alice@example.com",
+            &single_host("https://example-review.example.com"),
+            &sample_stats(),
+        )
+        .unwrap();
+        assert!(!md.contains("Notable"), "{md}");
+    }
+
     #[test]
     fn render_contains_owner() {
         let stats = sample_stats();
@@ -242,8 +755,11 @@ mod tests {
         .unwrap();
         assert!(md.contains("Merged (all time)"));
         assert!(md.contains("Last 90 days"));
+        assert!(md.contains("| Projects | **3** |"));
+        assert!(md.contains("| Active | **2024-06-03 → 2024-06-06 (0.0 yrs)** |"));
         assert!(md.contains("Lines added"));
         assert!(md.contains("Lines removed"));
+        assert!(md.contains("| Density | **3.0/active wk** |"), "{md}");
     }
 
     #[test]
@@ -271,6 +787,83 @@ mod tests {
         assert!(md.contains("12,345"), "insertions not comma-formatted");
     }
 
+    #[test]
+    fn render_monthly_table() {
+        let stats = sample_stats();
+        let md = render("u@example.com", &single_host("https://example.com"), &stats).unwrap();
+        assert!(md.contains("| Month | CLs | Reviews | Lines |"));
+        assert!(md.contains("| Jun 2024 | 3 | 0 | 215 |"), "{md}");
+        assert!(md.contains("| Mar 2024 | 0 | 0 | 0 |"));
+    }
+
+    #[test]
+    fn render_omits_review_rows_when_excluded() {
+        let mut stats = sample_stats();
+        stats.reviews_included = false;
+        let md = render("u@example.com", &single_host("https://example.com"), &stats).unwrap();
+        assert!(!md.contains("| Reviews"), "{md}");
+        assert!(!md.contains("Lines reviewed"), "{md}");
+        assert!(md.contains("| Month | CLs | Lines |"), "{md}");
+        assert!(md.contains("|:------|----:|------:|"), "{md}");
+        assert!(md.contains("| Merged (all time) |"), "{md}");
+    }
+
+    #[test]
+    fn render_reviewed_lines_row() {
+        let mut stats = sample_stats();
+        stats.reviewed_insertions = 1_200_000;
+        stats.reviewed_deletions = 45;
+        let md = render("u@example.com", &single_host("https://example.com"), &stats).unwrap();
+        assert!(md.contains("| Lines reviewed | **+1,200,000 / -45** |"));
+    }
+
+    #[test]
+    fn render_votes_received_row_only_when_present() {
+        let mut stats = sample_stats();
+        let md = render("u@example.com", &single_host("https://example.com"), &stats).unwrap();
+        assert!(!md.contains("Votes received"));
+
+        stats.received_score_histogram = [(2, 3), (-1, 1)].into_iter().collect();
+        let md = render("u@example.com", &single_host("https://example.com"), &stats).unwrap();
+        assert!(md.contains("| Votes received | **+2 ×3 · -1 ×1** |"));
+    }
+
+    #[test]
+    fn render_flags_failed_hosts() {
+        let mut stats = sample_stats();
+        let md = render("u@example.com", &single_host("https://example.com"), &stats).unwrap();
+        assert!(!md.contains("⚠"), "no note when every host succeeded");
+
+        stats.failed_hosts = vec!["go".to_owned()];
+        let md = render("u@example.com", &single_host("https://example.com"), &stats).unwrap();
+        assert!(md.contains("⚠ 1 host failed (go)"));
+    }
+
+    #[test]
+    fn render_footer_shows_host_breakdown_when_counted() {
+        let mut stats = sample_stats();
+        let md = render("u@example.com", &single_host("https://example.com"), &stats).unwrap();
+        assert!(!md.contains("CLs, "), "no breakdown without counts");
+
+        stats.host_counts = vec![
+            HostCount {
+                alias: "chromium".to_owned(),
+                cls: 812,
+                reviews: 90,
+            },
+            HostCount {
+                alias: "go".to_owned(),
+                cls: 44,
+                reviews: 3,
+            },
+        ];
+        let md = render("u@example.com", &single_host("https://example.com"), &stats).unwrap();
+        assert!(
+            md.contains("_chromium: 812 CLs, 90 reviews · go: 44 CLs, 3 reviews_"),
+            "{md}"
+        );
+    }
+
     #[test]
     fn render_multi_host_footer_uses_aliases() {
         let stats = sample_stats();
@@ -298,4 +891,81 @@ mod tests {
             "protocol leaked into multi-host link text"
         );
     }
+
+    #[test]
+    fn anonymized_footer_links_omit_owner_query() {
+        let stats = sample_stats();
+        let md = render_with_template(
+            "a***@example.com",
+            &single_host("https://example-review.example.com"),
+            &stats,
+            TEMPLATE,
+            &BLOCK_GLYPHS,
+            &Locale::EN,
+            None,
+        )
+        .unwrap();
+        assert!(md.contains("a***@example.com"));
+        assert!(
+            md.contains("[example-review.example.com](https://example-review.example.com)"),
+            "{md}"
+        );
+        assert!(!md.contains("/q/owner:"), "{md}");
+    }
+
+    #[test]
+    fn render_custom_template() {
+        let stats = sample_stats();
+        let md = render_with_template(
+            "alice@example.com",
+            &single_host("https://example-review.example.com"),
+            &stats,
+            "{{ owner }}",
+            &BLOCK_GLYPHS,
+            &Locale::EN,
+            Some("alice@example.com"),
+        )
+        .unwrap();
+        assert_eq!(md, "alice@example.com");
+    }
+
+    #[test]
+    fn render_custom_template_rejects_undefined() {
+        let stats = sample_stats();
+        let err = render_with_template(
+            "alice@example.com",
+            &single_host("https://example-review.example.com"),
+            &stats,
+            "{{ ownr }}",
+            &BLOCK_GLYPHS,
+            &Locale::EN,
+            Some("alice@example.com"),
+        )
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("undefined"), "{err:#}");
+    }
+
+    #[test]
+    fn render_custom_template_extra_context() {
+        let stats = sample_stats();
+        let md = render_with_template(
+            "alice@example.com",
+            &[
+                ("chromium".to_owned(), "https://a.example.com".to_owned()),
+                ("go".to_owned(), "https://b.example.com".to_owned()),
+            ],
+            &stats,
+            "{{ peak_week_count }}|{{ host_aliases | join(',') }}|\
+             {% for d in weekday_counts %}{{ d.day }}={{ d.count }} {% endfor %}",
+            &BLOCK_GLYPHS,
+            &Locale::EN,
+            Some("alice@example.com"),
+        )
+        .unwrap();
+        // All three sample CLs land in the week of 2024-06-03 (Mon, Wed, Thu).
+        assert_eq!(
+            md,
+            "3|chromium,go|Mon=1 Tue=0 Wed=1 Thu=1 Fri=0 Sat=0 Sun=0 "
+        );
+    }
 }
@@ -0,0 +1,102 @@
+//! Weekly trend as a Mermaid chart, for READMEs that render Mermaid.
+//!
+//! A fenced `xychart-beta` block with one point per heatmap week, oldest
+//! first:
+//!
+//! ````text
+//! ```mermaid
+//! xychart-beta
+//!     title "Weekly contributions"
+//!     x-axis ["Jun 12", "Jun 19", …]
+//!     y-axis "CLs + reviews" 0 --> 9
+//!     line [0, 3, …]
+//! ```
+//! ````
+
+use chrono::Datelike;
+
+use crate::stats::Heatmap;
+
+use super::{month_abbr, Locale};
+
+/// Render `h` as a fenced Mermaid line chart, ending in a newline.
+pub fn render(h: &Heatmap, locale: &Locale) -> String {
+    let labels: Vec<String> = h
+        .weeks
+        .iter()
+        .map(|b| {
+            let d = b.week_start;
+            format!("\"{} {}\"", month_abbr(d.month(), locale), d.day())
+        })
+        .collect();
+    let counts: Vec<String> = h.weeks.iter().map(|b| b.count.to_string()).collect();
+    // Mermaid draws nothing for a 0 --> 0 axis; keep all-zero weeks a flat
+    // line along the bottom instead.
+    let top = h.max_count.max(1);
+
+    format!(
+        "```mermaid\n\
+         xychart-beta\n    \
+         title \"Weekly contributions\"\n    \
+         x-axis [{}]\n    \
+         y-axis \"CLs + reviews\" 0 --> {top}\n    \
+         line [{}]\n\
+         ```\n",
+        labels.join(", "),
+        counts.join(", "),
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats;
+    use chrono::{TimeZone, Utc};
+
+    fn empty_heatmap() -> Heatmap {
+        let now = Utc.with_ymd_and_hms(2024, 6, 12, 12, 0, 0).unwrap();
+        stats::compute(&[], &[], now).heatmap
+    }
+
+    /// The values inside `line [...]`.
+    fn points(out: &str) -> Vec<u32> {
+        let line = out
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("line ["))
+            .unwrap();
+        line.trim_end_matches(']')
+            .split(", ")
+            .map(|n| n.parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn one_point_per_week() {
+        let mut h = empty_heatmap();
+        h.weeks[10].count = 4;
+        h.weeks[51].count = 7;
+        h.max_count = 7;
+
+        let out = render(&h, &Locale::EN);
+        assert!(out.starts_with("```mermaid\nxychart-beta\n"), "{out}");
+        assert!(out.ends_with("```\n"), "{out}");
+        let points = points(&out);
+        assert_eq!(points.len(), h.weeks.len());
+        assert_eq!((points[10], points[51]), (4, 7));
+        assert!(out.contains("y-axis \"CLs + reviews\" 0 --> 7"), "{out}");
+        assert!(out.contains("x-axis [\"Jun 19\", "), "{out}");
+        assert!(out.contains(", \"Jun 10\"]"), "{out}");
+    }
+
+    #[test]
+    fn all_zero_weeks_draw_a_flat_line() {
+        let h = empty_heatmap();
+        let out = render(&h, &Locale::EN);
+        assert_eq!(points(&out), vec![0; h.weeks.len()]);
+        assert!(out.contains("0 --> 1"), "{out}");
+    }
+}
@@ -1,40 +1,177 @@
 //! Shared rendering utilities used by all output backends.
 
+pub mod badge;
+pub mod csv;
+pub mod digest;
+pub mod events;
+pub mod json;
 pub mod markdown;
+pub mod mermaid;
 pub mod svg;
 
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use anyhow::{bail, Result};
 use chrono::Datelike;
 
-use crate::stats::Heatmap;
+use crate::stats::{Heatmap, HostCount, ProjectRank, Stats};
+
+use self::badge::BadgeMetric;
+use self::svg::{Palette, SvgOptions};
+
+// ---------------------------------------------------------------------------
+// Format dispatch
+// ---------------------------------------------------------------------------
+
+/// An output format selectable with `--format`, for printing to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Markdown,
+    Svg,
+    Csv,
+    Badge,
+    Json,
+    Digest,
+    Mermaid,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "svg" => Ok(OutputFormat::Svg),
+            "csv" => Ok(OutputFormat::Csv),
+            "badge" => Ok(OutputFormat::Badge),
+            "json" => Ok(OutputFormat::Json),
+            "digest" => Ok(OutputFormat::Digest),
+            "mermaid" => Ok(OutputFormat::Mermaid),
+            other => bail!(
+                "unknown format {other:?}; valid names: markdown, svg, csv, badge, json, \
+                 digest, mermaid"
+            ),
+        }
+    }
+}
+
+/// Per-format settings for [`render_format`].
+pub struct FormatOptions<'a> {
+    pub svg: SvgOptions<'a>,
+    pub badge_metric: BadgeMetric,
+    /// Custom markdown template; `None` uses the built-in one.
+    pub template: Option<&'a str>,
+    /// Heatmap glyphs for the markdown code block (see [`parse_glyphs`]).
+    pub glyphs: [char; 5],
+    /// Digit grouping and month names for human-readable formats.
+    pub locale: Locale,
+    /// The `owner` passed to [`render_format`] is masked (see
+    /// [`mask_email`]), so markdown footer links leave out the `owner:` query.
+    pub anonymize_owner: bool,
+}
+
+impl Default for FormatOptions<'static> {
+    fn default() -> Self {
+        FormatOptions {
+            svg: SvgOptions::default(),
+            badge_metric: BadgeMetric::default(),
+            template: None,
+            glyphs: BLOCK_GLYPHS,
+            locale: Locale::EN,
+            anonymize_owner: false,
+        }
+    }
+}
+
+/// Render `stats` in `format` using the matching backend.
+pub fn render_format(
+    format: OutputFormat,
+    owner: &str,
+    hosts: &[(String, String)],
+    stats: &Stats,
+    opts: &FormatOptions<'_>,
+) -> Result<String> {
+    match format {
+        OutputFormat::Markdown => markdown::render_with_template(
+            owner,
+            hosts,
+            stats,
+            opts.template.unwrap_or(markdown::TEMPLATE),
+            &opts.glyphs,
+            &opts.locale,
+            (!opts.anonymize_owner).then_some(owner),
+        ),
+        OutputFormat::Svg => svg::render(owner, hosts, stats, &opts.svg),
+        OutputFormat::Csv => Ok(csv::render(&stats.heatmap)),
+        OutputFormat::Badge => badge::render(stats, opts.badge_metric, &opts.locale),
+        OutputFormat::Json => json::render(owner, hosts, stats),
+        OutputFormat::Digest => Ok(digest::render(stats, &opts.locale)),
+        OutputFormat::Mermaid => Ok(mermaid::render(&stats.heatmap, &opts.locale)),
+    }
+}
 
 // ---------------------------------------------------------------------------
 // Heatmap ASCII builders
 // ---------------------------------------------------------------------------
 
-/// Block glyphs for intensity levels 0 – 4.
-const BLOCKS: [char; 5] = [' ', '░', '▒', '▓', '█'];
+/// Default glyphs for intensity levels 0 – 4: Unicode shade blocks.
+pub const BLOCK_GLYPHS: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// ASCII-safe ramp for terminals that draw the shade blocks as boxes.
+pub const ASCII_GLYPHS: [char; 5] = [' ', '.', ':', '+', '#'];
+
+/// Parse a `--heatmap-glyphs` value: `blocks` (the default), `ascii`, or
+/// exactly five characters for levels 0 – 4, e.g. `" .oO@"`.
+pub fn parse_glyphs(s: &str) -> Result<[char; 5]> {
+    match s {
+        "blocks" => Ok(BLOCK_GLYPHS),
+        "ascii" => Ok(ASCII_GLYPHS),
+        custom => {
+            let chars: Vec<char> = custom.chars().collect();
+            match <[char; 5]>::try_from(chars) {
+                Ok(glyphs) => Ok(glyphs),
+                Err(chars) => bail!(
+                    "--heatmap-glyphs {custom:?} has {} characters; expected `blocks`, `ascii`, \
+                     or exactly 5 (levels 0-4)",
+                    chars.len()
+                ),
+            }
+        }
+    }
+}
 
 /// Month-label line that sits above the heatmap body.
 ///
-/// Each 3-char abbreviation is placed at the first bucket of a new calendar
-/// month, skipped when there isn't room (< 4 cols since the last label).
+/// Each label ([`month_label`]) is placed at the first bucket of a new
+/// calendar month, skipped when it would touch the previous one (no blank
+/// column between them).  A year-suffixed January that won't fit before
+/// the end of the row drops its suffix.
 ///
-/// Example: `"Feb   Apr May Jun  Jul Aug Sep  Oct Nov Dec  Jan Feb"`
-pub fn heatmap_header(h: &Heatmap) -> String {
+/// Example: `"Feb   Apr May Jun  Jul Aug Sep  Oct Nov Dec  Jan'25 Mar"`
+pub fn heatmap_header(h: &Heatmap, locale: &Locale) -> String {
     let mut row = vec![' '; h.weeks.len()];
+    let with_years = spans_years(h);
     let mut last_month = 0u32;
-    let mut last_pos = 0usize;
+    // First column the next label may start at.
+    let mut free_from = 0usize;
 
     for (i, b) in h.weeks.iter().enumerate() {
         let m = b.week_start.month();
         if m != last_month {
-            if i == 0 || i >= last_pos + 4 {
-                for (j, ch) in month_abbr(m).chars().enumerate() {
+            if i >= free_from {
+                let mut label = month_label(b.week_start, with_years, locale);
+                if i + label.chars().count() > row.len() {
+                    label = month_abbr(m, locale).to_owned();
+                }
+                let mut width = 0;
+                for (j, ch) in label.chars().enumerate() {
                     if i + j < row.len() {
                         row[i + j] = ch;
                     }
+                    width = j + 1;
                 }
-                last_pos = i;
+                free_from = i + width + 1;
             }
             last_month = m;
         }
@@ -43,11 +180,89 @@ pub fn heatmap_header(h: &Heatmap) -> String {
     row.into_iter().collect()
 }
 
-/// Raw heatmap body: one block glyph per week bucket, no brackets.
+/// True when the heatmap's weeks fall in more than one calendar year, so
+/// month labels alone are ambiguous.
+pub fn spans_years(h: &Heatmap) -> bool {
+    match (h.weeks.first(), h.weeks.last()) {
+        (Some(first), Some(last)) => first.week_start.year() != last.week_start.year(),
+        _ => false,
+    }
+}
+
+/// Header label for the month starting at `date`: its abbreviation, with a
+/// two-digit year on January when `with_year` (`"Jan'25"`).
+pub fn month_label(date: chrono::NaiveDate, with_year: bool, locale: &Locale) -> String {
+    let abbr = month_abbr(date.month(), locale);
+    if with_year && date.month() == 1 {
+        format!("{abbr}'{:02}", date.year() % 100)
+    } else {
+        abbr.to_owned()
+    }
+}
+
+/// Raw heatmap body: one glyph per week bucket, no brackets.
+///
+/// Example with [`BLOCK_GLYPHS`]: `"  ░▒░ ░░░░░░░ ░░ ░  ░▒ ░ ░  ░░░ ░░ ░▒█▓░█▓▓░▒▓▓  █▓▓"`
+pub fn heatmap_body(h: &Heatmap, glyphs: &[char; 5]) -> String {
+    levels_body(&h.levels(), glyphs)
+}
+
+/// Heatmap body from precomputed levels, e.g. [`Heatmap::smoothed`].
+pub fn levels_body(levels: &[u8], glyphs: &[char; 5]) -> String {
+    levels.iter().map(|&l| glyphs[l as usize]).collect()
+}
+
+/// [`levels_body`] with each glyph wrapped in a 24-bit ANSI foreground
+/// escape using the palette's level colour.
 ///
-/// Example: `"  ░▒░ ░░░░░░░ ░░ ░  ░▒ ░ ░  ░░░ ░░ ░▒█▓░█▓▓░▒▓▓  █▓▓"`
-pub fn heatmap_body(h: &Heatmap) -> String {
-    h.weeks.iter().map(|b| BLOCKS[b.level() as usize]).collect()
+/// Levels whose colour doesn't parse as hex fall back to the plain glyph.
+pub fn heatmap_body_colored(levels: &[u8], glyphs: &[char; 5], palette: &Palette<'_>) -> String {
+    levels
+        .iter()
+        .map(|&level| {
+            let level = level as usize;
+            let glyph = glyphs[level];
+            match parse_hex_color(palette.levels[level]) {
+                Some((r, g, b)) => format!("\x1b[38;2;{r};{g};{b}m{glyph}\x1b[0m"),
+                None => glyph.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Parse `#rgb` / `#rrggbb` into an RGB triple.
+pub fn parse_hex_color(s: &str) -> Option<(u8, u8, u8)> {
+    let hex = s.strip_prefix('#')?;
+    // Also keeps the byte slicing below on char boundaries.
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |h: &str| u8::from_str_radix(h, 16).ok();
+    match hex.len() {
+        3 => {
+            let expand = |i: usize| channel(&hex[i..i + 1].repeat(2));
+            Some((expand(0)?, expand(1)?, expand(2)?))
+        }
+        6 => Some((
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+        )),
+        _ => None,
+    }
+}
+
+/// Peak-week caption: `"peak: 12 in week of 2024-03-04"`, or `"peak: 0"`
+/// when the heatmap is empty.
+pub fn peak_label(h: &Heatmap) -> String {
+    match h.busiest_week() {
+        Some(b) => format!(
+            "peak: {} in week of {}",
+            b.count,
+            b.week_start.format("%Y-%m-%d")
+        ),
+        None => "peak: 0".to_owned(),
+    }
 }
 
 /// Full markdown code-block for the heatmap, ready to embed in a template.
@@ -60,33 +275,270 @@ pub fn heatmap_body(h: &Heatmap) -> String {
 /// ```
 /// Feb   Apr May Jun  ...
 /// [  ░▒░ ░░░░░░░ ...]
-/// peak: 12 CLs/wk
+/// peak: 12 in week of 2024-03-04
 /// ```
 /// ```
-pub fn heatmap_code_block(h: &Heatmap) -> String {
+pub fn heatmap_code_block(h: &Heatmap, glyphs: &[char; 5], locale: &Locale) -> String {
+    format!(
+        "```\n{}\n[{}]\n{}\n```",
+        heatmap_header(h, locale),
+        heatmap_body(h, glyphs),
+        peak_label(h),
+    )
+}
+
+// ---------------------------------------------------------------------------
+// One-line summary
+// ---------------------------------------------------------------------------
+
+/// Compact one-liner for shell prompts and status bars:
+/// `"me: 1,234 CLs · 42/90d · 7wk streak"` (`/30d` with `--recent-days 30`).
+pub fn summary_line(owner: &str, s: &Stats, locale: &Locale) -> String {
     format!(
-        "```\n{}\n[{}]\npeak: {}/wk\n```",
-        heatmap_header(h),
-        heatmap_body(h),
-        h.max_count,
+        "{owner}: {} CLs · {}/{}d · {}wk streak",
+        fmt_count(s.total_merged as i64, locale),
+        fmt_count(s.recent_merged_90d as i64, locale),
+        s.recent_days,
+        s.current_streak(),
     )
 }
 
+/// `--anonymize-owner`: keep the first character of the owner's local part
+/// and the domain, `"jophba@chromium.org"` → `"j***@chromium.org"`.  A bare
+/// username becomes `"j***"`.
+pub fn mask_email(owner: &str) -> String {
+    let (local, domain) = match owner.split_once('@') {
+        Some((local, domain)) => (local, Some(domain)),
+        None => (owner, None),
+    };
+    let mut masked: String = local.chars().take(1).collect();
+    masked.push_str("***");
+    if let Some(domain) = domain {
+        masked.push('@');
+        masked.push_str(domain);
+    }
+    masked
+}
+
+// ---------------------------------------------------------------------------
+// Terminal report layout
+// ---------------------------------------------------------------------------
+
+/// Inner width of the terminal report's header box when the terminal's
+/// width is unknown.
+pub const REPORT_WIDTH: usize = 60;
+
+/// Narrowest inner width the report is laid out for (`--width` rejects
+/// less); the header still fits its labels and a few owner characters.
+pub const MIN_REPORT_WIDTH: usize = 30;
+
+/// The terminal report's header box, `width` columns between its borders.
+/// Owner and host list are cut with `…` to fit, keeping the right border
+/// aligned.
+pub fn report_header(owner: &str, host_label: &str, width: usize) -> [String; 4] {
+    let width = width.max(MIN_REPORT_WIDTH);
+    let bar = "─".repeat(width);
+    let field = |label: &str, value: &str| {
+        let room = width - label.chars().count();
+        format!("│{label}{:<room$}│", truncate(value, room))
+    };
+    [
+        format!("┌{bar}┐"),
+        field("  gerritoscope · ", owner),
+        field("  hosts: ", host_label),
+        format!("└{bar}┘"),
+    ]
+}
+
+/// Column width for project names in the report's top-projects list, which
+/// spends 24 columns on indentation and the CL count.
+pub fn project_name_width(width: usize) -> usize {
+    width.max(MIN_REPORT_WIDTH) - 24
+}
+
+/// `s` cut to at most `max` characters, ending in `…` when shortened.
+pub fn truncate(s: &str, max: usize) -> String {
+    if s.chars().count() <= max {
+        return s.to_owned();
+    }
+    let mut cut: String = s.chars().take(max.saturating_sub(1)).collect();
+    if max > 0 {
+        cut.push('…');
+    }
+    cut
+}
+
+// ---------------------------------------------------------------------------
+// Contribution range
+// ---------------------------------------------------------------------------
+
+/// `"2019-03-11 → 2024-06-10 (5.3 yrs)"`, or `"n/a"` with no merged CLs.
+pub fn active_range(s: &Stats) -> String {
+    match (s.first_contribution, s.last_contribution) {
+        (Some(first), Some(last)) => format!(
+            "{} → {} ({:.1} yrs)",
+            first.format("%Y-%m-%d"),
+            last.format("%Y-%m-%d"),
+            (last - first).num_days() as f64 / 365.25,
+        ),
+        _ => "n/a".to_owned(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Partial-result note
+// ---------------------------------------------------------------------------
+
+/// `"⚠ 2 hosts failed (go, qt)"`, or `None` when every host succeeded.
+pub fn failed_hosts_note(failed: &[String]) -> Option<String> {
+    if failed.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "⚠ {} host{} failed ({})",
+        failed.len(),
+        if failed.len() == 1 { "" } else { "s" },
+        failed.join(", "),
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// Per-host breakdown
+// ---------------------------------------------------------------------------
+
+/// `"chromium: 812 CLs, 1,020 reviews · go: 44 CLs, 3 reviews"`, or `None`
+/// without counts (`--show-host-breakdown` not given).
+pub fn host_breakdown(counts: &[HostCount], locale: &Locale) -> Option<String> {
+    if counts.is_empty() {
+        return None;
+    }
+    let parts: Vec<String> = counts
+        .iter()
+        .map(|h| {
+            format!(
+                "{}: {} CL{}, {} review{}",
+                h.alias,
+                fmt_count(h.cls as i64, locale),
+                if h.cls == 1 { "" } else { "s" },
+                fmt_count(h.reviews as i64, locale),
+                if h.reviews == 1 { "" } else { "s" },
+            )
+        })
+        .collect();
+    Some(parts.join(" · "))
+}
+
+// ---------------------------------------------------------------------------
+// Empty-result note
+// ---------------------------------------------------------------------------
+
+/// Explanation for an all-empty report (see [`Stats::is_empty`]), or `None`
+/// when there is something to show.
+pub fn no_activity_note(owner: &str, hosts: &[(String, String)], s: &Stats) -> Option<String> {
+    if !s.is_empty() {
+        return None;
+    }
+    let aliases: Vec<&str> = hosts.iter().map(|(alias, _)| alias.as_str()).collect();
+    Some(format!(
+        "No merged changes or reviews found for {owner} on {}. \
+         Check the --owner value and try --username/--password.",
+        aliases.join(", "),
+    ))
+}
+
+/// `--fail-on-empty`: an error carrying [`no_activity_note`] when `fail` is
+/// set and the report is empty, so CI jobs stop before writing a zeroed card.
+pub fn check_not_empty(
+    owner: &str,
+    hosts: &[(String, String)],
+    s: &Stats,
+    fail: bool,
+) -> Result<()> {
+    match no_activity_note(owner, hosts, s) {
+        Some(note) if fail => bail!("--fail-on-empty: {note}"),
+        _ => Ok(()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Locale
+// ---------------------------------------------------------------------------
+
+/// Digit grouping and month abbreviations selected with `--locale`.
+///
+/// Month names are kept to three characters so the text heatmap header
+/// lines up the same way in every locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    /// Thousands separator used by [`fmt_count`].
+    pub group_sep: char,
+    /// Abbreviations for January through December.
+    pub months: [&'static str; 12],
+}
+
+impl Locale {
+    pub const EN: Locale = Locale {
+        group_sep: ',',
+        months: [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ],
+    };
+
+    pub const DE: Locale = Locale {
+        group_sep: '.',
+        months: [
+            "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+        ],
+    };
+
+    /// French groups digits with a narrow no-break space (U+202F).
+    pub const FR: Locale = Locale {
+        group_sep: '\u{202f}',
+        months: [
+            "jan", "fév", "mar", "avr", "mai", "jun", "jul", "aoû", "sep", "oct", "nov", "déc",
+        ],
+    };
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::EN
+    }
+}
+
+impl FromStr for Locale {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "en" => Ok(Locale::EN),
+            "de" => Ok(Locale::DE),
+            "fr" => Ok(Locale::FR),
+            other => bail!("unknown locale {other:?}; valid names: en, de, fr"),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Number formatting
 // ---------------------------------------------------------------------------
 
-/// Format an integer with thousands separators: `1234567` → `"1,234,567"`.
-pub fn fmt_count(n: i64) -> String {
+/// Format an integer with the locale's thousands separator: `1234567` →
+/// `"1,234,567"` (`en`) or `"1.234.567"` (`de`).
+pub fn fmt_count(n: i64, locale: &Locale) -> String {
     let digits = n.unsigned_abs().to_string();
     let grouped: String = digits
         .chars()
         .rev()
         .enumerate()
         .flat_map(|(i, c)| {
-            if i > 0 && i % 3 == 0 { Some(',') } else { None }
-                .into_iter()
-                .chain(std::iter::once(c))
+            if i > 0 && i % 3 == 0 {
+                Some(locale.group_sep)
+            } else {
+                None
+            }
+            .into_iter()
+            .chain(std::iter::once(c))
         })
         .collect::<String>()
         .chars()
@@ -99,24 +551,65 @@ pub fn fmt_count(n: i64) -> String {
     }
 }
 
+/// [`Stats::total_reviews`] via [`fmt_count`], with a trailing `+` when
+/// `--max-reviews` cut the fetch short: `"5,000+"`.
+pub fn fmt_total_reviews(s: &Stats, locale: &Locale) -> String {
+    let count = fmt_count(s.total_reviews as i64, locale);
+    if s.reviews_capped {
+        format!("{count}+")
+    } else {
+        count
+    }
+}
+
+/// The period [`Stats::total_reviews`] covers, for labels: `"52 wk"`, or
+/// `"since 2019-01-01"` when `--after` extended the review fetch.
+pub fn reviews_window(s: &Stats) -> String {
+    match s.reviews_since {
+        Some(date) => format!("since {date}"),
+        None => "52 wk".to_owned(),
+    }
+}
+
+/// Change from `previous` to `current` as an arrow and a [`fmt_count`]:
+/// `"↑8"`, `"↓5"`, or `"="` when flat.
+pub fn fmt_delta(current: usize, previous: usize, locale: &Locale) -> String {
+    let diff = current as i64 - previous as i64;
+    match diff.signum() {
+        1 => format!("↑{}", fmt_count(diff, locale)),
+        -1 => format!("↓{}", fmt_count(-diff, locale)),
+        _ => "=".to_owned(),
+    }
+}
+
+/// `"top 5% of 120 contributors"`; capped counts read `"~top 5% of 120+
+/// contributors"`.
+pub fn fmt_project_rank(r: &ProjectRank, locale: &Locale) -> String {
+    let (approx, plus) = if r.approximate { ("~", "+") } else { ("", "") };
+    format!(
+        "{approx}top {}% of {}{plus} contributors",
+        r.top_percent(),
+        fmt_count(r.contributors as i64, locale),
+    )
+}
+
+/// Format a vote histogram highest score first: `"+2 ×131 · +1 ×38 · -1 ×12"`.
+pub fn fmt_score_histogram(hist: &BTreeMap<i32, usize>, locale: &Locale) -> String {
+    hist.iter()
+        .rev()
+        .map(|(score, n)| format!("{score:+} ×{}", fmt_count(*n as i64, locale)))
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
 
-pub fn month_abbr(m: u32) -> &'static str {
+/// Three-letter month name for `m` (1 – 12) in `locale`.
+pub fn month_abbr(m: u32, locale: &Locale) -> &'static str {
     match m {
-        1 => "Jan",
-        2 => "Feb",
-        3 => "Mar",
-        4 => "Apr",
-        5 => "May",
-        6 => "Jun",
-        7 => "Jul",
-        8 => "Aug",
-        9 => "Sep",
-        10 => "Oct",
-        11 => "Nov",
-        12 => "Dec",
+        1..=12 => locale.months[m as usize - 1],
         _ => "???",
     }
 }
@@ -133,13 +626,19 @@ mod tests {
     use std::collections::HashMap;
 
     fn empty_heatmap(weeks: usize) -> Heatmap {
+        heatmap_from(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), weeks)
+    }
+
+    fn heatmap_from(start: NaiveDate, weeks: usize) -> Heatmap {
         Heatmap {
             weeks: (0..weeks)
                 .map(|i| WeekBucket {
-                    week_start: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
-                        + chrono::Duration::weeks(i as i64),
+                    week_start: start + chrono::Duration::weeks(i as i64),
                     count: 0,
                     review_count: 0,
+                    lines_changed: 0,
+                    insertions: 0,
+                    deletions: 0,
                     family_counts: HashMap::new(),
                 })
                 .collect(),
@@ -150,45 +649,446 @@ mod tests {
     #[test]
     fn heatmap_body_length_matches_weeks() {
         let h = empty_heatmap(52);
-        assert_eq!(heatmap_body(&h).chars().count(), 52);
+        assert_eq!(heatmap_body(&h, &BLOCK_GLYPHS).chars().count(), 52);
     }
 
     #[test]
     fn heatmap_body_all_spaces_when_empty() {
         let h = empty_heatmap(52);
-        assert!(heatmap_body(&h).chars().all(|c| c == ' '));
+        assert!(heatmap_body(&h, &BLOCK_GLYPHS).chars().all(|c| c == ' '));
+    }
+
+    #[test]
+    fn ascii_glyphs_map_peak_to_hash() {
+        let glyphs = parse_glyphs("ascii").unwrap();
+        assert_eq!(levels_body(&[0, 1, 2, 3, 4], &glyphs), " .:+#");
+
+        let mut h = empty_heatmap(3);
+        h.weeks[2].count = 10;
+        assert_eq!(heatmap_body(&h, &glyphs), "  #");
+        assert!(heatmap_code_block(&h, &glyphs, &Locale::EN).contains("[  #]"));
+    }
+
+    #[test]
+    fn custom_glyphs_need_exactly_five_chars() {
+        assert_eq!(parse_glyphs(" .oO@").unwrap(), [' ', '.', 'o', 'O', '@']);
+        assert_eq!(parse_glyphs("blocks").unwrap(), BLOCK_GLYPHS);
+        // Counted in characters, not bytes.
+        assert_eq!(parse_glyphs("·░▒▓█").unwrap()[0], '·');
+
+        let err = parse_glyphs(" .oO").unwrap_err().to_string();
+        assert!(err.contains("has 4 characters"), "{err}");
+        assert!(parse_glyphs(" .oO@#").is_err());
+        assert!(parse_glyphs("").is_err());
     }
 
     #[test]
     fn heatmap_header_length_matches_weeks() {
         let h = empty_heatmap(52);
-        assert_eq!(heatmap_header(&h).len(), 52);
+        assert_eq!(heatmap_header(&h, &Locale::EN).len(), 52);
+    }
+
+    #[test]
+    fn january_gets_a_year_across_a_year_boundary() {
+        // 2024-06-17 … 2025-06-09: Dec 2024 then Jan 2025.
+        let h = heatmap_from(NaiveDate::from_ymd_opt(2024, 6, 17).unwrap(), 52);
+        assert!(spans_years(&h));
+        let header = heatmap_header(&h, &Locale::EN);
+        assert_eq!(header.chars().count(), 52);
+        assert!(header.contains("Dec  Jan'25"), "{header:?}");
+        // The wider label crowds out February rather than overlapping it.
+        assert!(!header.contains("Feb"), "{header:?}");
+        assert!(header.contains("Mar"), "{header:?}");
+
+        // One calendar year: plain labels.
+        let header = heatmap_header(&empty_heatmap(52), &Locale::EN);
+        assert!(header.starts_with("Jan "), "{header:?}");
+        assert!(!header.contains('\''), "{header:?}");
+    }
+
+    #[test]
+    fn month_label_suffixes_only_january() {
+        let d = |m| NaiveDate::from_ymd_opt(2005, m, 3).unwrap();
+        assert_eq!(month_label(d(1), true, &Locale::EN), "Jan'05");
+        assert_eq!(month_label(d(2), true, &Locale::EN), "Feb");
+        assert_eq!(month_label(d(1), false, &Locale::EN), "Jan");
     }
 
     #[test]
     fn fmt_count_zero() {
-        assert_eq!(fmt_count(0), "0");
+        assert_eq!(fmt_count(0, &Locale::EN), "0");
     }
 
     #[test]
     fn fmt_count_thousands() {
-        assert_eq!(fmt_count(1_234_567), "1,234,567");
+        assert_eq!(fmt_count(1_234_567, &Locale::EN), "1,234,567");
     }
 
     #[test]
     fn fmt_count_negative() {
-        assert_eq!(fmt_count(-42_000), "-42,000");
+        assert_eq!(fmt_count(-42_000, &Locale::EN), "-42,000");
     }
 
     #[test]
     fn fmt_count_below_thousand() {
-        assert_eq!(fmt_count(999), "999");
+        assert_eq!(fmt_count(999, &Locale::EN), "999");
+    }
+
+    #[test]
+    fn fmt_count_german_grouping() {
+        let de: Locale = "de".parse().unwrap();
+        assert_eq!(fmt_count(1_234_567, &de), "1.234.567");
+        assert_eq!(fmt_count(-42_000, &de), "-42.000");
+        assert_eq!(
+            fmt_score_histogram(&BTreeMap::from([(2, 1200)]), &de),
+            "+2 ×1.200"
+        );
+    }
+
+    #[test]
+    fn capped_review_total_is_a_lower_bound() {
+        let mut s = crate::stats::sample_stats(NaiveDate::from_ymd_opt(2024, 6, 12).unwrap());
+        s.total_reviews = 5000;
+        assert_eq!(fmt_total_reviews(&s, &Locale::EN), "5,000");
+        s.reviews_capped = true;
+        assert_eq!(fmt_total_reviews(&s, &Locale::EN), "5,000+");
+    }
+
+    #[test]
+    fn delta_arrows() {
+        assert_eq!(fmt_delta(42, 34, &Locale::EN), "↑8");
+        assert_eq!(fmt_delta(30, 35, &Locale::EN), "↓5");
+        assert_eq!(fmt_delta(7, 7, &Locale::EN), "=");
+        assert_eq!(fmt_delta(1500, 0, &Locale::DE), "↑1.500");
+    }
+
+    #[test]
+    fn unknown_locale_is_rejected() {
+        let err = "xx".parse::<Locale>().unwrap_err().to_string();
+        assert!(err.contains("en, de, fr"), "{err}");
+    }
+
+    fn format_sample(format: OutputFormat) -> String {
+        let now = chrono::Utc::now();
+        let mut s = crate::stats::compute(&[], &[], now);
+        s.total_merged = 3;
+        s.heatmap.weeks.last_mut().unwrap().count = 3;
+        let hosts = vec![(
+            "go".to_owned(),
+            "https://go-review.googlesource.com".to_owned(),
+        )];
+        render_format(format, "me", &hosts, &s, &FormatOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn output_format_parse() {
+        assert_eq!(
+            "md".parse::<OutputFormat>().unwrap(),
+            OutputFormat::Markdown
+        );
+        assert_eq!("json".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
+        assert_eq!(
+            "digest".parse::<OutputFormat>().unwrap(),
+            OutputFormat::Digest
+        );
+        assert_eq!(
+            "mermaid".parse::<OutputFormat>().unwrap(),
+            OutputFormat::Mermaid
+        );
+        assert!("yaml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn render_format_markdown() {
+        let out = format_sample(OutputFormat::Markdown);
+        assert!(out.starts_with("## gerritoscope · me"));
+        assert_eq!(out.matches("```").count(), 2);
+    }
+
+    #[test]
+    fn render_format_svg() {
+        let out = format_sample(OutputFormat::Svg);
+        assert!(out.trim_start().starts_with("<svg"));
+        assert!(out.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn render_format_csv() {
+        let out = format_sample(OutputFormat::Csv);
+        let mut lines = out.lines();
+        assert_eq!(lines.next(), Some(csv::HEADER));
+        assert_eq!(lines.count(), crate::stats::HEATMAP_WEEKS);
+    }
+
+    #[test]
+    fn render_format_badge() {
+        let out = format_sample(OutputFormat::Badge);
+        let v: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(v["message"], "3");
+    }
+
+    #[test]
+    fn render_format_json() {
+        let out = format_sample(OutputFormat::Json);
+        let v: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(v["stats"]["total_merged"], 3);
+    }
+
+    #[test]
+    fn render_format_digest() {
+        let out = format_sample(OutputFormat::Digest);
+        assert!(out.contains("you merged 3 CLs"), "{out}");
+    }
+
+    #[test]
+    fn render_format_mermaid() {
+        let out = format_sample(OutputFormat::Mermaid);
+        assert!(out.contains("xychart-beta"), "{out}");
+    }
+
+    #[test]
+    fn summary_line_format() {
+        let now = chrono::Utc::now();
+        let mut s = crate::stats::compute(&[], &[], now);
+        s.total_merged = 1234;
+        s.recent_merged_90d = 42;
+        for b in s.heatmap.weeks.iter_mut().rev().take(7) {
+            b.count = 1;
+        }
+        assert_eq!(
+            summary_line("me", &s, &Locale::EN),
+            "me: 1,234 CLs · 42/90d · 7wk streak"
+        );
+        s.recent_days = 30;
+        assert!(summary_line("me", &s, &Locale::EN).contains(" · 42/30d · "));
+    }
+
+    #[test]
+    fn active_range_format() {
+        let mut s = crate::stats::compute(&[], &[], chrono::Utc::now());
+        assert_eq!(active_range(&s), "n/a");
+        s.first_contribution = NaiveDate::from_ymd_opt(2019, 3, 11);
+        s.last_contribution = NaiveDate::from_ymd_opt(2024, 6, 10);
+        assert_eq!(active_range(&s), "2019-03-11 → 2024-06-10 (5.3 yrs)");
+    }
+
+    #[test]
+    fn host_breakdown_lists_each_alias_with_its_counts() {
+        let counts = [
+            HostCount {
+                alias: "chromium".to_owned(),
+                cls: 812,
+                reviews: 1020,
+            },
+            HostCount {
+                alias: "go".to_owned(),
+                cls: 1,
+                reviews: 0,
+            },
+        ];
+        assert_eq!(
+            host_breakdown(&counts, &Locale::EN).unwrap(),
+            "chromium: 812 CLs, 1,020 reviews · go: 1 CL, 0 reviews"
+        );
+        assert_eq!(host_breakdown(&[], &Locale::EN), None);
+    }
+
+    #[test]
+    fn failed_hosts_note_none_when_empty() {
+        assert_eq!(failed_hosts_note(&[]), None);
+    }
+
+    #[test]
+    fn failed_hosts_note_pluralises() {
+        assert_eq!(
+            failed_hosts_note(&["go".to_owned()]).unwrap(),
+            "⚠ 1 host failed (go)"
+        );
+        assert_eq!(
+            failed_hosts_note(&["go".to_owned(), "qt".to_owned()]).unwrap(),
+            "⚠ 2 hosts failed (go, qt)"
+        );
+    }
+
+    #[test]
+    fn truncate_honours_width() {
+        assert_eq!(truncate("chromium/src", 20), "chromium/src");
+        assert_eq!(truncate("chromium/src", 12), "chromium/src");
+        assert_eq!(truncate("chromium/src", 8), "chromiu…");
+        assert_eq!(truncate("chromium/src", 8).chars().count(), 8);
+        assert_eq!(truncate("ünïcödé", 4), "ünï…");
+        assert_eq!(truncate("abc", 0), "");
+    }
+
+    #[test]
+    fn report_header_fills_width() {
+        for width in [MIN_REPORT_WIDTH, REPORT_WIDTH, 198] {
+            let lines = report_header("me@example.org", "chromium, go", width);
+            for line in &lines {
+                assert_eq!(line.chars().count(), width + 2, "{line:?}");
+            }
+            assert!(lines[1].contains("me@example"), "{:?}", lines[1]);
+        }
+    }
+
+    #[test]
+    fn narrow_report_header_stays_aligned() {
+        let owner = "a-very-long-owner-name@chromium.example.org";
+        let lines = report_header(owner, "chromium, go, android, fuchsia", 10);
+        // Clamped to the minimum width; long values are cut, not overflowed.
+        for line in &lines {
+            assert_eq!(line.chars().count(), MIN_REPORT_WIDTH + 2, "{line:?}");
+        }
+        assert!(lines[1].ends_with("…│"), "{:?}", lines[1]);
+        assert_eq!(project_name_width(10), MIN_REPORT_WIDTH - 24);
+        assert_eq!(project_name_width(REPORT_WIDTH), 36);
+    }
+
+    #[test]
+    fn mask_email_keeps_initial_and_domain() {
+        assert_eq!(mask_email("jophba@chromium.org"), "j***@chromium.org");
+        assert_eq!(mask_email("é@example.com"), "é***@example.com");
+    }
+
+    #[test]
+    fn mask_email_masks_bare_usernames() {
+        assert_eq!(mask_email("jophba"), "j***");
+        assert_eq!(mask_email("1000096"), "1***");
+    }
+
+    #[test]
+    fn no_activity_note_only_for_empty_stats() {
+        let hosts = vec![
+            (
+                "chromium".to_owned(),
+                "https://chromium-review.googlesource.com".to_owned(),
+            ),
+            (
+                "go".to_owned(),
+                "https://go-review.googlesource.com".to_owned(),
+            ),
+        ];
+        let mut s = crate::stats::compute(&[], &[], chrono::Utc::now());
+        assert!(s.is_empty());
+        assert_eq!(
+            no_activity_note("nobody@example.com", &hosts, &s).unwrap(),
+            "No merged changes or reviews found for nobody@example.com on chromium, go. \
+             Check the --owner value and try --username/--password."
+        );
+
+        s.total_reviews = 1;
+        assert!(!s.is_empty());
+        assert_eq!(no_activity_note("nobody@example.com", &hosts, &s), None);
+    }
+
+    #[test]
+    fn fail_on_empty_errors_only_when_set_and_empty() {
+        let hosts = vec![(
+            "go".to_owned(),
+            "https://go-review.googlesource.com".to_owned(),
+        )];
+        let mut s = crate::stats::compute(&[], &[], chrono::Utc::now());
+
+        let err = check_not_empty("me", &hosts, &s, true).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("--fail-on-empty: No merged changes"));
+        assert!(check_not_empty("me", &hosts, &s, false).is_ok());
+
+        s.total_merged = 1;
+        assert!(check_not_empty("me", &hosts, &s, true).is_ok());
+    }
+
+    #[test]
+    fn project_rank_reads_as_top_percent() {
+        let exact = ProjectRank {
+            rank: 6,
+            contributors: 120,
+            approximate: false,
+        };
+        assert_eq!(
+            fmt_project_rank(&exact, &Locale::EN),
+            "top 5% of 120 contributors"
+        );
+        let capped = ProjectRank {
+            rank: 1,
+            contributors: 1500,
+            approximate: true,
+        };
+        assert_eq!(
+            fmt_project_rank(&capped, &Locale::EN),
+            "~top 1% of 1,500+ contributors"
+        );
+    }
+
+    #[test]
+    fn fmt_score_histogram_highest_first() {
+        let hist: BTreeMap<i32, usize> = [(-1, 3), (2, 1200), (1, 4)].into_iter().collect();
+        assert_eq!(
+            fmt_score_histogram(&hist, &Locale::EN),
+            "+2 ×1,200 · +1 ×4 · -1 ×3"
+        );
+    }
+
+    /// Remove `ESC [ ... m` sequences.
+    fn strip_ansi(s: &str) -> String {
+        let mut out = String::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                for c in chars.by_ref() {
+                    if c == 'm' {
+                        break;
+                    }
+                }
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn heatmap_body_colored_strips_back_to_plain() {
+        let mut h = empty_heatmap(8);
+        for (i, b) in h.weeks.iter_mut().enumerate() {
+            b.count = i as u32 * 2;
+        }
+        let theme = svg::theme_by_name("github-dark").unwrap();
+        let colored = heatmap_body_colored(&h.levels(), &BLOCK_GLYPHS, theme.terminal_palette());
+        assert!(colored.contains("\x1b[38;2;"), "missing truecolor escape");
+        assert!(colored.contains("\x1b[0m"), "missing reset");
+        // github-dark L4 is #39d353.
+        assert!(colored.contains("\x1b[38;2;57;211;83m█"));
+        assert_eq!(strip_ansi(&colored), heatmap_body(&h, &BLOCK_GLYPHS));
+    }
+
+    #[test]
+    fn parse_hex_color_forms() {
+        assert_eq!(parse_hex_color("#39d353"), Some((0x39, 0xd3, 0x53)));
+        assert_eq!(parse_hex_color("#fff"), Some((255, 255, 255)));
+        assert_eq!(parse_hex_color("#ggg"), None);
+        assert_eq!(parse_hex_color("39d353"), None);
+        assert_eq!(parse_hex_color("#1234"), None);
+        assert_eq!(parse_hex_color("#aé"), None);
+        assert_eq!(parse_hex_color("#+f+f+f"), None);
+    }
+
+    #[test]
+    fn peak_label_names_the_week() {
+        let mut h = empty_heatmap(4);
+        assert_eq!(peak_label(&h), "peak: 0");
+        h.weeks[2].count = 12;
+        h.max_count = 12;
+        assert_eq!(peak_label(&h), "peak: 12 in week of 2024-01-15");
+        assert!(heatmap_code_block(&h, &BLOCK_GLYPHS, &Locale::EN)
+            .contains("peak: 12 in week of 2024-01-15"));
     }
 
     #[test]
     fn heatmap_code_block_contains_fence() {
         let h = empty_heatmap(4);
-        let block = heatmap_code_block(&h);
+        let block = heatmap_code_block(&h, &BLOCK_GLYPHS, &Locale::EN);
         assert!(block.starts_with("```\n"), "should open with fence+newline");
         assert!(block.ends_with("\n```"), "should close with newline+fence");
     }
@@ -3,42 +3,71 @@
 //! Produces a self-contained SVG that can be embedded in a GitHub profile
 //! README as `<img src="gerritoscope.svg">`.  The default `github` theme
 //! uses a CSS `prefers-color-scheme` media query to auto-switch between
-//! light and dark palettes.
+//! light and dark palettes; [`ColorMode`] pins one of them instead, for
+//! `<picture>` embeds with separate light and dark sources.
 
-use anyhow::{bail, Result};
-use chrono::Datelike;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-use crate::stats::{Heatmap, Stats};
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+
+use crate::cache::fnv1a;
+use crate::gerrit::AccountCard;
+use crate::stats::{sample_stats, Heatmap, Stats, SMOOTH_WEEKS};
+
+use super::{truncate, Locale};
 
 // ---------------------------------------------------------------------------
 // Palette & Theme
 // ---------------------------------------------------------------------------
 
 /// A complete colour palette for one display mode (light or dark).
-pub struct Palette {
-    pub bg: &'static str,
-    pub border: &'static str,
-    pub title: &'static str,
-    pub text: &'static str,
-    pub muted: &'static str,
+///
+/// Built-in palettes are `Palette<'static>`; palettes parsed from a theme
+/// file borrow their strings from the file contents.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Palette<'a> {
+    pub bg: &'a str,
+    pub border: &'a str,
+    pub title: &'a str,
+    pub text: &'a str,
+    pub muted: &'a str,
     /// Heatmap intensity colours: `levels[0]` is "empty", `levels[4]` is peak.
-    pub levels: [&'static str; 5],
+    #[serde(borrow)]
+    pub levels: [&'a str; 5],
 }
 
 /// A theme is either a pair of palettes (auto light/dark via CSS) or a single
 /// fixed palette.
-pub enum Theme {
+pub enum Theme<'a> {
     /// Embeds both palettes; CSS `prefers-color-scheme` auto-switches.
-    Auto { light: Palette, dark: Palette },
+    Auto {
+        light: Palette<'a>,
+        dark: Palette<'a>,
+    },
     /// Single fixed palette — no media query emitted.
-    Fixed(Palette),
+    Fixed(Palette<'a>),
+}
+
+impl<'a> Theme<'a> {
+    /// The palette to use outside the SVG, e.g. for the coloured terminal
+    /// heatmap.  Auto themes use their dark palette, matching most terminals.
+    pub fn terminal_palette(&self) -> &Palette<'a> {
+        match self {
+            Theme::Auto { dark, .. } => dark,
+            Theme::Fixed(p) => p,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
 // Built-in themes
 // ---------------------------------------------------------------------------
 
-static GITHUB: Theme = Theme::Auto {
+static GITHUB: Theme<'static> = Theme::Auto {
     light: Palette {
         bg: "#ffffff",
         border: "#d0d7de",
@@ -57,7 +86,7 @@ static GITHUB: Theme = Theme::Auto {
     },
 };
 
-static GITHUB_LIGHT: Theme = Theme::Fixed(Palette {
+static GITHUB_LIGHT: Theme<'static> = Theme::Fixed(Palette {
     bg: "#ffffff",
     border: "#d0d7de",
     title: "#24292f",
@@ -66,7 +95,7 @@ static GITHUB_LIGHT: Theme = Theme::Fixed(Palette {
     levels: ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"],
 });
 
-static GITHUB_DARK: Theme = Theme::Fixed(Palette {
+static GITHUB_DARK: Theme<'static> = Theme::Fixed(Palette {
     bg: "#0d1117",
     border: "#30363d",
     title: "#c9d1d9",
@@ -75,7 +104,7 @@ static GITHUB_DARK: Theme = Theme::Fixed(Palette {
     levels: ["#161b22", "#0e4429", "#006d32", "#26a641", "#39d353"],
 });
 
-static SOLARIZED_LIGHT: Theme = Theme::Fixed(Palette {
+static SOLARIZED_LIGHT: Theme<'static> = Theme::Fixed(Palette {
     bg: "#fdf6e3",
     border: "#93a1a1",
     title: "#073642",
@@ -84,7 +113,7 @@ static SOLARIZED_LIGHT: Theme = Theme::Fixed(Palette {
     levels: ["#eee8d5", "#b5d5a8", "#6dbf67", "#3a9443", "#1a6e29"],
 });
 
-static SOLARIZED_DARK: Theme = Theme::Fixed(Palette {
+static SOLARIZED_DARK: Theme<'static> = Theme::Fixed(Palette {
     bg: "#002b36",
     border: "#073642",
     title: "#93a1a1",
@@ -93,7 +122,7 @@ static SOLARIZED_DARK: Theme = Theme::Fixed(Palette {
     levels: ["#073642", "#0a3828", "#0a6640", "#1a8c52", "#2ab567"],
 });
 
-static GRUVBOX_DARK: Theme = Theme::Fixed(Palette {
+static GRUVBOX_DARK: Theme<'static> = Theme::Fixed(Palette {
     bg: "#282828",
     border: "#504945",
     title: "#ebdbb2",
@@ -102,7 +131,7 @@ static GRUVBOX_DARK: Theme = Theme::Fixed(Palette {
     levels: ["#3c3836", "#1d4a26", "#2d6a2f", "#3d8c3d", "#52b452"],
 });
 
-static GRUVBOX_LIGHT: Theme = Theme::Fixed(Palette {
+static GRUVBOX_LIGHT: Theme<'static> = Theme::Fixed(Palette {
     bg: "#fbf1c7",
     border: "#d5c4a1",
     title: "#3c3836",
@@ -111,7 +140,7 @@ static GRUVBOX_LIGHT: Theme = Theme::Fixed(Palette {
     levels: ["#f2e5bc", "#b8d8a8", "#6dbf67", "#3a9443", "#1a6e29"],
 });
 
-static TOKYO_NIGHT: Theme = Theme::Fixed(Palette {
+static TOKYO_NIGHT: Theme<'static> = Theme::Fixed(Palette {
     bg: "#1a1b26",
     border: "#292e42",
     title: "#c0caf5",
@@ -120,7 +149,7 @@ static TOKYO_NIGHT: Theme = Theme::Fixed(Palette {
     levels: ["#24283b", "#0d3b2e", "#1a6b3c", "#26a651", "#39d353"],
 });
 
-static DRACULA: Theme = Theme::Fixed(Palette {
+static DRACULA: Theme<'static> = Theme::Fixed(Palette {
     bg: "#282a36",
     border: "#44475a",
     title: "#f8f8f2",
@@ -129,7 +158,7 @@ static DRACULA: Theme = Theme::Fixed(Palette {
     levels: ["#44475a", "#1a3d2b", "#2d6a35", "#3d9140", "#50bd55"],
 });
 
-static CATPPUCCIN_MOCHA: Theme = Theme::Fixed(Palette {
+static CATPPUCCIN_MOCHA: Theme<'static> = Theme::Fixed(Palette {
     bg: "#1e1e2e",
     border: "#313244",
     title: "#cdd6f4",
@@ -138,9 +167,42 @@ static CATPPUCCIN_MOCHA: Theme = Theme::Fixed(Palette {
     levels: ["#313244", "#1a4731", "#1f6e3c", "#2a9c51", "#39d353"],
 });
 
-/// Look up a built-in theme by CLI name.
-pub fn theme_by_name(name: &str) -> Result<&'static Theme> {
+const THEME_NAMES: &[&str] = &[
+    "github",
+    "github-light",
+    "github-dark",
+    "solarized-light",
+    "solarized-dark",
+    "gruvbox-dark",
+    "gruvbox-light",
+    "tokyo-night",
+    "dracula",
+    "catppuccin-mocha",
+];
+
+/// CLI names of every built-in theme, in the order they're documented.
+pub fn theme_names() -> &'static [&'static str] {
+    THEME_NAMES
+}
+
+/// `--svg-theme random`: a built-in theme picked by hashing `day`, so the
+/// card changes daily but stays put within a day.
+pub fn random_theme_name(day: NaiveDate) -> &'static str {
+    // Fibonacci hashing spreads consecutive days across the list.
+    let hash = (day.num_days_from_ce() as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32;
+    THEME_NAMES[(hash % THEME_NAMES.len() as u64) as usize]
+}
+
+/// Look up a built-in theme by CLI name; `random` picks today's (UTC) theme
+/// via [`random_theme_name`].
+pub fn theme_by_name(name: &str) -> Result<&'static Theme<'static>> {
+    theme_on(name, chrono::Utc::now().date_naive())
+}
+
+/// [`theme_by_name`] with `random` resolved for `day`.
+pub fn theme_on(name: &str, day: NaiveDate) -> Result<&'static Theme<'static>> {
     match name {
+        "random" => theme_on(random_theme_name(day), day),
         "github" => Ok(&GITHUB),
         "github-light" => Ok(&GITHUB_LIGHT),
         "github-dark" => Ok(&GITHUB_DARK),
@@ -152,14 +214,207 @@ pub fn theme_by_name(name: &str) -> Result<&'static Theme> {
         "dracula" => Ok(&DRACULA),
         "catppuccin-mocha" => Ok(&CATPPUCCIN_MOCHA),
         other => bail!(
-            "unknown theme {:?}; valid names: github, github-light, github-dark, \
-             solarized-light, solarized-dark, gruvbox-dark, gruvbox-light, \
-             tokyo-night, dracula, catppuccin-mocha",
-            other
+            "unknown theme {:?}; valid names: {}, random",
+            other,
+            THEME_NAMES.join(", ")
         ),
     }
 }
 
+/// Render [`sample_stats`] with every built-in theme into
+/// `dir/<theme>.svg` (creating `dir`), returning the paths written.
+///
+/// Needs no network access; used by `--theme-preview` and `gen_samples`.
+pub fn write_theme_previews(dir: &Path, today: NaiveDate) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+
+    let stats = sample_stats(today);
+    let hosts = vec![(
+        "chromium".to_owned(),
+        "https://chromium-review.googlesource.com".to_owned(),
+    )];
+
+    let mut written = Vec::with_capacity(THEME_NAMES.len());
+    for name in THEME_NAMES {
+        let opts = SvgOptions {
+            theme: theme_by_name(name)?,
+            ..SvgOptions::default()
+        };
+        let svg = render("demo@example.com", &hosts, &stats, &opts)?;
+        let path = dir.join(format!("{name}.svg"));
+        std::fs::write(&path, svg).with_context(|| format!("writing {}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+// ---------------------------------------------------------------------------
+// Theme files
+// ---------------------------------------------------------------------------
+
+/// On-disk layout of a `--svg-theme-file`: a light (or only) palette at the
+/// top level, plus an optional `dark` palette that turns it into an
+/// auto-switching theme.
+///
+/// ```json
+/// {
+///   "bg": "#ffffff", "border": "#d0d7de", "title": "#24292f",
+///   "text": "#57606a", "muted": "#6e7781",
+///   "levels": ["#ebedf0", "#9be9a8", "#40c463", "#30a14e", "#216e39"],
+///   "dark": { "bg": "#0d1117", "...": "..." }
+/// }
+/// ```
+#[derive(Deserialize)]
+struct ThemeFile<'a> {
+    #[serde(flatten, borrow)]
+    light: Palette<'a>,
+    #[serde(default, borrow)]
+    dark: Option<Palette<'a>>,
+}
+
+/// Parse the contents of a `--svg-theme-file` (see the README for the layout).
+///
+/// The returned [`Theme`] borrows from `text`.  A file with a `dark` palette
+/// yields [`Theme::Auto`]; otherwise [`Theme::Fixed`].
+pub fn parse_theme(text: &str) -> Result<Theme<'_>> {
+    let file: ThemeFile<'_> = serde_json::from_str(text).context("parsing theme file")?;
+    validate_palette(&file.light).context("light palette")?;
+    match file.dark {
+        Some(dark) => {
+            validate_palette(&dark).context("dark palette")?;
+            Ok(Theme::Auto {
+                light: file.light,
+                dark,
+            })
+        }
+        None => Ok(Theme::Fixed(file.light)),
+    }
+}
+
+/// `true` for `#rgb` or `#rrggbb` hex colours (case-insensitive).
+pub fn is_valid_hex_color(s: &str) -> bool {
+    match s.strip_prefix('#') {
+        Some(hex) => {
+            (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => false,
+    }
+}
+
+/// Check every colour in a user-supplied palette, naming the first bad field.
+fn validate_palette(p: &Palette<'_>) -> Result<()> {
+    let named = [
+        ("bg".to_owned(), p.bg),
+        ("border".to_owned(), p.border),
+        ("title".to_owned(), p.title),
+        ("text".to_owned(), p.text),
+        ("muted".to_owned(), p.muted),
+    ];
+    let levels = p
+        .levels
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (format!("levels[{i}]"), *c));
+    for (field, color) in named.into_iter().chain(levels) {
+        if !is_valid_hex_color(color) {
+            bail!("{field}: '{color}' is not a valid #rgb/#rrggbb color");
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Theme checks
+// ---------------------------------------------------------------------------
+
+/// Least contrast for `title` and `text` against `bg` (WCAG AA body text).
+const MIN_TEXT_CONTRAST: f64 = 4.5;
+/// Least contrast for `muted` captions (WCAG AA large text).
+const MIN_MUTED_CONTRAST: f64 = 3.0;
+/// Heatmap levels closer than this to `levels[0]` pass for empty cells.
+const MIN_LEVEL_CONTRAST: f64 = 1.1;
+
+/// WCAG contrast ratio between two colours: 1 for identical luminance up
+/// to 21 for black on white.  Symmetric in its arguments.
+pub fn contrast_ratio(fg: (u8, u8, u8), bg: (u8, u8, u8)) -> f64 {
+    let (a, b) = (relative_luminance(fg), relative_luminance(bg));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// sRGB relative luminance, per WCAG 2.
+fn relative_luminance((r, g, b): (u8, u8, u8)) -> f64 {
+    let linear = |c: u8| {
+        let c = f64::from(c) / 255.0;
+        if c <= 0.039_28 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// `--validate-theme`: readability problems in `theme`, one line each,
+/// or an empty list when it passes.  Auto themes check both palettes.
+pub fn theme_warnings(theme: &Theme<'_>) -> Vec<String> {
+    match theme {
+        Theme::Auto { light, dark } => {
+            let mut warnings = palette_warnings(light, "light: ");
+            warnings.extend(palette_warnings(dark, "dark: "));
+            warnings
+        }
+        Theme::Fixed(p) => palette_warnings(p, ""),
+    }
+}
+
+fn palette_warnings(p: &Palette<'_>, prefix: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let Some(bg) = super::parse_hex_color(p.bg) else {
+        return vec![format!("{prefix}bg '{}' is not a valid color", p.bg)];
+    };
+    let on_bg = [
+        ("title", p.title, MIN_TEXT_CONTRAST),
+        ("text", p.text, MIN_TEXT_CONTRAST),
+        ("muted", p.muted, MIN_MUTED_CONTRAST),
+    ];
+    for (field, color, min) in on_bg {
+        match super::parse_hex_color(color) {
+            Some(fg) => {
+                let ratio = contrast_ratio(fg, bg);
+                if ratio < min {
+                    warnings.push(format!(
+                        "{prefix}{field} on bg has contrast {ratio:.1}:1, below {min}:1"
+                    ));
+                }
+            }
+            None => warnings.push(format!("{prefix}{field} '{color}' is not a valid color")),
+        }
+    }
+
+    let Some(empty) = super::parse_hex_color(p.levels[0]) else {
+        warnings.push(format!(
+            "{prefix}levels[0] '{}' is not a valid color",
+            p.levels[0]
+        ));
+        return warnings;
+    };
+    for (i, color) in p.levels.iter().enumerate().skip(1) {
+        match super::parse_hex_color(color) {
+            Some(level) if contrast_ratio(level, empty) < MIN_LEVEL_CONTRAST => {
+                warnings.push(format!(
+                    "{prefix}levels[{i}] '{color}' is hard to tell from levels[0] '{}'",
+                    p.levels[0]
+                ));
+            }
+            Some(_) => {}
+            None => warnings.push(format!(
+                "{prefix}levels[{i}] '{color}' is not a valid color"
+            )),
+        }
+    }
+    warnings
+}
+
 // ---------------------------------------------------------------------------
 // Multi-colour support
 // ---------------------------------------------------------------------------
@@ -200,23 +455,135 @@ const FAMILY_PALETTES: &[([&str; 4], [&str; 4])] = &[
     ),
 ];
 
+/// The [`FAMILY_PALETTES`] entry for the family called `name`.
+///
+/// Hashes the name with [`fnv1a`] (see there for why), so a family keeps its
+/// hue across runs and machines, no matter which other families appear
+/// alongside it.
+fn family_palette_index(name: &str) -> usize {
+    (fnv1a(name.as_bytes()) % FAMILY_PALETTES.len() as u64) as usize
+}
+
 // ---------------------------------------------------------------------------
 // Options
 // ---------------------------------------------------------------------------
 
+/// Which palette of a [`Theme::Auto`] to emit.  Fixed themes look the same in
+/// every mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Both palettes, switched by a `prefers-color-scheme` media query.
+    #[default]
+    Auto,
+    /// The light palette only, no media query.
+    Light,
+    /// The dark palette only, no media query.
+    Dark,
+}
+
+/// How each heatmap cell is drawn, selected with `--svg-stack`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SvgStack {
+    /// One square per week.
+    #[default]
+    None,
+    /// Split each active week vertically: merged CLs at the bottom, reviews
+    /// above in a lighter shade, each sized by its share of the week.
+    ClsReviews,
+}
+
+impl FromStr for SvgStack {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(SvgStack::None),
+            "cls-reviews" => Ok(SvgStack::ClsReviews),
+            other => bail!("unknown SVG stack mode {other:?}; valid names: none, cls-reviews"),
+        }
+    }
+}
+
+/// Which parts of the card [`render`] draws, selected with `--svg-layout`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SvgLayout {
+    /// Title, month labels, grid, peak note, divider and stats line.
+    #[default]
+    Full,
+    /// Just the grid, in a card shrunk to fit it.
+    HeatmapOnly,
+}
+
+impl FromStr for SvgLayout {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "full" => Ok(SvgLayout::Full),
+            "heatmap-only" => Ok(SvgLayout::HeatmapOnly),
+            other => bail!("unknown SVG layout {other:?}; valid names: full, heatmap-only"),
+        }
+    }
+}
+
 /// Rendering options passed to [`render`].
 pub struct SvgOptions<'a> {
-    /// Theme name (default `"github"`).
-    pub theme: &'a str,
+    /// Resolved theme — from [`theme_by_name`] or [`parse_theme`]
+    /// (default: the built-in `github` theme).
+    pub theme: &'a Theme<'a>,
     /// When true, colour each heatmap cell by the dominant Gerrit host/family.
     pub multi_color: bool,
+    /// When true, shade cells by the trailing [`SMOOTH_WEEKS`]-week average
+    /// instead of the raw weekly count.  Tooltips keep the raw counts.
+    pub smooth: bool,
+    /// When true, outline top-level (L4) cells in the theme's title colour.
+    pub highlight_peak: bool,
+    /// Auto-switching or pinned light/dark output (default: auto).
+    pub mode: ColorMode,
+    /// Month labels and digit grouping (default: English).
+    pub locale: Locale,
+    /// Display name and avatar for the header (`--svg-avatar`); `None`
+    /// titles the card with the bare owner.
+    pub account: Option<&'a AccountCard>,
+    /// `--anonymize-owner`: ignore `account`, so neither the real name nor
+    /// the avatar reaches the card; the title uses the (masked) owner.
+    pub anonymize: bool,
+    /// When true, add a muted `gerritoscope vX.Y.Z` in the bottom-right
+    /// corner.
+    pub watermark: bool,
+    /// Single-square or CL/review-stacked cells (default: single).
+    pub stack: SvgStack,
+    /// Full card or bare grid (default: full).
+    pub layout: SvgLayout,
+    /// When true, cells fade and grow in column by column on load, via a
+    /// CSS `@keyframes` rule in the `<style>` block.  CSS survives GitHub's
+    /// SVG sanitiser where SMIL `<animate>` doesn't, but animation still
+    /// only plays when the SVG is loaded as an image (not inlined), and
+    /// viewers who ask for reduced motion get the static card.
+    pub animate: bool,
+    /// When true, wrap the stats line in a `<switch>` whose first branch is
+    /// a `<foreignObject>` carrying it as styled HTML; clients without
+    /// HTML-in-SVG fall back to the plain `<text>`.  GitHub's sanitiser
+    /// strips `<foreignObject>`, so this is for self-hosted cards.
+    pub foreign_object: bool,
 }
 
 impl Default for SvgOptions<'static> {
     fn default() -> Self {
         SvgOptions {
-            theme: "github",
+            theme: &GITHUB,
             multi_color: false,
+            highlight_peak: false,
+            smooth: false,
+            mode: ColorMode::Auto,
+            locale: Locale::EN,
+            account: None,
+            watermark: false,
+            stack: SvgStack::None,
+            layout: SvgLayout::Full,
+            animate: false,
+            foreign_object: false,
+            anonymize: false,
         }
     }
 }
@@ -232,10 +599,26 @@ const GRID_TOP: u32 = 52;
 const CELL: u32 = 13; // 10 px square + 3 px gap
 const SQUARE: u32 = 10;
 const TITLE_Y: u32 = 30;
+const AVATAR: u32 = 24;
+const AVATAR_Y: u32 = 13;
+const AVATAR_GAP: u32 = 8;
 const MONTH_Y: u32 = 46;
 const PEAK_Y: u32 = 78;
 const DIVIDER_Y: u32 = 90;
 const STATS_Y: u32 = 106;
+const WATERMARK_Y: u32 = 130;
+/// Most families named in the multi-colour legend; the rest are "+N more".
+const LEGEND_MAX: usize = 6;
+/// Longest family name in the legend before it is cut with `…`.
+const LEGEND_NAME_MAX: usize = 16;
+/// Characters kept clear between the peak text and the legend.
+const LEGEND_GAP: usize = 3;
+/// Approximate advance of one character of the 10 px monospace text.
+const MONO_CHAR_W: u32 = 6;
+/// Margin around the grid in [`SvgLayout::HeatmapOnly`] cards.
+const BARE_PAD: u32 = 8;
+/// Delay between successive columns of the `animate` reveal.
+const ANIMATE_STEP_MS: u32 = 20;
 
 // ---------------------------------------------------------------------------
 // Public render entry point
@@ -247,14 +630,14 @@ const STATS_Y: u32 = 106;
 /// - `owner`      — Gerrit owner (e.g. `"jophba@chromium.org"`)
 /// - `hosts`      — slice of `(alias, url)` pairs from `hosts::expand()`
 /// - `stats`      — computed statistics
-/// - `opts`       — rendering options (theme name, multi-colour flag)
+/// - `opts`       — rendering options (theme, colour mode, multi-colour flag)
 pub fn render(
     owner: &str,
     hosts: &[(String, String)],
     stats: &Stats,
     opts: &SvgOptions<'_>,
 ) -> Result<String> {
-    let theme = theme_by_name(opts.theme)?;
+    let theme = opts.theme;
     let h = &stats.heatmap;
 
     // Collect unique families for multi-colour mode.
@@ -271,34 +654,142 @@ pub fn render(
         vec![]
     };
 
-    let css = css_block(theme, &families, opts.multi_color);
-    let months = month_label_elements(h);
-    let rects = rect_elements(h, &families, opts.multi_color);
-    let title_text = title_text(owner, hosts);
-    let stats_line = stats_line(stats, h);
+    let css = css_block(
+        theme,
+        opts.mode,
+        &families,
+        opts.multi_color,
+        opts.highlight_peak,
+        opts.stack,
+        opts.animate,
+    );
+    let levels = if opts.smooth {
+        h.smoothed(SMOOTH_WEEKS)
+    } else {
+        h.levels()
+    };
+    if opts.layout == SvgLayout::HeatmapOnly {
+        let rects = rect_elements(h, &levels, &families, opts);
+        let desc = xml_escape(&desc_text(stats, &opts.locale));
+        return Ok(bare_card(owner, &desc, &css, &rects, h.weeks.len() as u32));
+    }
+    let months = month_label_elements(h, &opts.locale);
+    let rects = rect_elements(h, &levels, &families, opts);
+    // The avatar sits left of the title and pushes it right.
+    let account = opts.account.filter(|_| !opts.anonymize);
+    let (title_x, avatar) = match account.and_then(|card| card.avatar_png.as_deref()) {
+        Some(image) => (GRID_LEFT + AVATAR + AVATAR_GAP, avatar_element(image)),
+        None => (GRID_LEFT, String::new()),
+    };
+    let display = account.map_or(owner, |card| card.name.as_str());
+    let title_text = title_text(&xml_escape(display), hosts);
+    let stats_line = stats_line(stats, &opts.locale);
+    let stats_line = if opts.foreign_object {
+        stats_switch(&stats_line, &stats_html(stats, &opts.locale))
+    } else {
+        stats_text(&stats_line)
+    };
+    let desc = xml_escape(&desc_text(stats, &opts.locale));
+
+    let watermark = if opts.watermark {
+        watermark_element()
+    } else {
+        String::new()
+    };
+    let breakdown = super::host_breakdown(&stats.host_counts, &opts.locale)
+        .map(|text| breakdown_element(&xml_escape(&text)))
+        .unwrap_or_default();
+
+    let mut peak_text = format!("peak: {}/wk", h.max_count);
+    if let Some(note) = super::failed_hosts_note(&stats.failed_hosts) {
+        peak_text.push_str(" · ");
+        peak_text.push_str(&note);
+    }
 
-    let peak_text = format!("peak: {}/wk", h.max_count);
+    let legend_room = ((CARD_W - 2 * GRID_LEFT) / MONO_CHAR_W) as usize;
+    let legend_room = legend_room.saturating_sub(peak_text.chars().count() + LEGEND_GAP);
+    let legend = legend_element(h, &families, legend_room);
 
     let svg = format!(
         r#"<svg xmlns="http://www.w3.org/2000/svg" width="{CARD_W}" height="{CARD_H}" viewBox="0 0 {CARD_W} {CARD_H}" role="img" aria-label="gerritoscope heatmap for {owner}">
 <title>gerritoscope · {owner}</title>
+<desc>{desc}</desc>
 <style>
 {css}
 </style>
 <rect width="{CARD_W}" height="{CARD_H}" rx="6" fill="var(--bg)" stroke="var(--border)" stroke-width="1"/>
-<text x="16" y="{TITLE_Y}" font-family="ui-monospace,SFMono-Regular,Menlo,monospace" font-size="14" font-weight="bold" fill="var(--title)">{title_text}</text>
+{avatar}<text x="{title_x}" y="{TITLE_Y}" font-family="ui-monospace,SFMono-Regular,Menlo,monospace" font-size="14" font-weight="bold" fill="var(--title)">{title_text}</text>
 {months}<g class="heatmap">
 {rects}</g>
 <text x="{GRID_LEFT}" y="{PEAK_Y}" font-family="ui-monospace,SFMono-Regular,Menlo,monospace" font-size="10" fill="var(--muted)">{peak_text}</text>
-<line x1="{GRID_LEFT}" y1="{DIVIDER_Y}" x2="{x2}" y2="{DIVIDER_Y}" stroke="var(--border)" stroke-width="1"/>
-<text x="{GRID_LEFT}" y="{STATS_Y}" font-family="ui-monospace,SFMono-Regular,Menlo,monospace" font-size="11" fill="var(--text)">{stats_line}</text>
-</svg>"#,
+{legend}<line x1="{GRID_LEFT}" y1="{DIVIDER_Y}" x2="{x2}" y2="{DIVIDER_Y}" stroke="var(--border)" stroke-width="1"/>
+{stats_line}
+{breakdown}{watermark}</svg>"#,
+        owner = xml_escape(owner),
         x2 = CARD_W - GRID_LEFT,
     );
 
     Ok(svg)
 }
 
+/// The [`SvgLayout::HeatmapOnly`] card: `rects` (laid out from
+/// [`BARE_PAD`]) in a frame sized to `weeks` columns.
+fn bare_card(owner: &str, desc: &str, css: &str, rects: &str, weeks: u32) -> String {
+    let width = 2 * BARE_PAD + weeks * CELL - (CELL - SQUARE);
+    let height = 2 * BARE_PAD + SQUARE;
+    let owner = xml_escape(owner);
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" role="img" aria-label="gerritoscope heatmap for {owner}">
+<title>gerritoscope · {owner}</title>
+<desc>{desc}</desc>
+<style>
+{css}
+</style>
+<rect width="{width}" height="{height}" rx="6" fill="var(--bg)" stroke="var(--border)" stroke-width="1"/>
+<g class="heatmap">
+{rects}</g>
+</svg>"#
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Size budget
+// ---------------------------------------------------------------------------
+
+/// Largest card GitHub's image proxy (camo) reliably serves; bigger cards
+/// may show up broken in a README.
+pub const SIZE_BUDGET: usize = 50 * 1024;
+
+/// A warning naming the options that inflate `svg` when it is over
+/// [`SIZE_BUDGET`], or `None` when it fits.
+pub fn size_warning(svg: &str, opts: &SvgOptions<'_>) -> Option<String> {
+    if svg.len() <= SIZE_BUDGET {
+        return None;
+    }
+    let mut hints = Vec::new();
+    if opts.multi_color {
+        hints.push("drop --svg-multi-color");
+    }
+    if opts.account.is_some_and(|a| a.avatar_png.is_some()) {
+        hints.push("drop --svg-avatar");
+    }
+    if opts.animate {
+        hints.push("use the static card");
+    }
+    if matches!(opts.theme, Theme::Auto { .. }) && opts.mode == ColorMode::Auto {
+        hints.push("pin a light or dark theme");
+    }
+    let mut msg = format!(
+        "SVG is {} KB, over the {} KB GitHub serves reliably",
+        svg.len().div_ceil(1024),
+        SIZE_BUDGET / 1024
+    );
+    if !hints.is_empty() {
+        msg.push_str(&format!("; try: {}", hints.join(", ")));
+    }
+    Some(msg)
+}
+
 // ---------------------------------------------------------------------------
 // Internal helpers
 // ---------------------------------------------------------------------------
@@ -316,25 +807,241 @@ fn title_text(owner: &str, hosts: &[(String, String)]) -> String {
     }
 }
 
-fn stats_line(stats: &Stats, h: &Heatmap) -> String {
+/// Left-aligned per-host counts below the stats line.
+fn breakdown_element(text: &str) -> String {
+    format!(
+        r#"<text x="{GRID_LEFT}" y="{WATERMARK_Y}" font-family="ui-monospace,SFMono-Regular,Menlo,monospace" font-size="9" fill="var(--muted)">{text}</text>
+"#
+    )
+}
+
+/// Multi-colour key, right-aligned on the peak line: a swatch in each
+/// family's top level and its name (cut to [`LEGEND_NAME_MAX`]), most-coloured
+/// weeks first, capped at [`LEGEND_MAX`].  Families are dropped from the end
+/// until the key fits in `room` characters, so it never runs into the peak
+/// text.  Empty when there are no families or not even one fits.
+fn legend_element(h: &Heatmap, families: &[String], room: usize) -> String {
+    if families.is_empty() {
+        return String::new();
+    }
+    let mut weeks = vec![0usize; families.len()];
+    for bucket in &h.weeks {
+        if let Some(fi) = bucket
+            .dominant_family()
+            .and_then(|fam| families.iter().position(|f| f == fam))
+        {
+            weeks[fi] += 1;
+        }
+    }
+    let mut order: Vec<usize> = (0..families.len()).collect();
+    // Stable, so ties keep the families' alphabetical order.
+    order.sort_by_key(|&fi| std::cmp::Reverse(weeks[fi]));
+
+    // The first `shown` families as markup, with the key's width in
+    // characters.
+    let items = |shown: usize| {
+        let mut width = 0;
+        let mut items: Vec<String> = order
+            .iter()
+            .take(shown)
+            .map(|&fi| {
+                let name = truncate(&families[fi], LEGEND_NAME_MAX);
+                width += 2 + name.chars().count();
+                format!(
+                    r#"<tspan fill="var(--f{fi}-l4)">■</tspan> {}"#,
+                    xml_escape(&name)
+                )
+            })
+            .collect();
+        if families.len() > shown {
+            let more = format!("+{} more", families.len() - shown);
+            width += more.len();
+            items.push(more);
+        }
+        width += " · ".chars().count() * (items.len() - 1);
+        (width, items)
+    };
+    let mut shown = families.len().min(LEGEND_MAX);
+    let items = loop {
+        let (width, items) = items(shown);
+        if width <= room {
+            break items;
+        }
+        if shown == 1 {
+            return String::new();
+        }
+        shown -= 1;
+    };
+    format!(
+        r#"<text class="legend" x="{x}" y="{PEAK_Y}" text-anchor="end" font-family="ui-monospace,SFMono-Regular,Menlo,monospace" font-size="10" fill="var(--muted)">{}</text>
+"#,
+        items.join(" · "),
+        x = CARD_W - GRID_LEFT,
+    )
+}
+
+/// Right-aligned version text below the stats line.
+fn watermark_element() -> String {
+    format!(
+        r#"<text x="{x}" y="{WATERMARK_Y}" text-anchor="end" font-family="ui-monospace,SFMono-Regular,Menlo,monospace" font-size="9" fill="var(--muted)" fill-opacity="0.7">gerritoscope v{}</text>
+"#,
+        env!("CARGO_PKG_VERSION"),
+        x = CARD_W - GRID_LEFT,
+    )
+}
+
+/// Circular `<image>` for the avatar, embedded as a base64 data URI so the
+/// card stays self-contained (GitHub's image proxy blocks external refs).
+fn avatar_element(image: &[u8]) -> String {
+    let r = AVATAR / 2;
+    let (cx, cy) = (GRID_LEFT + r, AVATAR_Y + r);
+    format!(
+        r#"<clipPath id="avatar-clip"><circle cx="{cx}" cy="{cy}" r="{r}"/></clipPath>
+<image x="{GRID_LEFT}" y="{AVATAR_Y}" width="{AVATAR}" height="{AVATAR}" clip-path="url(#avatar-clip)" href="data:{};base64,{}"/>
+"#,
+        image_mime(image),
+        base64(image),
+    )
+}
+
+/// MIME type from the image's magic bytes.  Avatar endpoints usually serve
+/// PNG, which is also the fallback.
+fn image_mime(image: &[u8]) -> &'static str {
+    if image.starts_with(&[0xff, 0xd8, 0xff]) {
+        "image/jpeg"
+    } else if image.starts_with(b"GIF8") {
+        "image/gif"
+    } else if image.len() >= 12 && &image[..4] == b"RIFF" && &image[8..12] == b"WEBP" {
+        "image/webp"
+    } else {
+        "image/png"
+    }
+}
+
+/// Standard padded base64 (RFC 4648).
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Escape text for an SVG text node or a double-quoted attribute.
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn stats_line(stats: &Stats, locale: &Locale) -> String {
     use crate::render::fmt_count;
+    let reviewed = if stats.reviews_included {
+        format!(
+            "{} reviewed · ",
+            fmt_count(stats.recent_reviews_90d as i64, locale)
+        )
+    } else {
+        String::new()
+    };
     format!(
-        "{} merged · {}/90d · {} reviewed · <tspan fill=\"#3fb950\">+{}</tspan>/<tspan fill=\"#f85149\">−{}</tspan> · {}wk streak",
-        fmt_count(stats.total_merged as i64),
-        fmt_count(stats.recent_merged_90d as i64),
-        fmt_count(stats.recent_reviews_90d as i64),
-        fmt_count(stats.total_insertions),
-        fmt_count(stats.total_deletions),
-        h.current_streak(),
+        "{} merged · {}/{}d · {reviewed}<tspan fill=\"#3fb950\">+{}</tspan>/<tspan fill=\"#f85149\">−{}</tspan> · {}wk streak",
+        fmt_count(stats.total_merged as i64, locale),
+        fmt_count(stats.recent_merged_90d as i64, locale),
+        stats.recent_days,
+        fmt_count(stats.total_insertions, locale),
+        fmt_count(stats.total_deletions, locale),
+        stats.current_streak(),
+    )
+}
+
+/// The `<desc>` sentence for screen readers: the card's numbers in words.
+fn desc_text(stats: &Stats, locale: &Locale) -> String {
+    use crate::render::fmt_count;
+    format!(
+        "{} merged CLs, {} in the last {} days, {}-week streak, peak {}/week",
+        fmt_count(stats.total_merged as i64, locale),
+        fmt_count(stats.recent_merged_90d as i64, locale),
+        stats.recent_days,
+        stats.current_streak(),
+        fmt_count(i64::from(stats.heatmap.max_count), locale),
+    )
+}
+
+/// The stats line as SVG text.
+fn stats_text(line: &str) -> String {
+    format!(
+        r#"<text x="{GRID_LEFT}" y="{STATS_Y}" font-family="ui-monospace,SFMono-Regular,Menlo,monospace" font-size="11" fill="var(--text)">{line}</text>"#
+    )
+}
+
+/// `--svg-foreign-object`: `html` for clients that render HTML in SVG,
+/// else the plain [`stats_text`] line.
+fn stats_switch(line: &str, html: &str) -> String {
+    let (top, width) = (STATS_Y - 12, CARD_W - 2 * GRID_LEFT);
+    format!(
+        r#"<switch>
+<foreignObject x="{GRID_LEFT}" y="{top}" width="{width}" height="16" requiredExtensions="http://www.w3.org/1999/xhtml"><div xmlns="http://www.w3.org/1999/xhtml" style="font:11px ui-monospace,SFMono-Regular,Menlo,monospace;color:var(--text);white-space:nowrap">{html}</div></foreignObject>
+{}
+</switch>"#,
+        stats_text(line)
+    )
+}
+
+/// [`stats_line`] as XHTML, with the numbers in bold.
+fn stats_html(stats: &Stats, locale: &Locale) -> String {
+    use crate::render::fmt_count;
+    let reviewed = if stats.reviews_included {
+        format!(
+            "<b>{}</b> reviewed · ",
+            fmt_count(stats.recent_reviews_90d as i64, locale)
+        )
+    } else {
+        String::new()
+    };
+    format!(
+        "<b>{}</b> merged · <b>{}</b>/{}d · {reviewed}<span style=\"color:#3fb950\">+{}</span>/<span style=\"color:#f85149\">−{}</span> · <b>{}</b>wk streak",
+        fmt_count(stats.total_merged as i64, locale),
+        fmt_count(stats.recent_merged_90d as i64, locale),
+        stats.recent_days,
+        fmt_count(stats.total_insertions, locale),
+        fmt_count(stats.total_deletions, locale),
+        stats.current_streak(),
     )
 }
 
 /// Build the `<style>` block for the given theme and families.
-fn css_block(theme: &Theme, families: &[String], multi_color: bool) -> String {
+fn css_block(
+    theme: &Theme<'_>,
+    mode: ColorMode,
+    families: &[String],
+    multi_color: bool,
+    highlight_peak: bool,
+    stack: SvgStack,
+    animate: bool,
+) -> String {
     let mut css = String::new();
 
-    match theme {
-        Theme::Auto { light, dark } => {
+    match (theme, mode) {
+        (Theme::Auto { light, .. }, ColorMode::Light) => {
+            css.push_str(&palette_vars(light));
+        }
+        (Theme::Auto { dark, .. }, ColorMode::Dark) => {
+            css.push_str(&palette_vars(dark));
+        }
+        (Theme::Auto { light, dark }, ColorMode::Auto) => {
             css.push_str(&palette_vars(light));
             css.push_str("\n@media (prefers-color-scheme: dark) {\n  :root {\n");
             for line in palette_vars_inner(dark) {
@@ -344,7 +1051,7 @@ fn css_block(theme: &Theme, families: &[String], multi_color: bool) -> String {
             }
             css.push_str("  }\n}\n");
         }
-        Theme::Fixed(p) => {
+        (Theme::Fixed(p), _) => {
             css.push_str(&palette_vars(p));
         }
     }
@@ -356,15 +1063,24 @@ fn css_block(theme: &Theme, families: &[String], multi_color: bool) -> String {
         // Variables are set in :root with !important override not needed;
         // each family gets its own set of --fN-lM vars in :root.
         // We emit the family variable block separately.
-        for (fi, _family) in families.iter().enumerate() {
-            let palette_idx = fi % FAMILY_PALETTES.len();
-            let (light_lvls, dark_lvls) = &FAMILY_PALETTES[palette_idx];
-            // Light (default) — variables must live inside :root {}.
+        for (fi, family) in families.iter().enumerate() {
+            let (light_lvls, dark_lvls) = &FAMILY_PALETTES[family_palette_index(family)];
+            // Light (default) — variables must live inside :root {}.  A
+            // pinned dark mode uses the dark levels here and skips the
+            // media query.
+            let base = if mode == ColorMode::Dark {
+                dark_lvls
+            } else {
+                light_lvls
+            };
             css.push_str(":root {\n");
-            for (li, color) in light_lvls.iter().enumerate() {
+            for (li, color) in base.iter().enumerate() {
                 css.push_str(&format!("  --f{fi}-l{}:{};\n", li + 1, color));
             }
             css.push_str("}\n");
+            if mode != ColorMode::Auto {
+                continue;
+            }
             // Dark override.
             css.push_str("@media (prefers-color-scheme: dark) {\n  :root {\n");
             for (li, color) in dark_lvls.iter().enumerate() {
@@ -386,10 +1102,34 @@ fn css_block(theme: &Theme, families: &[String], multi_color: bool) -> String {
         css.push_str(".l3{fill:var(--l3)} .l4{fill:var(--l4)}\n");
     }
 
+    if highlight_peak {
+        // Every cell carries its level class in both colour modes.  A 1 px
+        // centred stroke spills 0.5 px into the 3 px gap, so the grid
+        // geometry is unchanged.
+        css.push_str(
+            "rect.week.l4 { stroke: var(--title); stroke-width: 1; stroke-opacity: 0.6; }\n",
+        );
+    }
+
+    if stack == SvgStack::ClsReviews {
+        css.push_str("rect.week.rv { fill-opacity: 0.55; }\n");
+    }
+
+    if animate {
+        // Per-column delays are inline `animation-delay` styles on each cell.
+        css.push_str(
+            "@keyframes reveal { from { opacity: 0; transform: scale(0.4); } \
+             to { opacity: 1; transform: scale(1); } }\n\
+             rect.week { animation: reveal 0.4s ease-out both; \
+             transform-box: fill-box; transform-origin: center; }\n\
+             @media (prefers-reduced-motion: reduce) { rect.week { animation: none; } }\n",
+        );
+    }
+
     css
 }
 
-fn palette_vars(p: &Palette) -> String {
+fn palette_vars(p: &Palette<'_>) -> String {
     let mut s = String::from(":root {\n");
     for line in palette_vars_inner(p) {
         s.push_str("  ");
@@ -400,7 +1140,7 @@ fn palette_vars(p: &Palette) -> String {
     s
 }
 
-fn palette_vars_inner(p: &Palette) -> Vec<String> {
+fn palette_vars_inner(p: &Palette<'_>) -> Vec<String> {
     vec![
         format!("--bg:{};", p.bg),
         format!("--border:{};", p.border),
@@ -415,16 +1155,19 @@ fn palette_vars_inner(p: &Palette) -> Vec<String> {
     ]
 }
 
-fn month_label_positions(h: &Heatmap) -> Vec<(u32, String)> {
+fn month_label_positions(h: &Heatmap, locale: &Locale) -> Vec<(u32, String)> {
     let mut positions = Vec::new();
+    let with_years = super::spans_years(h);
     let mut last_month = 0u32;
     let mut last_col = 0usize;
 
     for (i, b) in h.weeks.iter().enumerate() {
         let m = b.week_start.month();
         if m != last_month {
+            // 4 columns is 52 px, room for even a 6-char "Jan'25".
             if i == 0 || i >= last_col + 4 {
-                positions.push((i as u32, super::month_abbr(m).to_owned()));
+                let label = super::month_label(b.week_start, with_years, locale);
+                positions.push((i as u32, label));
                 last_col = i;
             }
             last_month = m;
@@ -435,11 +1178,11 @@ fn month_label_positions(h: &Heatmap) -> Vec<(u32, String)> {
 }
 
 /// Build the month-label `<text>` elements row.
-fn month_label_elements(h: &Heatmap) -> String {
-    let positions = month_label_positions(h);
+fn month_label_elements(h: &Heatmap, locale: &Locale) -> String {
+    let positions = month_label_positions(h, locale);
     let mut out = String::new();
     for (col, abbr) in positions {
-        let x = GRID_LEFT + col as u32 * CELL;
+        let x = GRID_LEFT + col * CELL;
         out.push_str(&format!(
             r#"<text x="{x}" y="{MONTH_Y}" font-family="ui-monospace,SFMono-Regular,Menlo,monospace" font-size="11" fill="var(--muted)">{abbr}</text>"#
         ));
@@ -448,14 +1191,27 @@ fn month_label_elements(h: &Heatmap) -> String {
     out
 }
 
-/// Build the heatmap `<rect>` elements.
-fn rect_elements(h: &Heatmap, families: &[String], multi_color: bool) -> String {
+/// Build the heatmap `<rect>` elements, one per week from the grid's
+/// top-left corner ([`BARE_PAD`] in from the edges of a bare card).
+fn rect_elements(h: &Heatmap, levels: &[u8], families: &[String], opts: &SvgOptions<'_>) -> String {
+    let (multi_color, stack) = (opts.multi_color, opts.stack);
+    let (left, top) = match opts.layout {
+        SvgLayout::Full => (GRID_LEFT, GRID_TOP),
+        SvgLayout::HeatmapOnly => (BARE_PAD, BARE_PAD),
+    };
     let mut out = String::new();
 
-    for (i, bucket) in h.weeks.iter().enumerate() {
-        let x = GRID_LEFT + i as u32 * CELL;
-        let y = GRID_TOP;
-        let level = bucket.level();
+    for (i, (bucket, &level)) in h.weeks.iter().zip(levels).enumerate() {
+        let x = left + i as u32 * CELL;
+        let y = top;
+        let delay = if opts.animate {
+            format!(
+                r#" style="animation-delay:{}ms""#,
+                i as u32 * ANIMATE_STEP_MS
+            )
+        } else {
+            String::new()
+        };
 
         // Determine CSS class string.
         let class = if multi_color && level > 0 {
@@ -498,8 +1254,26 @@ fn rect_elements(h: &Heatmap, families: &[String], multi_color: bool) -> String
             format!("{} – week of {date_str}", parts.join(", "))
         };
 
+        if stack == SvgStack::ClsReviews && bucket.count > 0 {
+            let (cl_h, review_h) = stack_heights(merged, reviews);
+            out.push_str(&format!("  <g><title>{tooltip}</title>"));
+            if review_h > 0 {
+                out.push_str(&format!(
+                    r#"<rect x="{x}" y="{y}" width="{SQUARE}" height="{review_h}" rx="1" class="{class} rv"{delay}/>"#
+                ));
+            }
+            if cl_h > 0 {
+                out.push_str(&format!(
+                    r#"<rect x="{x}" y="{}" width="{SQUARE}" height="{cl_h}" rx="1" class="{class}"{delay}/>"#,
+                    y + review_h,
+                ));
+            }
+            out.push_str("</g>\n");
+            continue;
+        }
+
         out.push_str(&format!(
-            r#"  <rect x="{x}" y="{y}" width="{SQUARE}" height="{SQUARE}" rx="2" class="{class}"><title>{tooltip}</title></rect>"#
+            r#"  <rect x="{x}" y="{y}" width="{SQUARE}" height="{SQUARE}" rx="2" class="{class}"{delay}><title>{tooltip}</title></rect>"#
         ));
         out.push('\n');
     }
@@ -507,6 +1281,21 @@ fn rect_elements(h: &Heatmap, families: &[String], multi_color: bool) -> String
     out
 }
 
+/// Heights of the CL (bottom) and review (top) segments of a stacked cell,
+/// in proportion to their counts and summing to [`SQUARE`].  Each non-zero
+/// count gets at least 1 px.
+fn stack_heights(merged: u32, reviews: u32) -> (u32, u32) {
+    let total = merged + reviews;
+    if total == 0 {
+        return (0, 0);
+    }
+    let mut cl_h = (SQUARE * merged + total / 2) / total;
+    if merged > 0 && reviews > 0 {
+        cl_h = cl_h.clamp(1, SQUARE - 1);
+    }
+    (cl_h, SQUARE - cl_h)
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -516,14 +1305,41 @@ mod tests {
     use super::*;
     use crate::gerrit::{ChangeInfo, ChangeStatus};
     use crate::stats;
-    use chrono::{NaiveDate, TimeZone, Utc};
+    use crate::stats::HEATMAP_WEEKS;
+    use chrono::{TimeZone, Utc};
+
+    /// The instant these tests compute stats at.
+    fn clock() -> chrono::DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 12, 12, 0, 0).unwrap()
+    }
 
     fn empty_stats() -> Stats {
-        stats::compute(
-            &[],
-            &[],
-            Utc.with_ymd_and_hms(2024, 6, 12, 12, 0, 0).unwrap(),
-        )
+        stats::compute(&[], &[], clock())
+    }
+
+    /// A one-line merged CL in `project`, submitted `weeks_ago` weeks before
+    /// [`clock`]; build variants with `..`.
+    fn cl(project: &str, weeks_ago: i64) -> ChangeInfo {
+        let ts = clock() - chrono::Duration::weeks(weeks_ago);
+        ChangeInfo {
+            id: String::new(),
+            number: 1,
+            subject: String::new(),
+            project: project.to_owned(),
+            branch: "main".to_owned(),
+            status: ChangeStatus::Merged,
+            created: ts,
+            updated: ts,
+            submitted: Some(ts),
+            insertions: 1,
+            deletions: 0,
+            work_in_progress: false,
+            more_changes: None,
+            revision_count: None,
+            messages: vec![],
+            owner: None,
+            labels: Default::default(),
+        }
     }
 
     fn hosts_one() -> Vec<(String, String)> {
@@ -562,6 +1378,96 @@ mod tests {
         );
     }
 
+    #[test]
+    fn heatmap_only_layout_is_just_the_grid() {
+        let stats = empty_stats();
+        let opts = SvgOptions {
+            layout: SvgLayout::HeatmapOnly,
+            ..opts_default()
+        };
+        let svg = render("test@example.com", &hosts_one(), &stats, &opts).unwrap();
+        assert_eq!(svg.matches("class=\"week").count(), 52);
+        assert!(!svg.contains("<line"), "{svg}");
+        assert!(!svg.contains("<tspan"), "{svg}");
+        assert!(!svg.contains("peak:"), "{svg}");
+        // 52 columns of 13 px less the trailing gap, plus 8 px either side.
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="689" height="26""#),
+            "{svg}"
+        );
+        assert!(svg.contains(r#"<rect x="8" y="8" width="10""#), "{svg}");
+
+        let full = render("test@example.com", &hosts_one(), &stats, &opts_default()).unwrap();
+        assert!(full.contains("<line") && full.contains("<tspan"));
+    }
+
+    #[test]
+    fn owner_is_escaped_in_the_label_and_title() {
+        // A bare --raw-query stands in for the owner, quotes and all.
+        let query = r#"owner:me (project:a OR project:"b c") -age:<1y"#;
+        let escaped = "owner:me (project:a OR project:&quot;b c&quot;) -age:&lt;1y";
+        let stats = empty_stats();
+        for layout in [SvgLayout::Full, SvgLayout::HeatmapOnly] {
+            let opts = SvgOptions {
+                layout,
+                ..opts_default()
+            };
+            let svg = render(query, &hosts_one(), &stats, &opts).unwrap();
+            assert!(
+                svg.contains(&format!(
+                    r#"aria-label="gerritoscope heatmap for {escaped}">"#
+                )),
+                "{svg}"
+            );
+            assert!(
+                svg.contains(&format!("<title>gerritoscope · {escaped}</title>")),
+                "{svg}"
+            );
+            assert!(!svg.contains(query), "{svg}");
+        }
+    }
+
+    #[test]
+    fn desc_summarises_the_stats_after_the_title() {
+        let mut stats = empty_stats();
+        stats.total_merged = 1234;
+        let svg = render("test@example.com", &hosts_one(), &stats, &opts_default()).unwrap();
+        assert!(
+            svg.contains(
+                "</title>\n<desc>1,234 merged CLs, 0 in the last 90 days, 0-week streak, peak 0/week</desc>"
+            ),
+            "{svg}"
+        );
+    }
+
+    #[test]
+    fn foreign_object_wraps_the_stats_line_with_a_text_fallback() {
+        let mut stats = empty_stats();
+        stats.total_merged = 1234;
+        let plain = render("test@example.com", &hosts_one(), &stats, &opts_default()).unwrap();
+        assert!(!plain.contains("<foreignObject"), "{plain}");
+        assert!(!plain.contains("<switch>"), "{plain}");
+        assert!(plain.contains(r#"<text x="16" y="106" "#), "{plain}");
+
+        let opts = SvgOptions {
+            foreign_object: true,
+            ..opts_default()
+        };
+        let svg = render("test@example.com", &hosts_one(), &stats, &opts).unwrap();
+        let switch = &svg[svg.find("<switch>").unwrap()..svg.find("</switch>").unwrap()];
+        assert!(
+            switch.contains(r#"requiredExtensions="http://www.w3.org/1999/xhtml""#),
+            "{switch}"
+        );
+        assert!(switch.contains("<b>1,234</b> merged"), "{switch}");
+        // The SVG fallback follows the HTML branch.
+        assert!(
+            switch.find("</foreignObject>").unwrap()
+                < switch.find(r#"<text x="16" y="106""#).unwrap(),
+            "{switch}"
+        );
+    }
+
     #[test]
     fn css_contains_bg_variable() {
         let stats = empty_stats();
@@ -583,8 +1489,19 @@ mod tests {
     fn fixed_theme_has_no_media_query() {
         let stats = empty_stats();
         let opts = SvgOptions {
-            theme: "github-dark",
+            theme: theme_by_name("github-dark").unwrap(),
             multi_color: false,
+            highlight_peak: false,
+            smooth: false,
+            mode: ColorMode::Auto,
+            locale: Locale::EN,
+            account: None,
+            watermark: false,
+            stack: SvgStack::None,
+            layout: SvgLayout::Full,
+            animate: false,
+            foreign_object: false,
+            anonymize: false,
         };
         let svg = render("test@example.com", &hosts_one(), &stats, &opts).unwrap();
         assert!(
@@ -593,6 +1510,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn pinned_modes_pick_one_auto_palette() {
+        let stats = empty_stats();
+        let Theme::Auto { light, dark } = theme_by_name("github").unwrap() else {
+            panic!("github should be an auto theme");
+        };
+        let opts = |mode| SvgOptions {
+            mode,
+            ..opts_default()
+        };
+
+        let svg = render("t@x.com", &hosts_one(), &stats, &opts(ColorMode::Light)).unwrap();
+        assert!(!svg.contains("prefers-color-scheme"));
+        assert!(svg.contains(&format!("--l4:{};", light.levels[4])));
+        assert!(!svg.contains(&format!("--l4:{};", dark.levels[4])));
+
+        let svg = render("t@x.com", &hosts_one(), &stats, &opts(ColorMode::Dark)).unwrap();
+        assert!(!svg.contains("prefers-color-scheme"));
+        assert!(svg.contains(&format!("--bg:{};", dark.bg)));
+        assert!(!svg.contains(&format!("--bg:{};", light.bg)));
+    }
+
+    #[test]
+    fn highlight_peak_adds_l4_stroke_only_when_enabled() {
+        let stats = empty_stats();
+        let plain = render("test@example.com", &hosts_one(), &stats, &opts_default()).unwrap();
+        assert!(plain.contains("rect.week { stroke: none; }"));
+        assert!(!plain.contains("rect.week.l4"));
+
+        for multi_color in [false, true] {
+            let opts = SvgOptions {
+                theme: theme_by_name("github").unwrap(),
+                multi_color,
+                highlight_peak: true,
+                smooth: false,
+                mode: ColorMode::Auto,
+                locale: Locale::EN,
+                account: None,
+                watermark: false,
+                stack: SvgStack::None,
+                layout: SvgLayout::Full,
+                animate: false,
+                foreign_object: false,
+                anonymize: false,
+            };
+            let svg = render("test@example.com", &hosts_one(), &stats, &opts).unwrap();
+            assert!(svg.contains("rect.week { stroke: none; }"));
+            assert!(
+                svg.contains("rect.week.l4 { stroke: var(--title);"),
+                "multi_color={multi_color}"
+            );
+        }
+    }
+
     // -----------------------------------------------------------------------
     // Multi-colour mode
     // -----------------------------------------------------------------------
@@ -600,33 +1571,118 @@ mod tests {
     #[test]
     fn multi_color_produces_family_class() {
         // Build stats with two families.
-        let now = Utc.with_ymd_and_hms(2024, 6, 12, 12, 0, 0).unwrap();
-        fn cl(project: &str, date: &str) -> ChangeInfo {
-            let d = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
-            let ts = d.and_hms_opt(12, 0, 0).unwrap().and_utc();
-            ChangeInfo {
-                project: project.to_owned(),
-                status: ChangeStatus::Merged,
-                updated: ts,
-                submitted: Some(ts),
-                insertions: 1,
-                deletions: 0,
-                more_changes: None,
-                messages: vec![],
-            }
-        }
-        let changes = vec![cl("alpha", "2024-06-10"), cl("beta", "2024-06-03")];
-        let s = stats::compute(&changes, &[], now);
+        let changes = vec![cl("alpha", 0), cl("beta", 1)];
+        let s = stats::compute(&changes, &[], clock());
         let opts = SvgOptions {
-            theme: "github",
+            theme: theme_by_name("github").unwrap(),
             multi_color: true,
+            highlight_peak: false,
+            smooth: false,
+            mode: ColorMode::Auto,
+            locale: Locale::EN,
+            account: None,
+            watermark: false,
+            stack: SvgStack::None,
+            layout: SvgLayout::Full,
+            animate: false,
+            foreign_object: false,
+            anonymize: false,
         };
         let svg = render("test@example.com", &hosts_one(), &s, &opts).unwrap();
+        assert!(
+            svg.contains(
+                r#"<tspan fill="var(--f0-l4)">■</tspan> alpha · <tspan fill="var(--f1-l4)">■</tspan> beta</text>"#
+            ),
+            "{svg}"
+        );
         // At least one rect should have a family class like "f0" or "f1".
         assert!(
             svg.contains("class=\"week f0") || svg.contains("class=\"week f1"),
             "multi-color mode should produce family class attributes"
         );
+
+        // Pinned dark mode uses the dark family levels without a media query.
+        let dark = SvgOptions {
+            mode: ColorMode::Dark,
+            ..opts
+        };
+        let svg = render("test@example.com", &hosts_one(), &s, &dark).unwrap();
+        assert!(!svg.contains("prefers-color-scheme"));
+        let alpha = &FAMILY_PALETTES[family_palette_index("alpha")];
+        assert!(svg.contains(&format!("--f0-l4:{};", alpha.1[3])));
+    }
+
+    #[test]
+    fn legend_caps_families_and_skips_single_colour() {
+        // p0 leads two weeks; p1..p7 lead one each.
+        let mut changes: Vec<_> = (0..8).map(|i| cl(&format!("p{i}"), i)).collect();
+        changes.push(cl("p0", 10));
+        let s = stats::compute(&changes, &[], clock());
+        let opts = SvgOptions {
+            multi_color: true,
+            ..opts_default()
+        };
+        let svg = render("test@example.com", &hosts_one(), &s, &opts).unwrap();
+        let legend = svg
+            .lines()
+            .find(|l| l.starts_with(r#"<text class="legend""#))
+            .unwrap();
+        assert_eq!(legend.matches('■').count(), LEGEND_MAX, "{legend}");
+        assert!(legend.contains("</tspan> p0 · "), "{legend}");
+        assert!(legend.ends_with(" · +2 more</text>"), "{legend}");
+
+        let plain = render("test@example.com", &hosts_one(), &s, &opts_default()).unwrap();
+        assert!(!plain.contains("class=\"legend\""));
+    }
+
+    #[test]
+    fn legend_cuts_long_names_and_keeps_clear_of_the_peak_text() {
+        let changes: Vec<_> = (0..8)
+            .map(|i| cl(&format!("a-very-long-family-name-{i}"), i))
+            .collect();
+        let s = stats::compute(&changes, &[], clock());
+        let opts = SvgOptions {
+            multi_color: true,
+            ..opts_default()
+        };
+        let svg = render("test@example.com", &hosts_one(), &s, &opts).unwrap();
+        let legend = svg
+            .lines()
+            .find(|l| l.starts_with(r#"<text class="legend""#))
+            .unwrap();
+        assert!(legend.contains("</tspan> a-very-long-fam… · "), "{legend}");
+        // Six 16-character names would reach the peak text; four fit.
+        assert_eq!(legend.matches('■').count(), 4, "{legend}");
+        assert!(legend.ends_with(" · +4 more</text>"), "{legend}");
+    }
+
+    #[test]
+    fn family_palette_is_stable_across_family_sets() {
+        let opts = SvgOptions {
+            multi_color: true,
+            mode: ColorMode::Light,
+            ..opts_default()
+        };
+        let expected = FAMILY_PALETTES[family_palette_index("chromium")].0[3];
+        // "chromium" alone, and alongside families sorting before it.
+        for projects in [
+            vec!["chromium"],
+            vec!["angle", "chromium"],
+            vec!["aosp", "build", "chromium"],
+        ] {
+            let changes: Vec<_> = projects
+                .iter()
+                .enumerate()
+                .map(|(i, p)| cl(p, i as i64))
+                .collect();
+            let s = stats::compute(&changes, &[], clock());
+            let svg = render("test@example.com", &hosts_one(), &s, &opts).unwrap();
+            let fi = projects.len() - 1;
+            assert!(
+                svg.contains(&format!("--f{fi}-l4:{expected};")),
+                "{projects:?}"
+            );
+        }
     }
 
     // -----------------------------------------------------------------------
@@ -656,6 +1712,344 @@ mod tests {
         }
     }
 
+    #[test]
+    fn random_theme_is_a_builtin_fixed_per_day() {
+        let day = NaiveDate::from_ymd_opt(2024, 6, 12).unwrap();
+        let name = random_theme_name(day);
+        assert!(theme_names().contains(&name), "{name}");
+        assert!(std::ptr::eq(
+            theme_on("random", day).unwrap(),
+            theme_by_name(name).unwrap()
+        ));
+        assert_eq!(random_theme_name(day), name);
+        assert!(theme_by_name("random").is_ok());
+
+        // A month of days visits more than one theme.
+        let mut seen: Vec<&str> = (0..30)
+            .map(|i| random_theme_name(day + chrono::Duration::days(i)))
+            .collect();
+        seen.sort();
+        seen.dedup();
+        assert!(seen.len() > 1, "{seen:?}");
+    }
+
+    #[test]
+    fn theme_previews_one_valid_svg_per_theme() {
+        let dir =
+            std::env::temp_dir().join(format!("gerritoscope-previews-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let today = NaiveDate::from_ymd_opt(2024, 6, 12).unwrap();
+
+        let written = write_theme_previews(&dir, today).unwrap();
+        assert_eq!(written.len(), THEME_NAMES.len());
+        for name in THEME_NAMES {
+            let svg = std::fs::read_to_string(dir.join(format!("{name}.svg"))).unwrap();
+            assert!(
+                svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""),
+                "{name}"
+            );
+            assert!(svg.ends_with("</svg>"), "{name}");
+            assert_eq!(svg.matches("class=\"week").count(), HEATMAP_WEEKS, "{name}");
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // -----------------------------------------------------------------------
+    // Theme files
+    // -----------------------------------------------------------------------
+
+    const BRAND_THEME: &str = r##"{
+        "bg": "#fafafa", "border": "#dddddd", "title": "#111111",
+        "text": "#333333", "muted": "#777777",
+        "levels": ["#eeeeee", "#ffd6e0", "#ff8fab", "#fb6f92", "#c9184a"]
+    }"##;
+
+    #[test]
+    fn theme_file_round_trips_into_svg() {
+        let theme = parse_theme(BRAND_THEME).unwrap();
+        assert!(matches!(theme, Theme::Fixed(_)));
+        let opts = SvgOptions {
+            theme: &theme,
+            multi_color: false,
+            highlight_peak: false,
+            smooth: false,
+            mode: ColorMode::Auto,
+            locale: Locale::EN,
+            account: None,
+            watermark: false,
+            stack: SvgStack::None,
+            layout: SvgLayout::Full,
+            animate: false,
+            foreign_object: false,
+            anonymize: false,
+        };
+        let svg = render("test@example.com", &hosts_one(), &empty_stats(), &opts).unwrap();
+        assert!(
+            svg.contains("--l4:#c9184a;"),
+            "custom --l4 missing from CSS"
+        );
+        assert!(!svg.contains("prefers-color-scheme"));
+    }
+
+    #[test]
+    fn theme_file_with_dark_is_auto() {
+        let text = BRAND_THEME.trim_end().trim_end_matches('}').to_owned()
+            + r##", "dark": {
+                "bg": "#000000", "border": "#222222", "title": "#eeeeee",
+                "text": "#cccccc", "muted": "#888888",
+                "levels": ["#111111", "#590d22", "#800f2f", "#a4133c", "#ff4d6d"]
+            }}"##;
+        let theme = parse_theme(&text).unwrap();
+        assert!(matches!(theme, Theme::Auto { .. }));
+    }
+
+    #[test]
+    fn theme_file_requires_five_levels() {
+        let text = BRAND_THEME.replace(r##", "#c9184a""##, "");
+        assert!(parse_theme(&text).is_err());
+    }
+
+    #[test]
+    fn theme_file_rejects_non_hex_level() {
+        let text = BRAND_THEME.replace("#c9184a", "crimson");
+        let err = format!("{:#}", parse_theme(&text).err().unwrap());
+        assert!(
+            err.contains("levels[4]"),
+            "error should name the level: {err}"
+        );
+    }
+
+    #[test]
+    fn theme_file_rejects_bad_border() {
+        let text = BRAND_THEME.replace("#dddddd", "#12");
+        let err = format!("{:#}", parse_theme(&text).err().unwrap());
+        assert!(
+            err.contains("border: '#12' is not a valid #rgb/#rrggbb color"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn hex_color_valid() {
+        assert!(is_valid_hex_color("#fff"));
+        assert!(is_valid_hex_color("#C9184a"));
+        assert!(is_valid_hex_color("#000000"));
+    }
+
+    #[test]
+    fn hex_color_wrong_length() {
+        assert!(!is_valid_hex_color("#"));
+        assert!(!is_valid_hex_color("#12"));
+        assert!(!is_valid_hex_color("#1234"));
+        assert!(!is_valid_hex_color("#1234567"));
+    }
+
+    #[test]
+    fn hex_color_non_hex() {
+        assert!(!is_valid_hex_color("#gggggg"));
+        assert!(!is_valid_hex_color("ffffff"));
+        assert!(!is_valid_hex_color("crimson"));
+        assert!(!is_valid_hex_color("#ff ff0"));
+    }
+
+    #[test]
+    fn french_locale_month_labels() {
+        let opts = SvgOptions {
+            locale: Locale::FR,
+            ..opts_default()
+        };
+        let svg = render("test@example.com", &hosts_one(), &empty_stats(), &opts).unwrap();
+        assert!(svg.contains(">aoû</text>"), "{svg}");
+        assert!(svg.contains(">déc</text>"));
+        assert!(!svg.contains(">Aug</text>"));
+    }
+
+    #[test]
+    fn january_label_carries_the_year() {
+        // The window runs Jun 2023 – Jun 2024.
+        let labels: Vec<String> = month_label_positions(&empty_stats().heatmap, &Locale::EN)
+            .into_iter()
+            .map(|(_, label)| label)
+            .collect();
+        assert!(labels.contains(&"Jan'24".to_owned()), "{labels:?}");
+        assert!(labels.contains(&"Feb".to_owned()), "{labels:?}");
+        assert!(!labels.contains(&"Jan".to_owned()), "{labels:?}");
+    }
+
+    #[test]
+    fn stacked_cell_splits_mixed_week_by_share() {
+        let mut stats = empty_stats();
+        let last = stats.heatmap.weeks.last_mut().unwrap();
+        last.count = 4;
+        last.review_count = 1;
+        stats.heatmap.max_count = 4;
+
+        let opts = SvgOptions {
+            stack: SvgStack::ClsReviews,
+            ..opts_default()
+        };
+        let svg = render("me", &hosts_one(), &stats, &opts).unwrap();
+        let heights: Vec<u32> = svg
+            .lines()
+            .filter(|l| l.starts_with("  <g><title>3 CLs, 1 review"))
+            .flat_map(|l| l.split(r#"height=""#).skip(1))
+            .map(|rest| rest.split('"').next().unwrap().parse().unwrap())
+            .collect();
+        // Review on top (2 px for 1 of 4), CLs below (8 px for 3 of 4).
+        assert_eq!(heights, [2, 8], "{svg}");
+        assert_eq!(heights.iter().sum::<u32>(), SQUARE);
+        assert!(svg.contains("rect.week.rv"));
+
+        // Empty weeks and the default mode keep one square per week.
+        assert!(svg.contains(r#"height="10" rx="2" class="week l0""#));
+        let plain = render("me", &hosts_one(), &stats, &opts_default()).unwrap();
+        assert!(!plain.contains(" rv\""));
+        assert_eq!(plain.matches("class=\"week").count(), HEATMAP_WEEKS);
+    }
+
+    #[test]
+    fn stack_heights_keep_both_segments_visible() {
+        assert_eq!(stack_heights(1, 0), (SQUARE, 0));
+        assert_eq!(stack_heights(0, 5), (0, SQUARE));
+        assert_eq!(stack_heights(99, 1), (SQUARE - 1, 1));
+        assert_eq!(stack_heights(1, 99), (1, SQUARE - 1));
+    }
+
+    #[test]
+    fn animation_adds_keyframes_without_changing_structure() {
+        let stats = empty_stats();
+        let still = render("me", &hosts_one(), &stats, &opts_default()).unwrap();
+        assert!(!still.contains("@keyframes"));
+        assert!(!still.contains("animation-delay"));
+
+        let opts = SvgOptions {
+            animate: true,
+            ..opts_default()
+        };
+        let animated = render("me", &hosts_one(), &stats, &opts).unwrap();
+        assert!(animated.contains("@keyframes reveal"));
+        assert!(animated.contains("animation: reveal"));
+        assert!(animated.contains("prefers-reduced-motion"));
+        assert_eq!(
+            animated.matches("<rect x=").count(),
+            still.matches("<rect x=").count()
+        );
+        assert_eq!(animated.matches("class=\"week").count(), HEATMAP_WEEKS);
+        // Staggered by column.
+        assert!(animated.contains(r#"style="animation-delay:0ms""#));
+        assert!(animated.contains(&format!(
+            r#"style="animation-delay:{}ms""#,
+            (HEATMAP_WEEKS as u32 - 1) * ANIMATE_STEP_MS
+        )));
+    }
+
+    #[test]
+    fn stats_line_leaves_out_reviews_when_excluded() {
+        let mut stats = empty_stats();
+        assert!(stats_line(&stats, &Locale::EN).contains(" reviewed · "));
+        stats.reviews_included = false;
+        let line = stats_line(&stats, &Locale::EN);
+        assert!(!line.contains("reviewed"), "{line}");
+        assert!(line.starts_with("0 merged · 0/90d · <tspan"), "{line}");
+    }
+
+    #[test]
+    fn host_breakdown_only_when_counted() {
+        let mut stats = empty_stats();
+        let svg = render("test@example.com", &hosts_one(), &stats, &opts_default()).unwrap();
+        assert!(!svg.contains("chromium: "));
+
+        stats.host_counts = vec![crate::stats::HostCount {
+            alias: "chromium".to_owned(),
+            cls: 812,
+            reviews: 5,
+        }];
+        let svg = render("test@example.com", &hosts_one(), &stats, &opts_default()).unwrap();
+        assert!(
+            svg.contains(">chromium: 812 CLs, 5 reviews</text>"),
+            "{svg}"
+        );
+    }
+
+    #[test]
+    fn watermark_only_when_enabled() {
+        let version = concat!("gerritoscope v", env!("CARGO_PKG_VERSION"));
+        let svg = render("me", &hosts_one(), &empty_stats(), &opts_default()).unwrap();
+        assert!(!svg.contains(version));
+
+        let opts = SvgOptions {
+            watermark: true,
+            ..opts_default()
+        };
+        let svg = render("me", &hosts_one(), &empty_stats(), &opts).unwrap();
+        assert!(svg.contains(&format!(">{version}</text>")), "{svg}");
+        // Right-aligned below the stats line, so the two can't overlap.
+        assert!(svg.contains(r#"x="724" y="130" text-anchor="end""#));
+    }
+
+    #[test]
+    fn avatar_embedded_only_when_provided() {
+        let stats = empty_stats();
+        let card = AccountCard {
+            name: "Ada <Lovelace> & co".to_owned(),
+            avatar_png: Some(b"\x89PNG\r\n\x1a\n".to_vec()),
+        };
+        let opts = SvgOptions {
+            account: Some(&card),
+            ..opts_default()
+        };
+        let svg = render("ada@example.com", &hosts_one(), &stats, &opts).unwrap();
+        assert!(svg.contains("<image "), "{svg}");
+        assert!(svg.contains(r#"href="data:image/png;base64,iVBORw0KGgo=""#));
+        assert!(svg.contains("gerritoscope · Ada &lt;Lovelace&gt; &amp; co"));
+
+        let no_avatar = AccountCard {
+            avatar_png: None,
+            ..card.clone()
+        };
+        let opts = SvgOptions {
+            account: Some(&no_avatar),
+            ..opts_default()
+        };
+        let svg = render("ada@example.com", &hosts_one(), &stats, &opts).unwrap();
+        assert!(!svg.contains("<image"));
+        assert!(svg.contains("Ada &lt;Lovelace&gt;"));
+
+        let svg = render("ada@example.com", &hosts_one(), &stats, &opts_default()).unwrap();
+        assert!(!svg.contains("<image"));
+        assert!(svg.contains("gerritoscope · ada@example.com"));
+    }
+
+    #[test]
+    fn anonymized_card_never_shows_the_account() {
+        let stats = empty_stats();
+        let card = AccountCard {
+            name: "Jane Realname".to_owned(),
+            avatar_png: Some(b"\x89PNG\r\n\x1a\n".to_vec()),
+        };
+        let opts = SvgOptions {
+            account: Some(&card),
+            anonymize: true,
+            ..opts_default()
+        };
+        let svg = render("j***@example.com", &hosts_one(), &stats, &opts).unwrap();
+        assert!(!svg.contains("Jane"), "{svg}");
+        assert!(!svg.contains("<image"), "{svg}");
+        assert!(svg.contains("gerritoscope · j***@example.com"), "{svg}");
+    }
+
+    #[test]
+    fn base64_matches_rfc4648_vectors() {
+        let encoded: Vec<String> = ["", "f", "fo", "foo", "foob", "fooba", "foobar"]
+            .iter()
+            .map(|s| base64(s.as_bytes()))
+            .collect();
+        assert_eq!(
+            encoded,
+            ["", "Zg==", "Zm8=", "Zm9v", "Zm9vYg==", "Zm9vYmE=", "Zm9vYmFy"]
+        );
+    }
+
     #[test]
     fn tooltip_in_rect_title() {
         let now = Utc.with_ymd_and_hms(2024, 6, 12, 12, 0, 0).unwrap();
@@ -666,4 +2060,80 @@ mod tests {
             "rects should contain <title> tooltip elements"
         );
     }
+
+    // -----------------------------------------------------------------------
+    // Theme checks
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn contrast_ratio_of_known_pairs() {
+        let black_on_white = contrast_ratio((0, 0, 0), (255, 255, 255));
+        assert!((black_on_white - 21.0).abs() < 1e-9, "{black_on_white}");
+        // Mid greys a shade apart: far too close to read.
+        let grey = contrast_ratio((0x77, 0x77, 0x77), (0x88, 0x88, 0x88));
+        assert!(grey < 1.5, "{grey}");
+        assert_eq!(grey, contrast_ratio((0x88, 0x88, 0x88), (0x77, 0x77, 0x77)));
+    }
+
+    #[test]
+    fn github_themes_pass_checks() {
+        // Not every built-in passes: Solarized's low-contrast text is part of
+        // its design.
+        for name in ["github", "github-light", "github-dark"] {
+            let warnings = theme_warnings(theme_by_name(name).unwrap());
+            assert!(warnings.is_empty(), "{name}: {warnings:?}");
+        }
+    }
+
+    #[test]
+    fn low_contrast_theme_warns() {
+        let theme = parse_theme(
+            r##"{"bg": "#888888", "border": "#000", "title": "#000", "text": "#777777",
+                "muted": "#000",
+                "levels": ["#ebedf0", "#ebedf1", "#40c463", "#30a14e", "#216e39"]}"##,
+        )
+        .unwrap();
+        let warnings = theme_warnings(&theme);
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+        assert!(
+            warnings[0].starts_with("text on bg has contrast 1.3:1"),
+            "{warnings:?}"
+        );
+        assert!(
+            warnings[1].starts_with("levels[1] '#ebedf1'"),
+            "{warnings:?}"
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Size budget
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn oversized_multi_color_card_warns() {
+        // A different project family in every week, plus a hefty avatar.
+        let changes: Vec<ChangeInfo> = (0..HEATMAP_WEEKS as i64)
+            .map(|w| ChangeInfo {
+                number: w as u64,
+                ..cl(&format!("family{w}/src"), w)
+            })
+            .collect();
+        let s = stats::compute(&changes, &[], clock());
+        let card = AccountCard {
+            name: "Ada".to_owned(),
+            avatar_png: Some(vec![0x89; 24 * 1024]),
+        };
+        let opts = SvgOptions {
+            multi_color: true,
+            account: Some(&card),
+            ..opts_default()
+        };
+        let svg = render("test@example.com", &hosts_one(), &s, &opts).unwrap();
+        let warning = size_warning(&svg, &opts).expect("should be over budget");
+        assert!(warning.contains("--svg-multi-color"), "{warning}");
+        assert!(warning.contains("--svg-avatar"), "{warning}");
+
+        let plain = render("test@example.com", &hosts_one(), &s, &opts_default()).unwrap();
+        assert_eq!(size_warning(&plain, &opts_default()), None);
+    }
 }
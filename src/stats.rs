@@ -1,8 +1,11 @@
 //! Aggregation and heatmap bucketing over a collection of Gerrit changes.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
 
-use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use anyhow::{bail, Result};
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc, Weekday};
+use serde::Serialize;
 
 use crate::gerrit::{ChangeInfo, ChangeStatus, ReviewEvent};
 
@@ -16,12 +19,76 @@ pub const HEATMAP_WEEKS: usize = 52;
 /// Maximum number of projects surfaced in the stats summary.
 pub const TOP_PROJECTS_COUNT: usize = 5;
 
+/// Maximum number of authors in [`Stats::reviewed_authors`].
+pub const TOP_AUTHORS_COUNT: usize = 5;
+
+/// Length of the "recent" window behind [`Stats::recent_merged_90d`] and
+/// friends unless [`ComputeOptions::recent_days`] says otherwise.
+pub const DEFAULT_RECENT_DAYS: u32 = 90;
+
+/// Upper bounds, in days, of the first three [`Stats::open_ages`] bins;
+/// the last bin takes everything older.
+pub const OPEN_AGE_LIMITS_DAYS: [i64; 3] = [7, 30, 90];
+
+/// Labels for the [`Stats::open_ages`] bins.
+pub const OPEN_AGE_LABELS: [&str; 4] = ["≤ 1 week", "≤ 1 month", "≤ 3 months", "older"];
+
+/// [`Stats::reviewed_authors`] bucket for changes whose owner the host
+/// didn't reveal.
+pub const UNKNOWN_AUTHOR: &str = "unknown";
+
+/// Window, in weeks, of the trailing average used by `--smooth`.
+pub const SMOOTH_WEEKS: usize = 4;
+
+/// Default review lookback when `--after` isn't given: the heatmap window
+/// plus a little slack so the oldest grid week is fully covered.
+pub const REVIEW_LOOKBACK_WEEKS: i64 = 54;
+
+/// Label whose votes feed [`Stats::received_score_histogram`].
+pub const REVIEW_LABEL: &str = "Code-Review";
+
+// ---------------------------------------------------------------------------
+// Week start
+// ---------------------------------------------------------------------------
+
+/// Day each heatmap week begins on, selected with `--week-start`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WeekStart {
+    /// ISO weeks.
+    #[default]
+    Monday,
+    /// GitHub's contribution graph, for lining the two up side by side.
+    Sunday,
+}
+
+impl WeekStart {
+    /// Days from the start of the week to `day`, 0 – 6.
+    fn days_into_week(self, day: Weekday) -> i64 {
+        match self {
+            WeekStart::Monday => day.num_days_from_monday() as i64,
+            WeekStart::Sunday => day.num_days_from_sunday() as i64,
+        }
+    }
+}
+
+impl FromStr for WeekStart {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "monday" => Ok(WeekStart::Monday),
+            "sunday" => Ok(WeekStart::Sunday),
+            other => bail!("unknown week start {other:?}; valid names: monday, sunday"),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Output types
 // ---------------------------------------------------------------------------
 
 /// All aggregated statistics derived from a slice of [`ChangeInfo`]s.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Stats {
     /// Weekly activity grid for the past [`HEATMAP_WEEKS`] weeks.
     pub heatmap: Heatmap,
@@ -31,18 +98,138 @@ pub struct Stats {
     pub total_insertions: i64,
     /// Sum of `deletions` across all merged CLs.
     pub total_deletions: i64,
-    /// Merged CLs submitted in the last 90 days.
+    /// Merged CLs submitted in the last [`Stats::recent_days`] days (90 by
+    /// default, hence the name).
     pub recent_merged_90d: usize,
-    /// Total reviews performed within the ~54-week fetch window.
+    /// Merged CLs submitted in the equally long window before that (90–180
+    /// days ago by default), to compare [`Stats::recent_merged_90d`] against.
+    pub prev_merged_90d: usize,
+    /// Total reviews performed within the review fetch window: the last
+    /// [`REVIEW_LOOKBACK_WEEKS`] weeks, or back to [`Stats::reviews_since`].
     pub total_reviews: usize,
-    /// Reviews performed in the last 90 days.
+    /// Reviews performed in the last [`Stats::recent_days`] days.
     pub recent_reviews_90d: usize,
+    /// Reviews performed in the window before that; see
+    /// [`Stats::prev_merged_90d`].
+    pub prev_reviews_90d: usize,
+    /// Sum of `insertions` across reviewed changes (as fetched).
+    pub reviewed_insertions: i64,
+    /// Sum of `deletions` across reviewed changes (as fetched).
+    pub reviewed_deletions: i64,
+    /// Number of distinct full project names among merged CLs — a breadth
+    /// measure that, like `top_projects`, doesn't roll up into families.
+    pub distinct_projects: usize,
     /// Up to [`TOP_PROJECTS_COUNT`] projects, sorted descending by merged CL count.
     pub top_projects: Vec<ProjectStat>,
+    /// How concentrated the work is: the biggest project's
+    /// [`ProjectStat::share`], `0.0` with no merged CLs.
+    pub concentration: f64,
+    /// [`REVIEW_LABEL`] score → number of merged CLs that received at least
+    /// one vote of that score from someone other than the owner.
+    ///
+    /// Zero votes, self-votes, and voters listed in
+    /// [`ComputeOptions::exclude_voters`] are ignored.  Empty unless the
+    /// changes were fetched with `DETAILED_LABELS`.
+    pub received_score_histogram: BTreeMap<i32, usize>,
+    /// Heatmap-window contributions (merged CLs + reviews) by day of week,
+    /// Monday first.
+    pub weekday_counts: [u32; 7],
+    /// Calendar-month rollup of the heatmap window, oldest first, from the
+    /// month of the first heatmap week through the current month.  Months
+    /// without activity are present with zeros.
+    pub monthly: Vec<MonthBucket>,
+    /// Submission date of the earliest merged CL (all history, not just the
+    /// heatmap window).  `None` when there are no merged CLs.
+    pub first_contribution: Option<NaiveDate>,
+    /// Submission date of the most recent merged CL.
+    pub last_contribution: Option<NaiveDate>,
+    /// Aliases of hosts that failed to fetch under `--continue-on-error`.
+    ///
+    /// [`compute`] leaves this empty; the caller fills it in so renderers can
+    /// flag a partial result.
+    pub failed_hosts: Vec<String>,
+    /// The review fetch stopped at `--max-reviews` on some host, so
+    /// [`Stats::total_reviews`] is a lower bound.
+    ///
+    /// [`compute`] leaves this unset; the caller sets it.
+    pub reviews_capped: bool,
+    /// First day reviews were fetched from, when an `--after` earlier than
+    /// the default [`REVIEW_LOOKBACK_WEEKS`] window extended the fetch;
+    /// `None` for the default window.
+    ///
+    /// [`compute`] leaves this unset; the caller sets it.
+    pub reviews_since: Option<NaiveDate>,
+    /// Reviews were fetched.  `false` under `--owner-activity-only`, where
+    /// renderers leave out review rows instead of showing zeros.
+    ///
+    /// [`compute`] sets this; the caller clears it.
+    pub reviews_included: bool,
+    /// Merged CLs and review events fetched from each host, in host order,
+    /// for `--show-host-breakdown`.  Hosts that failed are left out.
+    ///
+    /// [`compute`] leaves this empty; the caller fills it in.
+    pub host_counts: Vec<HostCount>,
+    /// [`ComputeOptions::streak_grace`], kept so renderers report streaks
+    /// through [`Stats::current_streak`] / [`Stats::longest_streak`].
+    pub streak_grace: u32,
+    /// Length of the recent window in days ([`ComputeOptions::recent_days`]),
+    /// kept so renderers can label it ("last 30d").
+    pub recent_days: u32,
+    /// Mean patch sets per merged CL, over the CLs that carry a
+    /// [`ChangeInfo::revision_count`].  `None` unless the changes were
+    /// fetched with `ALL_REVISIONS` (`--with-revisions`).
+    pub avg_revisions_per_cl: Option<f64>,
+    /// Abandoned CLs across all provided history.  Zero unless the changes
+    /// were fetched with `is:closed` (see [`ChangeStatus::Closed`]).
+    pub total_abandoned: usize,
+    /// Open CLs by age (`now` minus `created`), binned by
+    /// [`OPEN_AGE_LIMITS_DAYS`].  All zero unless open changes were fetched
+    /// (`--include-open`).
+    pub open_ages: [usize; 4],
+    /// The [`ComputeOptions::notable`] merged CLs of the recent window with
+    /// the most lines changed, largest first.
+    pub notable_changes: Vec<ChangeRef>,
+    /// Up to [`TOP_AUTHORS_COUNT`] owners of reviewed changes with how many
+    /// of their changes were reviewed, most first.  Owners the host hides
+    /// are counted as [`UNKNOWN_AUTHOR`].
+    pub reviewed_authors: Vec<(String, usize)>,
+}
+
+impl Stats {
+    /// Share of closed CLs that were abandoned rather than merged, or `None`
+    /// with neither.
+    pub fn abandon_rate(&self) -> Option<f64> {
+        let closed = self.total_merged + self.total_abandoned;
+        (closed > 0).then(|| self.total_abandoned as f64 / closed as f64)
+    }
+
+    /// Time between [`Stats::first_contribution`] and
+    /// [`Stats::last_contribution`], or `None` with no merged CLs.
+    pub fn contribution_span(&self) -> Option<Duration> {
+        Some(self.last_contribution? - self.first_contribution?)
+    }
+
+    /// True when the owner has neither merged CLs nor reviews — usually a
+    /// mistyped `--owner` or an account the anonymous API can't see.
+    pub fn is_empty(&self) -> bool {
+        self.total_merged == 0 && self.total_reviews == 0
+    }
+
+    /// The heatmap's current streak, allowing [`Stats::streak_grace`] empty
+    /// weeks.
+    pub fn current_streak(&self) -> u32 {
+        self.heatmap.current_streak_with_grace(self.streak_grace)
+    }
+
+    /// The heatmap's longest streak, allowing [`Stats::streak_grace`] empty
+    /// weeks.
+    pub fn longest_streak(&self) -> u32 {
+        self.heatmap.longest_streak_with_grace(self.streak_grace)
+    }
 }
 
 /// Heatmap grid covering the last [`HEATMAP_WEEKS`] weeks.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Heatmap {
     /// Buckets in chronological order — `weeks[0]` is the oldest.
     pub weeks: Vec<WeekBucket>,
@@ -57,6 +244,21 @@ impl Heatmap {
         self.weeks.iter().rev().take_while(|b| b.count > 0).count() as u32
     }
 
+    /// [`Heatmap::current_streak`], except that an empty week containing
+    /// one of `holidays` is passed over rather than ending the streak.  Such
+    /// weeks don't add to the count either.
+    pub fn current_streak_excluding_holidays(&self, holidays: &[NaiveDate]) -> u32 {
+        let mut streak = 0u32;
+        for b in self.weeks.iter().rev() {
+            if b.count > 0 {
+                streak += 1;
+            } else if !b.contains_any(holidays) {
+                break;
+            }
+        }
+        streak
+    }
+
     /// Longest run of consecutive non-empty weeks anywhere in the window.
     pub fn longest_streak(&self) -> u32 {
         let mut longest = 0u32;
@@ -71,12 +273,122 @@ impl Heatmap {
         }
         longest
     }
+
+    /// [`Heatmap::current_streak`], except that runs of up to `grace` empty
+    /// weeks are bridged instead of ending the streak — including the most
+    /// recent weeks, so a streak survives a short break that's still going.
+    /// Bridged weeks don't add to the count.  `grace = 0` matches
+    /// [`Heatmap::current_streak`].
+    pub fn current_streak_with_grace(&self, grace: u32) -> u32 {
+        let mut streak = 0u32;
+        let mut gap = 0u32;
+        for b in self.weeks.iter().rev() {
+            if b.count > 0 {
+                streak += 1;
+                gap = 0;
+            } else {
+                gap += 1;
+                if gap > grace {
+                    break;
+                }
+            }
+        }
+        streak
+    }
+
+    /// [`Heatmap::longest_streak`] with runs of up to `grace` empty weeks
+    /// bridged, as in [`Heatmap::current_streak_with_grace`].
+    pub fn longest_streak_with_grace(&self, grace: u32) -> u32 {
+        let mut longest = 0u32;
+        let mut run = 0u32;
+        let mut gap = 0u32;
+        for b in &self.weeks {
+            if b.count > 0 {
+                run += 1;
+                gap = 0;
+                longest = longest.max(run);
+            } else {
+                gap += 1;
+                if gap > grace {
+                    run = 0;
+                }
+            }
+        }
+        longest
+    }
+
+    /// The bucket with the highest `count`, earliest week on ties.
+    ///
+    /// `None` when every bucket is empty.
+    pub fn busiest_week(&self) -> Option<&WeekBucket> {
+        // `max_by_key` keeps the *last* maximum, so scan newest-first to land
+        // on the earliest week.
+        self.weeks
+            .iter()
+            .rev()
+            .filter(|b| b.count > 0)
+            .max_by_key(|b| b.count)
+    }
+
+    /// [`WeekBucket::level`] of every bucket, oldest first.
+    pub fn levels(&self) -> Vec<u8> {
+        self.weeks.iter().map(WeekBucket::level).collect()
+    }
+
+    /// Intensity levels from the trailing `window`-week average of `count`
+    /// (rounded), oldest first.  The first `window - 1` weeks average over
+    /// however many weeks precede them.
+    ///
+    /// Only the shading changes: counts, tooltips and streaks still use the
+    /// raw buckets.
+    pub fn smoothed(&self, window: usize) -> Vec<u8> {
+        let window = window.max(1);
+        let counts: Vec<u32> = self.weeks.iter().map(|b| b.count).collect();
+        (0..counts.len())
+            .map(|i| {
+                let from = (i + 1).saturating_sub(window);
+                let samples = &counts[from..=i];
+                let sum: u32 = samples.iter().sum();
+                let avg = (sum as f64 / samples.len() as f64).round() as u32;
+                level_for_count(avg)
+            })
+            .collect()
+    }
+
+    /// Longest run of consecutive empty weeks anywhere in the window — the
+    /// flip side of [`Heatmap::longest_streak`].
+    pub fn longest_gap(&self) -> u32 {
+        let mut longest = 0u32;
+        let mut run = 0u32;
+        for b in &self.weeks {
+            if b.count == 0 {
+                run += 1;
+                longest = longest.max(run);
+            } else {
+                run = 0;
+            }
+        }
+        longest
+    }
+
+    /// Contributions per active week: the sum of every bucket's `count`
+    /// divided by the number of non-empty buckets.  `0.0` when every bucket
+    /// is empty.
+    pub fn density(&self) -> f64 {
+        let active = self.weeks.iter().filter(|b| b.count > 0).count();
+        if active == 0 {
+            return 0.0;
+        }
+        let total: u64 = self.weeks.iter().map(|b| u64::from(b.count)).sum();
+        total as f64 / active as f64
+    }
 }
 
 /// Activity summary for a single calendar week.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct WeekBucket {
-    /// The Monday that starts this ISO week.
+    /// The first day of this week: a Monday (ISO weeks) or a Sunday, per
+    /// [`ComputeOptions::week_start`].
     pub week_start: NaiveDate,
     /// Total contributions (merged CLs + reviews) during this week.
     pub count: u32,
@@ -84,6 +396,12 @@ pub struct WeekBucket {
     ///
     /// Tracked separately so that the SVG tooltip can break down CLs vs reviews.
     pub review_count: u32,
+    /// `insertions + deletions` summed over the merged CLs in this week.
+    pub lines_changed: i64,
+    /// Lines added by the merged CLs in this week.
+    pub insertions: i64,
+    /// Lines removed by the merged CLs in this week.
+    pub deletions: i64,
     /// CL count broken down by project family (see [`project_family`]).
     ///
     /// Used by the renderer to assign per-project colours within a cell.
@@ -94,6 +412,12 @@ pub struct WeekBucket {
 }
 
 impl WeekBucket {
+    /// Whether any of `days` falls within the seven days from `week_start`.
+    pub fn contains_any(&self, days: &[NaiveDate]) -> bool {
+        let end = self.week_start + Duration::days(7);
+        days.iter().any(|d| (self.week_start..end).contains(d))
+    }
+
     /// Heatmap intensity level in `0..=4`.
     ///
     /// Uses absolute thresholds on total contributions (merged CLs + reviews,
@@ -108,13 +432,7 @@ impl WeekBucket {
     /// L4  count ≥ 10
     /// ```
     pub fn level(&self) -> u8 {
-        match self.count {
-            0 => 0,
-            1..=2 => 1,
-            3..=5 => 2,
-            6..=9 => 3,
-            _ => 4,
-        }
+        level_for_count(self.count)
     }
 
     /// The project family with the most CLs this week.
@@ -129,141 +447,790 @@ impl WeekBucket {
 }
 
 /// Per-project contribution summary.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct ProjectStat {
     pub name: String,
     pub merged: usize,
     pub insertions: i64,
     pub deletions: i64,
+    /// Fraction of [`Stats::total_merged`] landed in this project, `0.0..=1.0`.
+    pub share: f64,
+    /// Standing among the project's contributors over the heatmap window
+    /// (`--project-rank`).  [`compute`] leaves this `None`; the caller fills
+    /// it in.
+    pub rank: Option<ProjectRank>,
+}
+
+/// One host's share of the fetched data, in [`Stats::host_counts`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HostCount {
+    pub alias: String,
+    /// Merged CLs fetched, before any filtering.
+    pub cls: usize,
+    /// Review events fetched.
+    pub reviews: usize,
+}
+
+/// A merged CL singled out by [`Stats::notable_changes`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangeRef {
+    /// Project name, with the `alias::` prefix when hosts were combined.
+    pub project: String,
+    /// Change number, for a `{host}/c/{number}` link.
+    pub number: u64,
+    pub subject: String,
+    pub insertions: i64,
+    pub deletions: i64,
+    pub submitted: DateTime<Utc>,
+}
+
+impl ChangeRef {
+    /// Lines changed: `insertions + deletions`.
+    pub fn churn(&self) -> i64 {
+        self.insertions + self.deletions
+    }
+}
+
+/// An owner's rank by merged CLs among everyone who merged into a project.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ProjectRank {
+    /// 1 for the most merged CLs; ties share a rank.
+    pub rank: usize,
+    /// Distinct owners with at least one merged CL.
+    pub contributors: usize,
+    /// Counting was capped, so this is an estimate.
+    pub approximate: bool,
+}
+
+impl ProjectRank {
+    /// The "top N%" figure: `rank / contributors` as a percentage, rounded up
+    /// and never below 1.
+    pub fn top_percent(&self) -> usize {
+        (self.rank * 100).div_ceil(self.contributors.max(1)).max(1)
+    }
+}
+
+/// Activity in one calendar month (see [`Stats::monthly`]).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonthBucket {
+    pub year: i32,
+    /// 1–12.
+    pub month: u32,
+    /// Merged CLs submitted this month.
+    pub cls: u32,
+    /// Reviews this month.
+    pub reviews: u32,
+    /// `insertions + deletions` over this month's merged CLs.
+    pub lines: i64,
 }
 
 // ---------------------------------------------------------------------------
 // Aggregation
 // ---------------------------------------------------------------------------
 
+/// Optional knobs for [`compute_with`].  The default applies no filtering.
+#[derive(Debug, Clone, Default)]
+pub struct ComputeOptions {
+    /// Voters (email or username) whose votes are left out of
+    /// [`Stats::received_score_histogram`] — typically bots.
+    pub exclude_voters: Vec<String>,
+    /// Project patterns (see [`project_matches`]) whose changes and review
+    /// events are dropped before any aggregation.
+    ///
+    /// Filtering uses the full project name, so excluding
+    /// `chromium/third_party/*` leaves `chromium/src` — and therefore the
+    /// `chromium` [`project_family`] — intact.
+    pub exclude_projects: Vec<String>,
+    /// Merged CLs with fewer than this many `insertions + deletions` are
+    /// dropped before aggregation.
+    pub min_cl_size: Option<i64>,
+    /// Merged CLs with more than this many `insertions + deletions` are
+    /// dropped before aggregation.
+    pub max_cl_size: Option<i64>,
+    /// Cap on each merged CL's insertions and on its deletions where line
+    /// counts are summed (totals, per-project stats, heatmap and monthly
+    /// lines), so a vendored bump still counts as a merge without drowning
+    /// out everything else.  [`Stats::notable_changes`] keep the real sizes.
+    pub clamp_cl_lines: Option<u32>,
+    /// Empty weeks a streak may skip without breaking; copied to
+    /// [`Stats::streak_grace`].  Doesn't affect aggregation.
+    pub streak_grace: u32,
+    /// Path segments that make up a [`project_family`] for
+    /// [`WeekBucket::family_counts`]; 0 (the default) behaves like 1.
+    pub family_depth: usize,
+    /// Day the heatmap weeks begin on.
+    pub week_start: WeekStart,
+    /// Keep review events out of the heatmap buckets so the grid shows only
+    /// authored CLs.  Review totals, weekday counts and the monthly rollup
+    /// still include them.
+    pub reviews_outside_heatmap: bool,
+    /// Family renames applied after [`project_family`], e.g. `webrtc` →
+    /// `chromium` to colour the two as one.  Applied once, not chained.
+    /// Per-project stats keep the real project names.
+    pub merge_families: HashMap<String, String>,
+    /// How many of the largest merged CLs of the recent window to keep in
+    /// [`Stats::notable_changes`]; 0 keeps none.
+    pub notable: usize,
+    /// Length of the recent window behind [`Stats::recent_merged_90d`],
+    /// [`Stats::recent_reviews_90d`] and [`Stats::notable_changes`]; the
+    /// `prev_*` counts cover the same length before it.  0 (the default)
+    /// means [`DEFAULT_RECENT_DAYS`].
+    pub recent_days: u32,
+    /// Drop changes flagged [`ChangeInfo::work_in_progress`] before any
+    /// aggregation, whatever their status.
+    pub exclude_wip: bool,
+}
+
+impl ComputeOptions {
+    /// [`ComputeOptions::recent_days`] with the default filled in.
+    fn recent_days(&self) -> u32 {
+        match self.recent_days {
+            0 => DEFAULT_RECENT_DAYS,
+            days => days,
+        }
+    }
+
+    /// Whether `change`'s size lies within `min_cl_size..=max_cl_size`.
+    fn size_in_range(&self, change: &ChangeInfo) -> bool {
+        let size = change.insertions as i64 + change.deletions as i64;
+        self.min_cl_size.is_none_or(|min| size >= min)
+            && self.max_cl_size.is_none_or(|max| size <= max)
+    }
+
+    /// `change`'s insertions and deletions, each capped at `clamp_cl_lines`.
+    fn counted_lines(&self, change: &ChangeInfo) -> (i64, i64) {
+        let clamp = |n: i32| {
+            let n = i64::from(n);
+            self.clamp_cl_lines.map_or(n, |max| n.min(i64::from(max)))
+        };
+        (clamp(change.insertions), clamp(change.deletions))
+    }
+
+    /// `project`'s heatmap family after `family_depth` and `merge_families`.
+    fn family<'a>(&'a self, project: &'a str) -> &'a str {
+        let family = project_family(project, self.family_depth);
+        self.merge_families
+            .get(family)
+            .map_or(family, String::as_str)
+    }
+
+    fn excludes_project(&self, project: &str) -> bool {
+        self.exclude_projects
+            .iter()
+            .any(|p| project_matches(p, project))
+    }
+}
+
+/// [`compute_with`] using [`ComputeOptions::default`].
+pub fn compute(changes: &[ChangeInfo], reviews: &[ReviewEvent], now: DateTime<Utc>) -> Stats {
+    compute_with(changes, reviews, now, &ComputeOptions::default())
+}
+
 /// Compute [`Stats`] from a raw slice of changes and review events.
 ///
 /// `now` is the reference instant for the heatmap boundary and the 90-day
 /// "recent" window.  Pass [`chrono::Utc::now()`] in production; a fixed
 /// value in tests.
 ///
-/// Non-merged changes (status `NEW` or `ABANDONED`) are silently ignored.
+/// Open changes only count towards [`Stats::open_ages`]; abandoned ones
+/// only towards [`Stats::total_abandoned`].
 /// Merged changes whose `submitted` timestamp falls outside the heatmap
-/// window still contribute to the lifetime totals.
-pub fn compute(changes: &[ChangeInfo], reviews: &[ReviewEvent], now: DateTime<Utc>) -> Stats {
-    let today = now.date_naive();
-    let current_week_start = iso_week_start(today);
-
-    // Oldest week in the grid: (HEATMAP_WEEKS - 1) Mondays before the
-    // current week's Monday → exactly HEATMAP_WEEKS buckets inclusive.
-    let heatmap_start = current_week_start - Duration::weeks((HEATMAP_WEEKS - 1) as i64);
-
-    // Pre-allocate one bucket per week, filled with zeros.
-    let mut buckets: Vec<WeekBucket> = (0..HEATMAP_WEEKS)
-        .map(|i| WeekBucket {
-            week_start: heatmap_start + Duration::weeks(i as i64),
-            count: 0,
-            review_count: 0,
-            family_counts: HashMap::new(),
-        })
-        .collect();
+/// window still contribute to the lifetime totals.  Changes and reviews
+/// after `now` are ignored, so a past `now` (`--as-of`) reports the stats as
+/// they stood at that instant.
+///
+/// This is the batch form of [`StatsAccumulator`]; both produce identical
+/// results for the same input.
+pub fn compute_with(
+    changes: &[ChangeInfo],
+    reviews: &[ReviewEvent],
+    now: DateTime<Utc>,
+    opts: &ComputeOptions,
+) -> Stats {
+    let mut acc = StatsAccumulator::new(now, opts);
+    for change in changes {
+        acc.add_change(change);
+    }
+    for event in reviews {
+        acc.add_review(event);
+    }
+    acc.finish()
+}
+
+/// Drop repeats of a change, keeping the first, so a CL fetched twice
+/// (e.g. by overlapping `--owner-email` queries, or a page boundary that
+/// shifted mid-fetch) counts once.  Changes are keyed by project and
+/// number, which stays unique across hosts once projects carry their host
+/// prefix.
+pub fn dedup_changes(changes: &mut Vec<ChangeInfo>) {
+    let mut seen = HashSet::new();
+    changes.retain(|c| seen.insert((c.project.clone(), c.number)));
+}
+
+/// Incremental form of [`compute_with`]: fold changes and review events in
+/// one at a time (e.g. page by page as they're fetched) so memory stays
+/// bounded by the number of projects rather than the number of changes.
+///
+/// ```
+/// use gerritoscope::stats::{ComputeOptions, StatsAccumulator};
+///
+/// let mut acc = StatsAccumulator::new(chrono::Utc::now(), &ComputeOptions::default());
+/// // for change in page { acc.add_change(&change); }
+/// let stats = acc.finish();
+/// assert_eq!(stats.total_merged, 0);
+/// ```
+#[derive(Debug)]
+pub struct StatsAccumulator {
+    opts: ComputeOptions,
+    /// First day of the oldest heatmap week.
+    heatmap_start: NaiveDate,
+    /// First day of the week containing `now`.
+    current_week_start: NaiveDate,
+    /// Reference instant; anything later is ignored (see [`compute_with`]).
+    now: DateTime<Utc>,
+    /// Start of the recent window.
+    recent_cutoff: DateTime<Utc>,
+    /// Start of the `prev_*_90d` window.
+    prev_cutoff: DateTime<Utc>,
+    buckets: Vec<WeekBucket>,
+    months: Vec<MonthBucket>,
+    total_merged: usize,
+    total_insertions: i64,
+    total_deletions: i64,
+    recent_merged_90d: usize,
+    prev_merged_90d: usize,
+    total_reviews: usize,
+    recent_reviews_90d: usize,
+    prev_reviews_90d: usize,
+    reviewed_insertions: i64,
+    reviewed_deletions: i64,
+    project_map: HashMap<String, ProjectStat>,
+    received_score_histogram: BTreeMap<i32, usize>,
+    weekday_counts: [u32; 7],
+    first_contribution: Option<NaiveDate>,
+    last_contribution: Option<NaiveDate>,
+    /// Merged CLs with a known revision count, and the sum of those counts.
+    revised_cls: usize,
+    total_revisions: usize,
+    total_abandoned: usize,
+    open_ages: [usize; 4],
+    /// Largest recent merged CLs so far, kept sorted by [`by_churn`].
+    notable_changes: Vec<ChangeRef>,
+    /// Reviews per owner of the reviewed change.
+    author_counts: HashMap<String, usize>,
+}
 
-    let cutoff_90d = now - Duration::days(90);
+impl StatsAccumulator {
+    /// An empty accumulator; see [`compute_with`] for the meaning of `now`.
+    pub fn new(now: DateTime<Utc>, opts: &ComputeOptions) -> Self {
+        let current_week_start = week_start(now.date_naive(), opts.week_start);
+        let heatmap_start = heatmap_start(now.date_naive(), opts.week_start);
+
+        // Pre-allocate one bucket per week, filled with zeros.
+        let buckets: Vec<WeekBucket> = (0..HEATMAP_WEEKS)
+            .map(|i| WeekBucket {
+                week_start: heatmap_start + Duration::weeks(i as i64),
+                count: 0,
+                review_count: 0,
+                lines_changed: 0,
+                insertions: 0,
+                deletions: 0,
+                family_counts: HashMap::new(),
+            })
+            .collect();
 
-    let mut total_merged = 0usize;
-    let mut total_insertions = 0i64;
-    let mut total_deletions = 0i64;
-    let mut recent_merged_90d = 0usize;
-    let mut total_reviews = 0usize;
-    let mut recent_reviews_90d = 0usize;
-    let mut project_map: HashMap<String, ProjectStat> = HashMap::new();
+        let months = empty_months(heatmap_start, now.date_naive());
+
+        Self {
+            opts: opts.clone(),
+            heatmap_start,
+            current_week_start,
+            now,
+            recent_cutoff: now - Duration::days(opts.recent_days().into()),
+            prev_cutoff: now - Duration::days(2 * i64::from(opts.recent_days())),
+            buckets,
+            months,
+            total_merged: 0,
+            total_insertions: 0,
+            total_deletions: 0,
+            recent_merged_90d: 0,
+            prev_merged_90d: 0,
+            total_reviews: 0,
+            recent_reviews_90d: 0,
+            prev_reviews_90d: 0,
+            reviewed_insertions: 0,
+            reviewed_deletions: 0,
+            project_map: HashMap::new(),
+            received_score_histogram: BTreeMap::new(),
+            weekday_counts: [0; 7],
+            first_contribution: None,
+            last_contribution: None,
+            revised_cls: 0,
+            total_revisions: 0,
+            total_abandoned: 0,
+            open_ages: [0; 4],
+            notable_changes: Vec::new(),
+            author_counts: HashMap::new(),
+        }
+    }
 
-    for change in changes {
-        if change.status != ChangeStatus::Merged {
-            continue;
+    /// Fold in one change, classified by its own `status`: abandoned
+    /// changes are only counted, open ones only binned by age into
+    /// [`Stats::open_ages`], and filtered-out changes are ignored.
+    pub fn add_change(&mut self, change: &ChangeInfo) {
+        let opts = &self.opts;
+        if opts.excludes_project(&change.project)
+            || !opts.size_in_range(change)
+            || (opts.exclude_wip && change.work_in_progress)
+        {
+            return;
+        }
+        match change.status {
+            ChangeStatus::Merged => {}
+            // Abandoned changes have no `submitted`; the last update is
+            // when they were abandoned.
+            ChangeStatus::Abandoned if change.updated <= self.now => {
+                self.total_abandoned += 1;
+                return;
+            }
+            ChangeStatus::New if change.created <= self.now => {
+                let age = (self.now - change.created).num_days();
+                let bin = OPEN_AGE_LIMITS_DAYS
+                    .iter()
+                    .position(|&limit| age <= limit)
+                    .unwrap_or(OPEN_AGE_LIMITS_DAYS.len());
+                self.open_ages[bin] += 1;
+                return;
+            }
+            _ => return,
         }
         let Some(submitted) = change.submitted else {
             // A merged change without a submitted timestamp is a data anomaly;
             // skip rather than panic.
-            continue;
+            return;
         };
+        if submitted > self.now {
+            return;
+        }
 
-        total_merged += 1;
-        total_insertions += change.insertions as i64;
-        total_deletions += change.deletions as i64;
+        let (insertions, deletions) = opts.counted_lines(change);
+        self.total_merged += 1;
+        self.total_insertions += insertions;
+        self.total_deletions += deletions;
+        if let Some(n) = change.revision_count {
+            self.revised_cls += 1;
+            self.total_revisions += n;
+        }
 
-        if submitted > cutoff_90d {
-            recent_merged_90d += 1;
+        if submitted > self.recent_cutoff {
+            self.recent_merged_90d += 1;
+            if self.opts.notable > 0 {
+                self.notable_changes.push(ChangeRef {
+                    project: change.project.clone(),
+                    number: change.number,
+                    subject: change.subject.clone(),
+                    insertions: change.insertions as i64,
+                    deletions: change.deletions as i64,
+                    submitted,
+                });
+                self.notable_changes.sort_by(by_churn);
+                self.notable_changes.truncate(self.opts.notable);
+            }
+        } else if submitted > self.prev_cutoff {
+            self.prev_merged_90d += 1;
         }
 
+        let day = submitted.date_naive();
+        self.first_contribution = Some(self.first_contribution.map_or(day, |d| d.min(day)));
+        self.last_contribution = Some(self.last_contribution.map_or(day, |d| d.max(day)));
+
         // Update per-project totals.
-        let ps = project_map
+        let ps = self
+            .project_map
             .entry(change.project.clone())
             .or_insert_with(|| ProjectStat {
                 name: change.project.clone(),
                 merged: 0,
                 insertions: 0,
                 deletions: 0,
+                share: 0.0,
+                rank: None,
             });
         ps.merged += 1;
-        ps.insertions += change.insertions as i64;
-        ps.deletions += change.deletions as i64;
+        ps.insertions += insertions;
+        ps.deletions += deletions;
+
+        for score in received_scores(change, &self.opts.exclude_voters) {
+            *self.received_score_histogram.entry(score).or_insert(0) += 1;
+        }
 
         // Drop into a heatmap bucket if the submission falls inside the window.
-        let ws = iso_week_start(submitted.date_naive());
-        if ws >= heatmap_start && ws <= current_week_start {
-            let idx = (ws - heatmap_start).num_weeks() as usize;
-            if idx < HEATMAP_WEEKS {
-                buckets[idx].count += 1;
-                // Roll up into the project family for per-project colouring.
-                *buckets[idx]
-                    .family_counts
-                    .entry(project_family(&change.project).to_owned())
-                    .or_insert(0) += 1;
+        if let Some(idx) = self.bucket_index(day) {
+            let bucket = &mut self.buckets[idx];
+            bucket.count += 1;
+            bucket.lines_changed += insertions + deletions;
+            bucket.insertions += insertions;
+            bucket.deletions += deletions;
+            // Roll up into the project family for per-project colouring.
+            *bucket
+                .family_counts
+                .entry(self.opts.family(&change.project).to_owned())
+                .or_insert(0) += 1;
+            self.weekday_counts[submitted.weekday().num_days_from_monday() as usize] += 1;
+            if let Some(month) = self.month_bucket(day) {
+                month.cls += 1;
+                month.lines += insertions + deletions;
             }
         }
     }
 
-    // Aggregate review events into the heatmap and review counters.
-    for event in reviews {
-        total_reviews += 1;
-
-        if event.timestamp > cutoff_90d {
-            recent_reviews_90d += 1;
+    /// Fold in one review event.  Filtered-out projects are ignored.
+    pub fn add_review(&mut self, event: &ReviewEvent) {
+        if event.timestamp > self.now || self.opts.excludes_project(&event.project) {
+            return;
+        }
+        self.total_reviews += 1;
+        self.reviewed_insertions += event.insertions as i64;
+        self.reviewed_deletions += event.deletions as i64;
+        let author = event.author.as_deref().unwrap_or(UNKNOWN_AUTHOR);
+        *self.author_counts.entry(author.to_owned()).or_insert(0) += 1;
+
+        if event.timestamp > self.recent_cutoff {
+            self.recent_reviews_90d += 1;
+        } else if event.timestamp > self.prev_cutoff {
+            self.prev_reviews_90d += 1;
         }
 
-        let ws = iso_week_start(event.timestamp.date_naive());
-        if ws >= heatmap_start && ws <= current_week_start {
-            let idx = (ws - heatmap_start).num_weeks() as usize;
-            if idx < HEATMAP_WEEKS {
-                buckets[idx].count += 1;
-                buckets[idx].review_count += 1;
-                *buckets[idx]
+        if let Some(idx) = self.bucket_index(event.timestamp.date_naive()) {
+            if !self.opts.reviews_outside_heatmap {
+                let bucket = &mut self.buckets[idx];
+                bucket.count += 1;
+                bucket.review_count += 1;
+                *bucket
                     .family_counts
-                    .entry(project_family(&event.project).to_owned())
+                    .entry(self.opts.family(&event.project).to_owned())
                     .or_insert(0) += 1;
             }
+            self.weekday_counts[event.timestamp.weekday().num_days_from_monday() as usize] += 1;
+            if let Some(month) = self.month_bucket(event.timestamp.date_naive()) {
+                month.reviews += 1;
+            }
+        }
+    }
+
+    /// Add everything `other` has accumulated into `self`.
+    ///
+    /// Both accumulators must have been created with the same `now` and
+    /// options — e.g. one per host, merged once every host has finished.
+    pub fn merge(&mut self, other: StatsAccumulator) {
+        debug_assert_eq!(self.heatmap_start, other.heatmap_start);
+
+        self.total_merged += other.total_merged;
+        self.total_insertions += other.total_insertions;
+        self.total_deletions += other.total_deletions;
+        self.recent_merged_90d += other.recent_merged_90d;
+        self.prev_merged_90d += other.prev_merged_90d;
+        self.total_reviews += other.total_reviews;
+        self.recent_reviews_90d += other.recent_reviews_90d;
+        self.prev_reviews_90d += other.prev_reviews_90d;
+        self.reviewed_insertions += other.reviewed_insertions;
+        self.reviewed_deletions += other.reviewed_deletions;
+        self.revised_cls += other.revised_cls;
+        self.total_revisions += other.total_revisions;
+        self.total_abandoned += other.total_abandoned;
+        for (mine, theirs) in self.open_ages.iter_mut().zip(other.open_ages) {
+            *mine += theirs;
+        }
+        self.notable_changes.extend(other.notable_changes);
+        self.notable_changes.sort_by(by_churn);
+        self.notable_changes.truncate(self.opts.notable);
+        for (author, n) in other.author_counts {
+            *self.author_counts.entry(author).or_insert(0) += n;
+        }
+
+        for (mine, theirs) in self.buckets.iter_mut().zip(other.buckets) {
+            mine.count += theirs.count;
+            mine.review_count += theirs.review_count;
+            mine.lines_changed += theirs.lines_changed;
+            mine.insertions += theirs.insertions;
+            mine.deletions += theirs.deletions;
+            for (family, n) in theirs.family_counts {
+                *mine.family_counts.entry(family).or_insert(0) += n;
+            }
+        }
+
+        for (mine, theirs) in self.months.iter_mut().zip(other.months) {
+            mine.cls += theirs.cls;
+            mine.reviews += theirs.reviews;
+            mine.lines += theirs.lines;
+        }
+
+        for (name, theirs) in other.project_map {
+            let ps = self.project_map.entry(name).or_insert_with(|| ProjectStat {
+                name: theirs.name.clone(),
+                merged: 0,
+                insertions: 0,
+                deletions: 0,
+                share: 0.0,
+                rank: None,
+            });
+            ps.merged += theirs.merged;
+            ps.insertions += theirs.insertions;
+            ps.deletions += theirs.deletions;
+        }
+
+        for (score, n) in other.received_score_histogram {
+            *self.received_score_histogram.entry(score).or_insert(0) += n;
+        }
+        for (mine, theirs) in self.weekday_counts.iter_mut().zip(other.weekday_counts) {
+            *mine += theirs;
+        }
+
+        self.first_contribution = match (self.first_contribution, other.first_contribution) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.last_contribution = match (self.last_contribution, other.last_contribution) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    /// Produce the final [`Stats`].
+    pub fn finish(self) -> Stats {
+        let max_count = self.buckets.iter().map(|b| b.count).max().unwrap_or(0);
+
+        let distinct_projects = self.project_map.len();
+        let mut top_projects: Vec<ProjectStat> = self.project_map.into_values().collect();
+        // Break ties by name so the result doesn't depend on hash order.
+        top_projects.sort_unstable_by(|a, b| b.merged.cmp(&a.merged).then(a.name.cmp(&b.name)));
+        top_projects.truncate(TOP_PROJECTS_COUNT);
+        for p in &mut top_projects {
+            p.share = p.merged as f64 / self.total_merged as f64;
+        }
+        let concentration = top_projects.first().map_or(0.0, |p| p.share);
+
+        let mut reviewed_authors: Vec<(String, usize)> = self.author_counts.into_iter().collect();
+        reviewed_authors.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        reviewed_authors.truncate(TOP_AUTHORS_COUNT);
+
+        Stats {
+            heatmap: Heatmap {
+                weeks: self.buckets,
+                max_count,
+            },
+            total_merged: self.total_merged,
+            total_insertions: self.total_insertions,
+            total_deletions: self.total_deletions,
+            recent_merged_90d: self.recent_merged_90d,
+            prev_merged_90d: self.prev_merged_90d,
+            total_reviews: self.total_reviews,
+            recent_reviews_90d: self.recent_reviews_90d,
+            prev_reviews_90d: self.prev_reviews_90d,
+            reviewed_insertions: self.reviewed_insertions,
+            reviewed_deletions: self.reviewed_deletions,
+            distinct_projects,
+            top_projects,
+            concentration,
+            received_score_histogram: self.received_score_histogram,
+            weekday_counts: self.weekday_counts,
+            monthly: self.months,
+            first_contribution: self.first_contribution,
+            last_contribution: self.last_contribution,
+            failed_hosts: Vec::new(),
+            reviews_capped: false,
+            reviews_since: None,
+            reviews_included: true,
+            host_counts: Vec::new(),
+            streak_grace: self.opts.streak_grace,
+            recent_days: self.opts.recent_days(),
+            avg_revisions_per_cl: (self.revised_cls > 0)
+                .then(|| self.total_revisions as f64 / self.revised_cls as f64),
+            total_abandoned: self.total_abandoned,
+            open_ages: self.open_ages,
+            notable_changes: self.notable_changes,
+            reviewed_authors,
+        }
+    }
+
+    /// Index of the heatmap bucket for `day`, or `None` outside the window.
+    fn bucket_index(&self, day: NaiveDate) -> Option<usize> {
+        let ws = week_start(day, self.opts.week_start);
+        if ws < self.heatmap_start || ws > self.current_week_start {
+            return None;
         }
+        let idx = (ws - self.heatmap_start).num_weeks() as usize;
+        (idx < HEATMAP_WEEKS).then_some(idx)
+    }
+
+    /// The month bucket for `day`, which must already be inside the heatmap
+    /// window.
+    fn month_bucket(&mut self, day: NaiveDate) -> Option<&mut MonthBucket> {
+        let first = self.months.first()?;
+        let idx = month_ordinal(day) - (first.year * 12 + first.month as i32 - 1);
+        self.months.get_mut(usize::try_from(idx).ok()?)
     }
+}
+
+/// Months since year 0, so consecutive calendar months differ by one.
+fn month_ordinal(day: NaiveDate) -> i32 {
+    day.year() * 12 + day.month0() as i32
+}
+
+/// Zeroed buckets for every calendar month from `first`'s through `last`'s.
+fn empty_months(first: NaiveDate, last: NaiveDate) -> Vec<MonthBucket> {
+    (month_ordinal(first)..=month_ordinal(last))
+        .map(|m| MonthBucket {
+            year: m.div_euclid(12),
+            month: m.rem_euclid(12) as u32 + 1,
+            cls: 0,
+            reviews: 0,
+            lines: 0,
+        })
+        .collect()
+}
+
+/// Distinct non-zero [`REVIEW_LABEL`] scores on `change`, skipping the
+/// owner's own votes and anyone in `exclude_voters`.
+fn received_scores(change: &ChangeInfo, exclude_voters: &[String]) -> Vec<i32> {
+    let Some(label) = change.labels.get(REVIEW_LABEL) else {
+        return vec![];
+    };
+    let owner_id = change.owner.as_ref().and_then(|o| o.account_id);
+    let mut scores: Vec<i32> = label
+        .all
+        .iter()
+        .filter(|v| owner_id.is_none() || v.account.account_id != owner_id)
+        .filter(|v| !exclude_voters.iter().any(|x| v.account.matches(x)))
+        .filter_map(|v| v.value)
+        .filter(|&score| score != 0)
+        .collect();
+    scores.sort_unstable();
+    scores.dedup();
+    scores
+}
+
+// ---------------------------------------------------------------------------
+// Sample data
+// ---------------------------------------------------------------------------
+
+/// Realistic-looking synthetic [`Stats`] for theme previews, with the
+/// heatmap ending in the week of `today`.
+///
+/// Uses a fixed week-by-week activity pattern, so the output depends only on
+/// `today` (no RNG dependency needed).  Every intensity level appears.
+pub fn sample_stats(today: NaiveDate) -> Stats {
+    let heatmap_start = heatmap_start(today, WeekStart::Monday);
+
+    // (merged_cls, reviews) for each of the 52 weeks, oldest first.
+    // Designed to exercise all four intensity levels and produce a
+    // recognisable heatmap pattern.
+    #[rustfmt::skip]
+    let activity: [(u32, u32); HEATMAP_WEEKS] = [
+        (0, 2), (1, 3), (2, 4), (1, 2), (0, 0), (3, 5), (2, 3), (1, 4),
+        (0, 1), (2, 6), (3, 4), (1, 2), (2, 3), (0, 0), (4, 7), (2, 4),
+        (1, 3), (0, 2), (3, 5), (2, 3), (0, 0), (1, 4), (2, 6), (3, 3),
+        (1, 2), (0, 0), (2, 4), (3, 6), (1, 3), (2, 5), (0, 1), (4, 8),
+        (3, 5), (2, 4), (1, 2), (0, 0), (3, 6), (2, 3), (1, 4), (0, 0),
+        (2, 5), (3, 7), (4, 6), (2, 3), (1, 2), (0, 0), (3, 5), (2, 4),
+        (1, 3), (3, 6), (2, 4), (1, 2),
+    ];
+
+    let buckets: Vec<WeekBucket> = activity
+        .iter()
+        .enumerate()
+        .map(|(i, &(cls, reviews))| {
+            let count = cls + reviews;
+            let mut family_counts = HashMap::new();
+            if count > 0 {
+                family_counts.insert("chromium".to_owned(), count);
+            }
+            WeekBucket {
+                week_start: heatmap_start + Duration::weeks(i as i64),
+                count,
+                review_count: reviews,
+                lines_changed: cls as i64 * 130,
+                insertions: cls as i64 * 100,
+                deletions: cls as i64 * 30,
+                family_counts,
+            }
+        })
+        .collect();
 
     let max_count = buckets.iter().map(|b| b.count).max().unwrap_or(0);
 
-    let mut top_projects: Vec<ProjectStat> = project_map.into_values().collect();
-    top_projects.sort_unstable_by(|a, b| b.merged.cmp(&a.merged));
-    top_projects.truncate(TOP_PROJECTS_COUNT);
+    let mut monthly = empty_months(heatmap_start, today);
+    for b in &buckets {
+        let idx = (month_ordinal(b.week_start) - month_ordinal(heatmap_start)) as usize;
+        let month = &mut monthly[idx];
+        month.cls += b.count - b.review_count;
+        month.reviews += b.review_count;
+        month.lines += b.lines_changed;
+    }
 
     Stats {
         heatmap: Heatmap {
             weeks: buckets,
             max_count,
         },
-        total_merged,
-        total_insertions,
-        total_deletions,
-        recent_merged_90d,
-        total_reviews,
-        recent_reviews_90d,
-        top_projects,
+        total_merged: 142,
+        total_insertions: 18_432,
+        total_deletions: 4_217,
+        recent_merged_90d: 23,
+        prev_merged_90d: 19,
+        total_reviews: 287,
+        recent_reviews_90d: 41,
+        prev_reviews_90d: 44,
+        reviewed_insertions: 96_210,
+        reviewed_deletions: 41_877,
+        distinct_projects: 9,
+        top_projects: vec![
+            ProjectStat {
+                name: "chromium/src".to_owned(),
+                merged: 98,
+                insertions: 12_450,
+                deletions: 2_890,
+                share: 98.0 / 142.0,
+                rank: None,
+            },
+            ProjectStat {
+                name: "v8/v8".to_owned(),
+                merged: 27,
+                insertions: 3_812,
+                deletions: 890,
+                share: 27.0 / 142.0,
+                rank: None,
+            },
+            ProjectStat {
+                name: "angle/angle".to_owned(),
+                merged: 17,
+                insertions: 2_170,
+                deletions: 437,
+                share: 17.0 / 142.0,
+                rank: None,
+            },
+        ],
+        concentration: 98.0 / 142.0,
+        received_score_histogram: [(2, 131), (1, 38), (-1, 12)].into_iter().collect(),
+        weekday_counts: [41, 48, 45, 39, 30, 4, 2],
+        monthly,
+        first_contribution: NaiveDate::from_ymd_opt(2019, 3, 11),
+        last_contribution: NaiveDate::from_ymd_opt(2024, 6, 10),
+        failed_hosts: vec![],
+        reviews_capped: false,
+        reviews_since: None,
+        reviews_included: true,
+        host_counts: vec![],
+        streak_grace: 0,
+        recent_days: DEFAULT_RECENT_DAYS,
+        avg_revisions_per_cl: None,
+        total_abandoned: 0,
+        open_ages: [0; 4],
+        notable_changes: vec![],
+        reviewed_authors: vec![
+            ("alice@chromium.org".to_owned(), 40),
+            ("bob@chromium.org".to_owned(), 22),
+        ],
     }
 }
 
@@ -274,8 +1241,8 @@ pub fn compute(changes: &[ChangeInfo], reviews: &[ReviewEvent], now: DateTime<Ut
 /// Map a full Gerrit project path to the family name used for heatmap
 /// colouring and per-project segments.
 ///
-/// **Single-host** — family is the first `/`-separated path segment, rolling
-/// sub-repositories up into their parent:
+/// **Single-host** — family is the first `depth` `/`-separated path segments,
+/// rolling deeper sub-repositories up into their parent.  With `depth` 1:
 ///
 /// | Project                       | Family      |
 /// |-------------------------------|-------------|
@@ -284,10 +1251,15 @@ pub fn compute(changes: &[ChangeInfo], reviews: &[ReviewEvent], now: DateTime<Ut
 /// | `chromium/src`                | `chromium`  |
 /// | `chromium/third_party/ffmpeg` | `chromium`  |
 ///
+/// With `depth` 2, `chromium/src` and `chromium/third_party/ffmpeg` become
+/// the distinct families `chromium/src` and `chromium/third_party`.  A
+/// `depth` of 0 behaves like 1.
+///
 /// **Multi-host** — when querying multiple Gerrit instances `main.rs`
 /// prefixes each project with the host alias using `::` as separator
 /// (`"chromium::chromium/src"`).  In that case the prefix becomes the family,
-/// which conveniently groups all activity on that host under one colour:
+/// which conveniently groups all activity on that host under one colour,
+/// whatever the `depth`:
 ///
 /// | Project                      | Family     |
 /// |------------------------------|------------|
@@ -298,19 +1270,134 @@ pub fn compute(changes: &[ChangeInfo], reviews: &[ReviewEvent], now: DateTime<Ut
 /// Per-project *stats* (`top_projects`) always use the full project name
 /// (including any `alias::` prefix); only the heatmap visualisation uses
 /// families.
-pub fn project_family(project: &str) -> &str {
+pub fn project_family(project: &str, depth: usize) -> &str {
     // Multi-host prefix takes precedence: "alias::rest" → "alias"
     if let Some((prefix, _)) = project.split_once("::") {
         return prefix;
     }
-    // Single-host: first path segment rolls up sub-repos.
-    project.split('/').next().unwrap_or(project)
+    // Single-host: the first `depth` path segments roll up sub-repos.
+    match project.match_indices('/').nth(depth.max(1) - 1) {
+        Some((end, _)) => &project[..end],
+        None => project,
+    }
+}
+
+/// Largest churn first; ties go to the newer CL, then the higher number, so
+/// the order doesn't depend on fetch order.
+fn by_churn(a: &ChangeRef, b: &ChangeRef) -> std::cmp::Ordering {
+    b.churn()
+        .cmp(&a.churn())
+        .then(b.submitted.cmp(&a.submitted))
+        .then(b.number.cmp(&a.number))
+}
+
+/// Whether `project` matches an `--exclude-project` pattern.
+///
+/// - `*` matches any run of characters (including `/`):
+///   `chromium/third_party/*` matches `chromium/third_party/ffmpeg`.
+/// - A pattern without `*` matches the project exactly or any sub-repo under
+///   it: `chromium/third_party` matches `chromium/third_party/ffmpeg` but not
+///   `chromium/third_party_extra`.
+///
+/// Multi-host `alias::` prefixes are ignored unless the pattern includes one,
+/// so `infra/*` matches `chromium::infra/luci` while `go::*` matches only the
+/// `go` host.
+pub fn project_matches(pattern: &str, project: &str) -> bool {
+    let name = if pattern.contains("::") {
+        project
+    } else {
+        project.split_once("::").map_or(project, |(_, rest)| rest)
+    };
+    if pattern.contains('*') {
+        glob_match(pattern, name)
+    } else {
+        name == pattern
+            || name
+                .strip_prefix(pattern)
+                .is_some_and(|rest| rest.starts_with('/'))
+    }
+}
+
+/// Minimal `*`-only glob match over the whole string.
+fn glob_match(pattern: &str, s: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = s.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let last = parts.pop();
+    for part in parts {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    match last {
+        Some(last) => rest.ends_with(last),
+        None => rest.is_empty(),
+    }
+}
+
+/// First day of the oldest heatmap week when `today` is the current day:
+/// (HEATMAP_WEEKS - 1) weeks before the start of the current week, giving
+/// exactly [`HEATMAP_WEEKS`] buckets inclusive.
+pub fn heatmap_start(today: NaiveDate, start: WeekStart) -> NaiveDate {
+    week_start(today, start) - Duration::weeks((HEATMAP_WEEKS - 1) as i64)
+}
+
+/// First day to fetch review events from: the last
+/// [`REVIEW_LOOKBACK_WEEKS`] weeks, or further back to `after` when it is
+/// earlier, so reviews cover the same range as CLs.
+pub fn review_lookback_start(after: Option<NaiveDate>, now: DateTime<Utc>) -> NaiveDate {
+    let default = (now - Duration::weeks(REVIEW_LOOKBACK_WEEKS)).date_naive();
+    after.map_or(default, |a| a.min(default))
+}
+
+/// Parse an `--after` value: a `YYYY-MM-DD` date, or a duration before
+/// `today` written `<n><unit>` with unit `d` (days), `w` (weeks), `mo`
+/// (calendar months) or `y` (years) — `90d`, `6mo`, `2y`.
+///
+/// Month and year steps land on the same day of the month, clamped to the
+/// month's end (`1mo` before 31 March is 29 February in a leap year).
+pub fn parse_relative_or_date(s: &str, today: NaiveDate) -> Result<NaiveDate> {
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(date);
+    }
+    let invalid =
+        || anyhow::anyhow!("{s:?} is neither YYYY-MM-DD nor a duration like 90d, 12w, 6mo or 2y");
+
+    let split = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let (n, unit) = s.split_at(split);
+    let n: u32 = n.parse().map_err(|_| invalid())?;
+    let date = match unit {
+        "d" => today.checked_sub_signed(Duration::days(n.into())),
+        "w" => today.checked_sub_signed(Duration::weeks(n.into())),
+        "mo" => today.checked_sub_months(Months::new(n)),
+        "y" => n
+            .checked_mul(12)
+            .and_then(|months| today.checked_sub_months(Months::new(months))),
+        _ => return Err(invalid()),
+    };
+    date.ok_or_else(|| anyhow::anyhow!("{s:?} reaches too far back"))
+}
+
+/// Heatmap intensity level for a weekly contribution count; see
+/// [`WeekBucket::level`] for the thresholds.
+pub fn level_for_count(count: u32) -> u8 {
+    match count {
+        0 => 0,
+        1..=2 => 1,
+        3..=5 => 2,
+        6..=9 => 3,
+        _ => 4,
+    }
 }
 
-/// Return the Monday that begins the ISO week containing `date`.
-fn iso_week_start(date: NaiveDate) -> NaiveDate {
-    let days_since_monday = date.weekday().num_days_from_monday() as i64;
-    date - Duration::days(days_since_monday)
+/// Return the first day of the week containing `date`: its Monday for ISO
+/// weeks, its Sunday for [`WeekStart::Sunday`].
+fn week_start(date: NaiveDate, start: WeekStart) -> NaiveDate {
+    date - Duration::days(start.days_into_week(date.weekday()))
 }
 
 // ---------------------------------------------------------------------------
@@ -337,14 +1424,23 @@ mod tests {
 
     fn merged_cl(project: &str, submitted: &str, ins: i32, del: i32) -> ChangeInfo {
         ChangeInfo {
+            id: String::new(),
+            number: 1,
+            subject: String::new(),
             project: project.to_owned(),
+            branch: "main".to_owned(),
             status: ChangeStatus::Merged,
+            created: ts(submitted),
             updated: ts(submitted),
             submitted: Some(ts(submitted)),
             insertions: ins,
             deletions: del,
+            work_in_progress: false,
             more_changes: None,
+            revision_count: None,
             messages: vec![],
+            owner: None,
+            labels: Default::default(),
         }
     }
 
@@ -381,6 +1477,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn sunday_start_buckets_weekend_activity_into_next_week() {
+        let now = ts("2024-06-12"); // Wednesday
+        let opts = ComputeOptions {
+            week_start: WeekStart::Sunday,
+            ..Default::default()
+        };
+        // Sunday 2024-06-09 opens the current week under Sunday start, but
+        // closes the previous one under Monday start.
+        let changes = vec![merged_cl("a", "2024-06-09", 1, 0)];
+
+        let sunday = compute_with(&changes, &[], now, &opts);
+        let last = sunday.heatmap.weeks.last().unwrap();
+        assert_eq!(last.week_start.weekday(), Weekday::Sun);
+        assert_eq!(last.count, 1);
+
+        let monday = compute(&changes, &[], now);
+        assert_eq!(monday.heatmap.weeks.last().unwrap().count, 0);
+    }
+
     #[test]
     fn heatmap_weeks_are_consecutive_and_span_52_weeks() {
         let stats = compute(&[], &[], ts("2024-06-12"));
@@ -448,9 +1564,597 @@ mod tests {
         assert_eq!(last.count, 3);
     }
 
+    // -----------------------------------------------------------------------
+    // Smoothing
+    // -----------------------------------------------------------------------
+
     #[test]
-    fn abandoned_and_open_cls_are_ignored() {
-        let now = ts("2024-06-12");
+    fn smoothed_spreads_a_spike() {
+        let mut heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        heatmap.weeks[2].count = 12;
+        heatmap.weeks[8].count = 1;
+
+        let raw = heatmap.levels();
+        let smooth = heatmap.smoothed(SMOOTH_WEEKS);
+        assert_eq!(&raw[..10], &[0, 0, 4, 0, 0, 0, 0, 0, 1, 0]);
+        // Week 2 averages only three samples (12/3 = 4); weeks 3–5 see 12/4.
+        // The lone 1 at week 8 rounds to 0 (1/4 = 0.25).
+        assert_eq!(&smooth[..10], &[0, 0, 2, 2, 2, 2, 0, 0, 0, 0]);
+        assert_eq!(heatmap.smoothed(1), raw);
+    }
+
+    // -----------------------------------------------------------------------
+    // Density
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn density_of_empty_heatmap_is_zero() {
+        let heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        assert_eq!(heatmap.density(), 0.0);
+    }
+
+    #[test]
+    fn density_of_uniform_weeks() {
+        let mut heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        for b in &mut heatmap.weeks {
+            b.count = 2;
+        }
+        assert_eq!(heatmap.density(), 2.0);
+    }
+
+    #[test]
+    fn density_ignores_empty_weeks() {
+        let mut heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        heatmap.weeks[0].count = 1;
+        heatmap.weeks[10].count = 5;
+        heatmap.weeks[11].count = 3;
+        heatmap.weeks[51].count = 7;
+        // 16 contributions over 4 active weeks.
+        assert_eq!(heatmap.density(), 4.0);
+    }
+
+    #[test]
+    fn level_for_count_thresholds() {
+        let levels: Vec<u8> = [0, 1, 2, 3, 5, 6, 9, 10, 500]
+            .into_iter()
+            .map(level_for_count)
+            .collect();
+        assert_eq!(levels, [0, 1, 1, 2, 2, 3, 3, 4, 4]);
+    }
+
+    // -----------------------------------------------------------------------
+    // Review lookback
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn review_lookback_defaults_to_54_weeks() {
+        let now = ts("2024-06-12");
+        assert_eq!(
+            review_lookback_start(None, now),
+            NaiveDate::from_ymd_opt(2023, 5, 31).unwrap()
+        );
+    }
+
+    #[test]
+    fn review_lookback_extends_to_an_earlier_after_only() {
+        let now = ts("2024-06-12");
+        let default = review_lookback_start(None, now);
+        assert_eq!(default, NaiveDate::from_ymd_opt(2023, 5, 31).unwrap());
+        let early = NaiveDate::from_ymd_opt(2019, 1, 1).unwrap();
+        let late = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        assert_eq!(review_lookback_start(Some(early), now), early);
+        assert_eq!(review_lookback_start(Some(late), now), default);
+    }
+
+    #[test]
+    fn relative_after_counts_back_from_today() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 12).unwrap();
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(
+            parse_relative_or_date("90d", today).unwrap(),
+            date(2024, 3, 14)
+        );
+        assert_eq!(
+            parse_relative_or_date("2w", today).unwrap(),
+            date(2024, 5, 29)
+        );
+        assert_eq!(
+            parse_relative_or_date("6mo", today).unwrap(),
+            date(2023, 12, 12)
+        );
+        assert_eq!(
+            parse_relative_or_date("2y", today).unwrap(),
+            date(2022, 6, 12)
+        );
+    }
+
+    #[test]
+    fn relative_after_keeps_literal_dates() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 12).unwrap();
+        assert_eq!(
+            parse_relative_or_date("2024-03-14", today).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 14).unwrap()
+        );
+    }
+
+    #[test]
+    fn relative_after_rejects_garbage() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 12).unwrap();
+        for bad in ["banana", "", "90", "d", "3x", "-5d", "2024-13-01"] {
+            let err = parse_relative_or_date(bad, today).unwrap_err();
+            assert!(
+                err.to_string().contains("duration like 90d"),
+                "{bad}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn past_now_ends_heatmap_in_that_week() {
+        // Sunday 2023-12-31 belongs to the week starting Monday 2023-12-25.
+        let now = ts("2023-12-31");
+        let changes = vec![
+            merged_cl("a", "2023-12-27", 10, 0),
+            merged_cl("a", "2024-01-02", 10, 0),
+        ];
+        let reviews = vec![ReviewEvent {
+            timestamp: ts("2024-01-03"),
+            project: "a".to_owned(),
+            insertions: 1,
+            deletions: 0,
+            author: None,
+        }];
+        let stats = compute(&changes, &reviews, now);
+
+        let last = stats.heatmap.weeks.last().unwrap();
+        assert_eq!(
+            last.week_start,
+            NaiveDate::from_ymd_opt(2023, 12, 25).unwrap()
+        );
+        assert_eq!(last.count, 1);
+        // Activity after `now` doesn't count anywhere.
+        assert_eq!(stats.total_merged, 1);
+        assert_eq!(stats.total_reviews, 0);
+        assert_eq!(
+            stats.last_contribution,
+            NaiveDate::from_ymd_opt(2023, 12, 27)
+        );
+        assert_eq!(
+            stats.monthly.last().map(|m| (m.year, m.month)),
+            Some((2023, 12))
+        );
+    }
+
+    #[test]
+    fn heatmap_start_matches_first_bucket() {
+        let now = ts("2024-06-12");
+        let stats = compute(&[], &[], now);
+        assert_eq!(
+            heatmap_start(now.date_naive(), WeekStart::Monday),
+            stats.heatmap.weeks[0].week_start
+        );
+    }
+
+    // -----------------------------------------------------------------------
+    // Incremental accumulation
+    // -----------------------------------------------------------------------
+
+    fn mixed_history() -> (Vec<ChangeInfo>, Vec<ReviewEvent>) {
+        let changes = vec![
+            merged_cl("chromium/src", "2024-06-10", 10, 5),
+            merged_cl("openscreen", "2024-05-20", 3, 1),
+            merged_cl("openscreen/quic", "2024-05-21", 7, 0),
+            merged_cl("chromium/src", "2023-01-02", 100, 50),
+            merged_cl("go", "2019-03-11", 1, 1),
+            voted_cl(),
+        ];
+        let reviews = vec![
+            ReviewEvent {
+                timestamp: ts("2024-06-11"),
+                project: "chromium/src".to_owned(),
+                insertions: 0,
+                deletions: 0,
+                author: None,
+            },
+            ReviewEvent {
+                timestamp: ts("2024-02-01"),
+                project: "go".to_owned(),
+                insertions: 0,
+                deletions: 0,
+                author: None,
+            },
+        ];
+        (changes, reviews)
+    }
+
+    #[test]
+    fn folding_pages_matches_batch() {
+        let now = ts("2024-06-12");
+        let (changes, reviews) = mixed_history();
+        let batch = compute(&changes, &reviews, now);
+
+        let mut acc = StatsAccumulator::new(now, &ComputeOptions::default());
+        for page in changes.chunks(2) {
+            for change in page {
+                acc.add_change(change);
+            }
+        }
+        for event in &reviews {
+            acc.add_review(event);
+        }
+        assert_eq!(acc.finish(), batch);
+    }
+
+    #[test]
+    fn compute_is_deterministic_and_clones_compare_equal() {
+        let now = ts("2024-06-12");
+        let (changes, reviews) = mixed_history();
+        let stats = compute(&changes, &reviews, now);
+        assert_eq!(compute(&changes, &reviews, now), stats);
+        assert_eq!(stats.clone(), stats);
+        assert_eq!(stats.heatmap.clone(), stats.heatmap);
+    }
+
+    #[test]
+    fn changes_fetched_under_two_emails_count_once() {
+        let now = ts("2024-06-12");
+        let cl = |number, project: &str, submitted| ChangeInfo {
+            number,
+            ..merged_cl(project, submitted, 10, 2)
+        };
+        // CL 7 came back from both the old- and new-address queries.
+        let mut changes = vec![
+            cl(7, "repo", "2024-06-10"),
+            cl(8, "repo", "2024-06-03"),
+            cl(7, "repo", "2024-06-10"),
+            // Same number in another project is a different CL.
+            cl(7, "corp::repo", "2024-06-10"),
+        ];
+        dedup_changes(&mut changes);
+        let kept: Vec<(&str, u64)> = changes
+            .iter()
+            .map(|c| (c.project.as_str(), c.number))
+            .collect();
+        assert_eq!(kept, [("repo", 7), ("repo", 8), ("corp::repo", 7)]);
+
+        let stats = compute(&changes, &[], now);
+        assert_eq!(stats.total_merged, 3);
+        assert_eq!(stats.total_insertions, 30);
+    }
+
+    #[test]
+    fn merging_accumulators_matches_batch() {
+        let now = ts("2024-06-12");
+        let (changes, reviews) = mixed_history();
+        let batch = compute(&changes, &reviews, now);
+
+        let opts = ComputeOptions::default();
+        let (left, right) = changes.split_at(3);
+        let mut a = StatsAccumulator::new(now, &opts);
+        left.iter().for_each(|c| a.add_change(c));
+        a.add_review(&reviews[0]);
+        let mut b = StatsAccumulator::new(now, &opts);
+        right.iter().for_each(|c| b.add_change(c));
+        b.add_review(&reviews[1]);
+        a.merge(b);
+        assert_eq!(a.finish(), batch);
+    }
+
+    #[test]
+    fn notable_changes_are_the_largest_recent_cls() {
+        let now = ts("2024-06-12");
+        let cl = |number, submitted, ins, del, subject: &str| ChangeInfo {
+            number,
+            subject: subject.to_owned(),
+            ..merged_cl("repo", submitted, ins, del)
+        };
+        let changes = vec![
+            cl(1, "2024-06-10", 20, 5, "Fix typo"),
+            cl(2, "2024-05-02", 820, 12, "Refactor frame scheduler"),
+            // Biggest of all, but outside the 90-day window.
+            cl(3, "2023-01-09", 5000, 0, "Import ffmpeg"),
+            cl(4, "2024-04-01", 300, 300, "Split compositor"),
+            cl(5, "2024-06-03", 1, 1, "Bump version"),
+        ];
+        let opts = ComputeOptions {
+            notable: 2,
+            ..Default::default()
+        };
+        let stats = compute_with(&changes, &[], now, &opts);
+        let picked: Vec<(u64, &str, i64)> = stats
+            .notable_changes
+            .iter()
+            .map(|c| (c.number, c.subject.as_str(), c.churn()))
+            .collect();
+        assert_eq!(
+            picked,
+            [
+                (2, "Refactor frame scheduler", 832),
+                (4, "Split compositor", 600)
+            ]
+        );
+
+        // Off by default; merged accumulators agree with the batch result.
+        assert!(compute(&changes, &[], now).notable_changes.is_empty());
+        let mut a = StatsAccumulator::new(now, &opts);
+        changes[..2].iter().for_each(|c| a.add_change(c));
+        let mut b = StatsAccumulator::new(now, &opts);
+        changes[2..].iter().for_each(|c| b.add_change(c));
+        a.merge(b);
+        assert_eq!(a.finish(), stats);
+    }
+
+    #[test]
+    fn reviewed_authors_rank_owners_of_reviewed_changes() {
+        let now = ts("2024-06-12");
+        let review = |author: Option<&str>| ReviewEvent {
+            timestamp: ts("2024-06-05"),
+            project: "repo".to_owned(),
+            insertions: 0,
+            deletions: 0,
+            author: author.map(str::to_owned),
+        };
+        let mut reviews = vec![
+            review(Some("bob@example.com")),
+            review(Some("alice@example.com")),
+            review(None),
+            review(Some("alice@example.com")),
+            review(None),
+            review(Some("alice@example.com")),
+            review(Some("carol@example.com")),
+        ];
+        // Reviewed after `now`: not counted.
+        reviews.push(ReviewEvent {
+            timestamp: ts("2024-06-20"),
+            ..review(Some("bob@example.com"))
+        });
+
+        let stats = compute(&[], &reviews, now);
+        assert_eq!(
+            stats.reviewed_authors,
+            [
+                ("alice@example.com".to_owned(), 3),
+                (UNKNOWN_AUTHOR.to_owned(), 2),
+                ("bob@example.com".to_owned(), 1),
+                ("carol@example.com".to_owned(), 1),
+            ]
+        );
+
+        let many: Vec<ReviewEvent> = (0..TOP_AUTHORS_COUNT + 3)
+            .map(|i| review(Some(&format!("dev{i}@example.com"))))
+            .collect();
+        let stats = compute(&[], &many, now);
+        assert_eq!(stats.reviewed_authors.len(), TOP_AUTHORS_COUNT);
+    }
+
+    #[test]
+    fn contribution_range_spans_all_history() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("repo", "2024-06-10", 1, 0),
+            merged_cl("repo", "2019-03-11", 1, 0), // far outside the window
+            merged_cl("repo", "2021-01-04", 1, 0),
+        ];
+        let stats = compute(&changes, &[], now);
+        assert_eq!(
+            stats.first_contribution,
+            NaiveDate::from_ymd_opt(2019, 3, 11)
+        );
+        assert_eq!(
+            stats.last_contribution,
+            NaiveDate::from_ymd_opt(2024, 6, 10)
+        );
+        assert_eq!(stats.contribution_span().unwrap().num_days(), 1918);
+    }
+
+    #[test]
+    fn contribution_range_none_without_merged_cls() {
+        let stats = compute(&[], &[], ts("2024-06-12"));
+        assert_eq!(stats.first_contribution, None);
+        assert_eq!(stats.last_contribution, None);
+        assert!(stats.contribution_span().is_none());
+    }
+
+    #[test]
+    fn distinct_projects_counts_full_names() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("openscreen", "2024-06-10", 1, 0),
+            merged_cl("openscreen", "2024-06-11", 1, 0),
+            merged_cl("openscreen/quic", "2024-06-11", 1, 0),
+        ];
+        let stats = compute(&changes, &[], now);
+        // Same family, but two full project names.
+        assert_eq!(stats.distinct_projects, 2);
+    }
+
+    #[test]
+    fn max_cl_size_drops_huge_cls_everywhere() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("big", "2024-06-10", 5000, 0),
+            merged_cl("small", "2024-06-10", 10, 2),
+        ];
+        let opts = ComputeOptions {
+            max_cl_size: Some(1000),
+            ..Default::default()
+        };
+        let stats = compute_with(&changes, &[], now, &opts);
+        assert_eq!(stats.total_merged, 1);
+        assert_eq!(stats.total_insertions, 10);
+        assert_eq!(stats.heatmap.weeks.last().unwrap().count, 1);
+        assert_eq!(stats.top_projects.len(), 1);
+        assert_eq!(stats.top_projects[0].name, "small");
+    }
+
+    #[test]
+    fn clamp_cl_lines_caps_line_counts_but_keeps_the_merge() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("vendor", "2024-06-10", 1_000_000, 1_000_000),
+            merged_cl("src", "2024-06-10", 10, 2),
+        ];
+        let opts = ComputeOptions {
+            clamp_cl_lines: Some(500),
+            ..Default::default()
+        };
+        let stats = compute_with(&changes, &[], now, &opts);
+        assert_eq!(stats.total_merged, 2);
+        assert_eq!(stats.total_insertions, 510);
+        assert_eq!(stats.total_deletions, 502);
+        let week = stats.heatmap.weeks.last().unwrap();
+        assert_eq!(week.count, 2);
+        assert_eq!(week.lines_changed, 1012);
+        assert_eq!(stats.monthly.last().unwrap().lines, 1012);
+        let vendor = stats
+            .top_projects
+            .iter()
+            .find(|p| p.name == "vendor")
+            .unwrap();
+        assert_eq!((vendor.insertions, vendor.deletions), (500, 500));
+    }
+
+    #[test]
+    fn cl_size_bounds_are_inclusive() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("repo", "2024-06-10", 4, 0),   // below min
+            merged_cl("repo", "2024-06-10", 3, 2),   // == min
+            merged_cl("repo", "2024-06-10", 50, 50), // == max
+            merged_cl("repo", "2024-06-10", 100, 1), // above max
+        ];
+        let opts = ComputeOptions {
+            min_cl_size: Some(5),
+            max_cl_size: Some(100),
+            ..Default::default()
+        };
+        let stats = compute_with(&changes, &[], now, &opts);
+        assert_eq!(stats.total_merged, 2);
+    }
+
+    #[test]
+    fn weekday_counts_cover_cls_and_reviews_in_window() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("repo", "2024-06-10", 1, 0), // Monday
+            merged_cl("repo", "2024-06-12", 1, 0), // Wednesday
+            merged_cl("repo", "2023-05-03", 1, 0), // Wednesday, outside window
+        ];
+        let reviews = vec![ReviewEvent {
+            timestamp: ts("2024-06-05"), // Wednesday
+            project: "repo".to_owned(),
+            insertions: 0,
+            deletions: 0,
+            author: None,
+        }];
+        let stats = compute(&changes, &reviews, now);
+        assert_eq!(stats.weekday_counts, [1, 0, 2, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn reviewed_lines_sum_separately_from_authored() {
+        let now = ts("2024-06-12");
+        let review = |insertions, deletions| ReviewEvent {
+            timestamp: ts("2024-06-05"),
+            project: "repo".to_owned(),
+            insertions,
+            deletions,
+            author: None,
+        };
+        let changes = vec![merged_cl("repo", "2024-06-10", 7, 3)];
+        let reviews = vec![review(100, 10), review(50, 0)];
+        let stats = compute(&changes, &reviews, now);
+        assert_eq!(stats.reviewed_insertions, 150);
+        assert_eq!(stats.reviewed_deletions, 10);
+        assert_eq!(stats.total_insertions, 7);
+        assert_eq!(stats.total_deletions, 3);
+    }
+
+    fn month(stats: &Stats, year: i32, month: u32) -> &MonthBucket {
+        stats
+            .monthly
+            .iter()
+            .find(|m| (m.year, m.month) == (year, month))
+            .unwrap()
+    }
+
+    #[test]
+    fn monthly_separates_calendar_months() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("repo", "2024-01-15", 10, 5),
+            merged_cl("repo", "2024-01-31", 1, 0),
+            merged_cl("repo", "2024-02-01", 7, 3),
+            merged_cl("repo", "2022-01-10", 99, 0), // outside the window
+        ];
+        let reviews = vec![ReviewEvent {
+            timestamp: ts("2024-02-20"),
+            project: "repo".to_owned(),
+            insertions: 500,
+            deletions: 0,
+            author: None,
+        }];
+        let stats = compute(&changes, &reviews, now);
+
+        let jan = month(&stats, 2024, 1);
+        assert_eq!((jan.cls, jan.reviews, jan.lines), (2, 0, 16));
+        let feb = month(&stats, 2024, 2);
+        assert_eq!((feb.cls, feb.reviews, feb.lines), (1, 1, 10));
+    }
+
+    #[test]
+    fn monthly_covers_window_with_empty_months() {
+        let now = ts("2024-06-12");
+        let stats = compute(&[merged_cl("repo", "2024-01-15", 1, 0)], &[], now);
+
+        let first = &stats.monthly[0];
+        let heatmap_start = stats.heatmap.weeks[0].week_start;
+        assert_eq!(
+            (first.year, first.month),
+            (heatmap_start.year(), heatmap_start.month())
+        );
+        let last = stats.monthly.last().unwrap();
+        assert_eq!((last.year, last.month), (2024, 6));
+        assert_eq!(stats.monthly.len(), 13);
+
+        let march = month(&stats, 2024, 3);
+        assert_eq!((march.cls, march.reviews, march.lines), (0, 0, 0));
+    }
+
+    #[test]
+    fn lines_changed_accumulates_per_week() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("repo", "2024-06-10", 10, 5),
+            merged_cl("repo", "2024-06-11", 3, 2),
+            merged_cl("repo", "2024-06-03", 100, 0), // previous week
+        ];
+        let stats = compute(&changes, &[], now);
+        let n = stats.heatmap.weeks.len();
+        assert_eq!(stats.heatmap.weeks[n - 1].lines_changed, 20);
+        assert_eq!(stats.heatmap.weeks[n - 2].lines_changed, 100);
+        let last = &stats.heatmap.weeks[n - 1];
+        assert_eq!((last.insertions, last.deletions), (13, 7));
+    }
+
+    #[test]
+    fn lines_changed_ignores_reviews_and_out_of_window_cls() {
+        let now = ts("2024-06-12");
+        let changes = vec![merged_cl("repo", "2023-01-02", 50, 50)];
+        let reviews = vec![ReviewEvent {
+            timestamp: ts("2024-06-10"),
+            project: "repo".to_owned(),
+            insertions: 0,
+            deletions: 0,
+            author: None,
+        }];
+        let stats = compute(&changes, &reviews, now);
+        assert!(stats.heatmap.weeks.iter().all(|b| b.lines_changed == 0));
+    }
+
+    #[test]
+    fn abandoned_and_open_cls_stay_off_the_heatmap() {
+        let now = ts("2024-06-12");
         let mut abandoned = merged_cl("repo", "2024-06-10", 10, 5);
         abandoned.status = ChangeStatus::Abandoned;
         abandoned.submitted = None;
@@ -462,6 +2166,52 @@ mod tests {
         let stats = compute(&[abandoned, open], &[], now);
         assert_eq!(stats.total_merged, 0);
         assert_eq!(stats.heatmap.max_count, 0);
+        assert_eq!(stats.total_abandoned, 1);
+        assert_eq!(stats.open_ages, [1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn closed_page_classifies_by_status() {
+        let now = ts("2024-06-12");
+        let mut abandoned = merged_cl("repo", "2024-06-10", 10, 5);
+        abandoned.status = ChangeStatus::Abandoned;
+        abandoned.submitted = None;
+        let mut abandoned_later = merged_cl("repo", "2024-06-10", 10, 5);
+        abandoned_later.status = ChangeStatus::Abandoned;
+        abandoned_later.submitted = None;
+        abandoned_later.updated = ts("2024-06-20");
+        let changes = [
+            merged_cl("repo", "2024-06-03", 10, 5),
+            abandoned,
+            merged_cl("repo", "2024-06-05", 1, 1),
+            abandoned_later,
+        ];
+
+        let stats = compute(&changes, &[], now);
+        assert_eq!(stats.total_merged, 2);
+        assert_eq!(stats.total_insertions, 11);
+        // Abandoned after `now` doesn't count yet.
+        assert_eq!(stats.total_abandoned, 1);
+        assert_eq!(stats.abandon_rate(), Some(1.0 / 3.0));
+        assert_eq!(compute(&[], &[], now).abandon_rate(), None);
+    }
+
+    #[test]
+    fn open_cls_bin_by_age() {
+        let now = ts("2024-06-12");
+        let open = |created: &str| {
+            let mut c = merged_cl("repo", created, 10, 5);
+            c.status = ChangeStatus::New;
+            c.submitted = None;
+            c
+        };
+        // 3 days, 20 days and about 7 months old.
+        let changes = [open("2024-06-09"), open("2024-05-23"), open("2023-11-10")];
+
+        let stats = compute(&changes, &[], now);
+        assert_eq!(stats.open_ages, [1, 1, 0, 1]);
+        assert_eq!(stats.total_merged, 0);
+        assert_eq!(stats.heatmap.max_count, 0);
     }
 
     // -----------------------------------------------------------------------
@@ -481,6 +2231,50 @@ mod tests {
         assert_eq!(stats.total_deletions, 5);
     }
 
+    #[test]
+    fn avg_revisions_counts_only_cls_with_revision_data() {
+        let now = ts("2024-06-12");
+        let with = |n| ChangeInfo {
+            revision_count: Some(n),
+            ..merged_cl("a", "2024-06-10", 1, 0)
+        };
+        let changes = vec![with(1), with(4), merged_cl("a", "2024-06-10", 1, 0)];
+        let stats = compute(&changes, &[], now);
+        assert_eq!(stats.avg_revisions_per_cl, Some(2.5));
+
+        let without = compute(&[merged_cl("a", "2024-06-10", 1, 0)], &[], now);
+        assert_eq!(without.avg_revisions_per_cl, None);
+    }
+
+    #[test]
+    fn reviews_outside_heatmap_still_count_in_totals() {
+        let now = ts("2024-06-12");
+        let changes = vec![merged_cl("a", "2024-06-10", 1, 0)];
+        let reviews: Vec<ReviewEvent> = (0..5)
+            .map(|_| ReviewEvent {
+                timestamp: ts("2024-06-11"),
+                project: "b".to_owned(),
+                insertions: 1,
+                deletions: 0,
+                author: None,
+            })
+            .collect();
+        let opts = ComputeOptions {
+            reviews_outside_heatmap: true,
+            ..Default::default()
+        };
+
+        let with = compute(&changes, &reviews, now);
+        let without = compute_with(&changes, &reviews, now, &opts);
+        assert_eq!(with.heatmap.max_count, 6);
+        assert_eq!(without.heatmap.max_count, 1);
+        let last = without.heatmap.weeks.last().unwrap();
+        assert_eq!((last.count, last.review_count), (1, 0));
+        assert!(!last.family_counts.contains_key("b"));
+        assert_eq!(without.total_reviews, 5);
+        assert_eq!(without.recent_reviews_90d, 5);
+    }
+
     #[test]
     fn recent_90d_count_is_accurate() {
         let now = ts("2024-06-12");
@@ -493,6 +2287,56 @@ mod tests {
         assert_eq!(stats.recent_merged_90d, 2);
     }
 
+    #[test]
+    fn recent_days_narrows_the_recent_window() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("r", "2024-06-01", 1, 0), // 11 days
+            merged_cl("r", "2024-04-28", 1, 0), // 45 days
+            merged_cl("r", "2024-04-01", 1, 0), // 72 days
+        ];
+        let opts = ComputeOptions {
+            recent_days: 30,
+            ..Default::default()
+        };
+        let stats = compute_with(&changes, &[], now, &opts);
+        assert_eq!(stats.recent_days, 30);
+        assert_eq!(stats.recent_merged_90d, 1, "45 days old is outside 30d");
+        // The previous window is days 30–60.
+        assert_eq!(stats.prev_merged_90d, 1);
+
+        let stats = compute(&changes, &[], now);
+        assert_eq!(stats.recent_days, DEFAULT_RECENT_DAYS);
+        assert_eq!(stats.recent_merged_90d, 3);
+    }
+
+    #[test]
+    fn prev_90d_window_excludes_the_recent_one() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("r", "2024-06-01", 1, 0), // 11 days → recent only
+            merged_cl("r", "2024-03-14", 1, 0), // exactly 90 days → previous
+            merged_cl("r", "2024-01-01", 1, 0), // 163 days → previous
+            merged_cl("r", "2023-12-01", 1, 0), // 194 days → neither
+        ];
+        let review = |day| ReviewEvent {
+            timestamp: ts(day),
+            project: "r".to_owned(),
+            insertions: 0,
+            deletions: 0,
+            author: None,
+        };
+        let reviews = [
+            review("2024-06-10"),
+            review("2024-05-01"),
+            review("2024-02-01"),
+        ];
+
+        let stats = compute(&changes, &reviews, now);
+        assert_eq!((stats.recent_merged_90d, stats.prev_merged_90d), (1, 2));
+        assert_eq!((stats.recent_reviews_90d, stats.prev_reviews_90d), (2, 1));
+    }
+
     // -----------------------------------------------------------------------
     // Top projects
     // -----------------------------------------------------------------------
@@ -513,6 +2357,46 @@ mod tests {
         assert_eq!(stats.top_projects[1].merged, 1);
     }
 
+    #[test]
+    fn project_shares_follow_merged_counts() {
+        let now = ts("2024-06-12");
+        // 5 + 3 + 2 merged CLs: 50% / 30% / 20%.
+        let changes: Vec<ChangeInfo> = [("alpha", 5), ("beta", 3), ("gamma", 2)]
+            .into_iter()
+            .flat_map(|(p, n)| (0..n).map(move |_| merged_cl(p, "2024-06-10", 1, 0)))
+            .collect();
+        let stats = compute(&changes, &[], now);
+        let shares: Vec<(&str, f64)> = stats
+            .top_projects
+            .iter()
+            .map(|p| (p.name.as_str(), p.share))
+            .collect();
+        assert_eq!(shares, [("alpha", 0.5), ("beta", 0.3), ("gamma", 0.2)]);
+        assert_eq!(stats.concentration, 0.5);
+
+        assert_eq!(compute(&[], &[], now).concentration, 0.0);
+    }
+
+    #[test]
+    fn project_shares_sum_to_at_most_one() {
+        let now = ts("2024-06-12");
+        // More projects than TOP_PROJECTS_COUNT, so the tail is cut off.
+        let changes: Vec<ChangeInfo> = (0..TOP_PROJECTS_COUNT + 3)
+            .flat_map(|i| {
+                let project = format!("p{i}");
+                (0..=i).map(move |_| merged_cl(&project, "2024-06-10", 1, 0))
+            })
+            .collect();
+        let stats = compute(&changes, &[], now);
+        let total: f64 = stats.top_projects.iter().map(|p| p.share).sum();
+        assert!(total > 0.0 && total <= 1.0, "{total}");
+        assert_eq!(stats.concentration, stats.top_projects[0].share);
+        assert!(stats
+            .top_projects
+            .windows(2)
+            .all(|w| w[0].share >= w[1].share));
+    }
+
     #[test]
     fn top_projects_capped_at_limit() {
         let now = ts("2024-06-12");
@@ -524,6 +2408,190 @@ mod tests {
         assert!(stats.top_projects.len() <= TOP_PROJECTS_COUNT);
     }
 
+    // -----------------------------------------------------------------------
+    // Received votes
+    // -----------------------------------------------------------------------
+
+    /// A merged CL owned by account 1 with Code-Review votes from a mix of
+    /// voters: the owner, two humans, and a bot.
+    fn voted_cl() -> ChangeInfo {
+        let json = r#"{
+            "id": "repo~main~Ivoted",
+            "_number": 7,
+            "subject": "Voted change",
+            "project": "repo",
+            "branch": "main",
+            "status": "MERGED",
+            "created": "2024-06-09 12:00:00.000000000",
+            "updated": "2024-06-10 12:00:00.000000000",
+            "submitted": "2024-06-10 12:00:00.000000000",
+            "insertions": 1,
+            "deletions": 0,
+            "owner": {"_account_id": 1, "email": "me@example.com"},
+            "labels": {
+                "Code-Review": {"all": [
+                    {"value": 2, "_account_id": 1, "email": "me@example.com"},
+                    {"value": 2, "_account_id": 2, "email": "alice@example.com"},
+                    {"value": -1, "_account_id": 3, "email": "bob@example.com"},
+                    {"value": 1, "_account_id": 4, "username": "autoroll-bot"},
+                    {"value": 0, "_account_id": 5, "email": "cc@example.com"}
+                ]},
+                "Commit-Queue": {"all": [
+                    {"value": 2, "_account_id": 1, "email": "me@example.com"}
+                ]}
+            }
+        }"#;
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn received_scores_skip_self_and_zero_votes() {
+        let stats = compute(&[voted_cl()], &[], ts("2024-06-12"));
+        let hist = &stats.received_score_histogram;
+        assert_eq!(hist.get(&2), Some(&1), "alice's +2 counts once");
+        assert_eq!(hist.get(&-1), Some(&1));
+        assert_eq!(hist.get(&1), Some(&1), "bot counts unless excluded");
+        assert_eq!(hist.get(&0), None);
+    }
+
+    #[test]
+    fn received_scores_honour_exclude_voters() {
+        let opts = ComputeOptions {
+            exclude_voters: vec!["autoroll-bot".to_owned(), "bob@example.com".to_owned()],
+            ..Default::default()
+        };
+        let stats = compute_with(&[voted_cl(), voted_cl()], &[], ts("2024-06-12"), &opts);
+        let hist = &stats.received_score_histogram;
+        assert_eq!(hist.get(&2), Some(&2), "one +2 per CL");
+        assert_eq!(hist.get(&1), None);
+        assert_eq!(hist.get(&-1), None);
+    }
+
+    #[test]
+    fn exclude_wip_drops_wip_changes() {
+        let wip: ChangeInfo = serde_json::from_str(
+            r#"{
+            "id": "repo~main~I2",
+            "_number": 2,
+            "subject": "Draft that got merged",
+            "project": "repo",
+            "branch": "main",
+            "status": "MERGED",
+            "created": "2024-06-01 09:00:00.000000000",
+            "updated": "2024-06-10 09:00:00.000000000",
+            "submitted": "2024-06-10 09:00:00.000000000",
+            "insertions": 40,
+            "deletions": 2,
+            "work_in_progress": true
+        }"#,
+        )
+        .unwrap();
+        assert!(wip.work_in_progress);
+        assert!(!voted_cl().work_in_progress, "absent means not WIP");
+
+        let changes = [wip, voted_cl()];
+        let now = ts("2024-06-12");
+        assert_eq!(compute(&changes, &[], now).total_merged, 2);
+        let opts = ComputeOptions {
+            exclude_wip: true,
+            ..Default::default()
+        };
+        let stats = compute_with(&changes, &[], now, &opts);
+        assert_eq!(stats.total_merged, 1);
+        assert_eq!(stats.total_insertions, voted_cl().insertions as i64);
+    }
+
+    #[test]
+    fn received_scores_empty_without_labels() {
+        let changes = vec![merged_cl("repo", "2024-06-10", 1, 0)];
+        let stats = compute(&changes, &[], ts("2024-06-12"));
+        assert!(stats.received_score_histogram.is_empty());
+    }
+
+    // -----------------------------------------------------------------------
+    // Project exclusion
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn project_matches_exact_and_sub_repo_prefix() {
+        assert!(project_matches(
+            "chromium/third_party",
+            "chromium/third_party"
+        ));
+        assert!(project_matches(
+            "chromium/third_party",
+            "chromium/third_party/ffmpeg"
+        ));
+        assert!(!project_matches(
+            "chromium/third_party",
+            "chromium/third_party_extra"
+        ));
+        assert!(!project_matches("chromium/third_party", "chromium/src"));
+    }
+
+    #[test]
+    fn project_matches_glob() {
+        assert!(project_matches(
+            "chromium/third_party/*",
+            "chromium/third_party/ffmpeg"
+        ));
+        assert!(project_matches("*/infra", "chrome/infra"));
+        assert!(project_matches("a*c*e", "abcde"));
+        assert!(!project_matches("chromium/third_party/*", "chromium/src"));
+    }
+
+    #[test]
+    fn project_matches_multi_host_prefix() {
+        assert!(project_matches("infra/*", "chromium::infra/luci"));
+        assert!(project_matches("go::*", "go::tools"));
+        assert!(!project_matches("go::*", "chromium::go"));
+    }
+
+    #[test]
+    fn exclude_projects_filters_stats_and_heatmap() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("chromium/src", "2024-06-10", 10, 0),
+            merged_cl("chromium/third_party/ffmpeg", "2024-06-10", 5000, 0),
+            merged_cl("chromium/third_party/skia", "2024-06-10", 5000, 0),
+        ];
+        let reviews = vec![ReviewEvent {
+            timestamp: ts("2024-06-10"),
+            project: "chromium/third_party/ffmpeg".to_owned(),
+            insertions: 0,
+            deletions: 0,
+            author: None,
+        }];
+        let opts = ComputeOptions {
+            exclude_projects: vec!["chromium/third_party/*".to_owned()],
+            ..Default::default()
+        };
+        let stats = compute_with(&changes, &reviews, now, &opts);
+        assert_eq!(stats.total_merged, 1);
+        assert_eq!(stats.total_insertions, 10);
+        assert_eq!(stats.total_reviews, 0);
+        assert_eq!(stats.top_projects.len(), 1);
+        let last = stats.heatmap.weeks.last().unwrap();
+        assert_eq!(last.count, 1);
+        assert_eq!(last.family_counts.get("chromium").copied(), Some(1));
+    }
+
+    #[test]
+    fn exclude_projects_exact_name() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("infra", "2024-06-10", 1, 0),
+            merged_cl("infra-tools", "2024-06-10", 1, 0),
+        ];
+        let opts = ComputeOptions {
+            exclude_projects: vec!["infra".to_owned()],
+            ..Default::default()
+        };
+        let stats = compute_with(&changes, &[], now, &opts);
+        assert_eq!(stats.total_merged, 1);
+        assert_eq!(stats.top_projects[0].name, "infra-tools");
+    }
+
     // -----------------------------------------------------------------------
     // Intensity levels
     // -----------------------------------------------------------------------
@@ -533,6 +2601,9 @@ mod tests {
             week_start: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
             count,
             review_count,
+            lines_changed: 0,
+            insertions: 0,
+            deletions: 0,
             family_counts: HashMap::new(),
         }
     }
@@ -565,7 +2636,7 @@ mod tests {
         for c in 1..=20u32 {
             let lv = bucket(c, 0).level();
             assert!(lv >= prev, "level dropped: count={c} lv={lv} prev={prev}");
-            assert!(lv >= 1 && lv <= 4);
+            assert!((1..=4).contains(&lv));
             prev = lv;
         }
     }
@@ -593,6 +2664,83 @@ mod tests {
         assert_eq!(heatmap.current_streak(), 0);
     }
 
+    #[test]
+    fn holiday_week_does_not_break_streak() {
+        let mut heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        let n = heatmap.weeks.len();
+        heatmap.weeks[n - 1].count = 3;
+        heatmap.weeks[n - 2].count = 0; // holiday week
+        heatmap.weeks[n - 3].count = 2;
+        heatmap.weeks[n - 4].count = 1;
+        heatmap.weeks[n - 5].count = 0; // ordinary gap
+        heatmap.weeks[n - 6].count = 4;
+
+        // Thursday of the empty week.
+        let holiday = heatmap.weeks[n - 2].week_start + Duration::days(3);
+        assert_eq!(heatmap.current_streak(), 1);
+        assert_eq!(heatmap.current_streak_excluding_holidays(&[holiday]), 3);
+        // A holiday in a week that had activity changes nothing.
+        let busy = heatmap.weeks[n - 3].week_start;
+        assert_eq!(heatmap.current_streak_excluding_holidays(&[busy]), 1);
+        assert_eq!(heatmap.current_streak_excluding_holidays(&[]), 1);
+    }
+
+    #[test]
+    fn grace_bridges_single_gap_but_not_two() {
+        let mut heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        let n = heatmap.weeks.len();
+        // Oldest → newest: 1 1 _ 1 _ _ 1 1 _ 1
+        for i in [10, 9, 7, 4, 3, 1] {
+            heatmap.weeks[n - i].count = 1;
+        }
+        assert_eq!(heatmap.current_streak(), 1);
+        assert_eq!(heatmap.current_streak_with_grace(0), 1);
+        // Bridges the single gap, stops at the double one.
+        assert_eq!(heatmap.current_streak_with_grace(1), 3);
+        assert_eq!(heatmap.current_streak_with_grace(2), 6);
+
+        assert_eq!(heatmap.longest_streak(), 2);
+        assert_eq!(heatmap.longest_streak_with_grace(0), 2);
+        assert_eq!(heatmap.longest_streak_with_grace(1), 3);
+        assert_eq!(heatmap.longest_streak_with_grace(2), 6);
+    }
+
+    #[test]
+    fn grace_keeps_current_streak_through_a_fresh_break() {
+        let mut heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        let n = heatmap.weeks.len();
+        heatmap.weeks[n - 2].count = 2;
+        heatmap.weeks[n - 3].count = 2;
+        assert_eq!(heatmap.current_streak(), 0);
+        assert_eq!(heatmap.current_streak_with_grace(1), 2);
+    }
+
+    #[test]
+    fn stats_streaks_use_compute_grace() {
+        let opts = ComputeOptions {
+            streak_grace: 1,
+            ..Default::default()
+        };
+        let changes = [
+            merged_cl("a", "2024-06-10", 1, 0),
+            merged_cl("a", "2024-05-27", 1, 0),
+        ];
+        let stats = compute_with(&changes, &[], ts("2024-06-12"), &opts);
+        assert_eq!(stats.heatmap.current_streak(), 1);
+        assert_eq!(stats.current_streak(), 2);
+        assert_eq!(stats.longest_streak(), 2);
+    }
+
+    #[test]
+    fn week_contains_any_covers_monday_to_sunday() {
+        let b = bucket(0, 0);
+        let monday = b.week_start;
+        assert!(b.contains_any(&[monday]));
+        assert!(b.contains_any(&[monday + Duration::days(6)]));
+        assert!(!b.contains_any(&[monday + Duration::days(7)]));
+        assert!(!b.contains_any(&[monday - Duration::days(1)]));
+    }
+
     #[test]
     fn longest_streak_finds_peak_run() {
         let mut heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
@@ -624,21 +2772,114 @@ mod tests {
         assert_eq!(heatmap.longest_streak(), HEATMAP_WEEKS as u32);
     }
 
+    #[test]
+    fn busiest_week_clear_winner() {
+        let mut heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        heatmap.weeks[3].count = 4;
+        heatmap.weeks[10].count = 12;
+        heatmap.weeks[20].count = 7;
+        let peak = heatmap.busiest_week().unwrap();
+        assert_eq!(peak.count, 12);
+        assert_eq!(peak.week_start, heatmap.weeks[10].week_start);
+    }
+
+    #[test]
+    fn busiest_week_tie_resolves_to_earlier() {
+        let mut heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        heatmap.weeks[5].count = 9;
+        heatmap.weeks[30].count = 9;
+        let peak = heatmap.busiest_week().unwrap();
+        assert_eq!(peak.week_start, heatmap.weeks[5].week_start);
+    }
+
+    #[test]
+    fn busiest_week_none_when_empty() {
+        let heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        assert!(heatmap.busiest_week().is_none());
+    }
+
+    #[test]
+    fn longest_gap_finds_peak_break() {
+        let mut heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        for b in &mut heatmap.weeks {
+            b.count = 1;
+        }
+        // Pattern: 1 | 0 0 | 1 | 0 0 0 0 | 1 ...
+        heatmap.weeks[1].count = 0;
+        heatmap.weeks[2].count = 0; // gap of 2
+        heatmap.weeks[4].count = 0;
+        heatmap.weeks[5].count = 0;
+        heatmap.weeks[6].count = 0;
+        heatmap.weeks[7].count = 0; // gap of 4
+        assert_eq!(heatmap.longest_gap(), 4);
+    }
+
+    #[test]
+    fn longest_gap_counts_leading_and_trailing_breaks() {
+        let mut heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        let n = heatmap.weeks.len();
+        // 3 empty weeks at the start, 5 at the end, activity in between.
+        for b in &mut heatmap.weeks[3..n - 5] {
+            b.count = 2;
+        }
+        assert_eq!(heatmap.longest_gap(), 5);
+
+        // Flip it: a longer leading gap wins.
+        for b in &mut heatmap.weeks[3..10] {
+            b.count = 0;
+        }
+        assert_eq!(heatmap.longest_gap(), 10);
+    }
+
+    #[test]
+    fn longest_gap_all_empty_is_full_window() {
+        let heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        assert_eq!(heatmap.longest_gap(), HEATMAP_WEEKS as u32);
+    }
+
+    #[test]
+    fn longest_gap_all_full_is_zero() {
+        let mut heatmap = compute(&[], &[], ts("2024-06-12")).heatmap;
+        for b in &mut heatmap.weeks {
+            b.count = 1;
+        }
+        assert_eq!(heatmap.longest_gap(), 0);
+    }
+
     // -----------------------------------------------------------------------
-    // iso_week_start helper
+    // week_start helper
     // -----------------------------------------------------------------------
 
     #[test]
-    fn iso_week_start_on_monday_returns_self() {
+    fn monday_week_start_on_monday_returns_self() {
         let monday = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(); // known Monday
-        assert_eq!(iso_week_start(monday), monday);
+        assert_eq!(week_start(monday, WeekStart::Monday), monday);
     }
 
     #[test]
-    fn iso_week_start_on_sunday_returns_preceding_monday() {
+    fn monday_week_start_on_sunday_returns_preceding_monday() {
         let sunday = NaiveDate::from_ymd_opt(2024, 6, 16).unwrap(); // known Sunday
         let expected = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap();
-        assert_eq!(iso_week_start(sunday), expected);
+        assert_eq!(week_start(sunday, WeekStart::Monday), expected);
+    }
+
+    #[test]
+    fn sunday_week_start_on_sunday_returns_self() {
+        let sunday = NaiveDate::from_ymd_opt(2024, 6, 16).unwrap(); // known Sunday
+        assert_eq!(week_start(sunday, WeekStart::Sunday), sunday);
+    }
+
+    #[test]
+    fn sunday_week_start_on_monday_returns_preceding_sunday() {
+        let monday = NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(); // known Monday
+        let expected = NaiveDate::from_ymd_opt(2024, 6, 9).unwrap();
+        assert_eq!(week_start(monday, WeekStart::Sunday), expected);
+    }
+
+    #[test]
+    fn week_start_parses_names() {
+        assert_eq!("sunday".parse::<WeekStart>().unwrap(), WeekStart::Sunday);
+        assert!("tuesday".parse::<WeekStart>().is_err());
     }
 
     // -----------------------------------------------------------------------
@@ -647,25 +2888,25 @@ mod tests {
 
     #[test]
     fn project_family_top_level_repo() {
-        assert_eq!(project_family("openscreen"), "openscreen");
+        assert_eq!(project_family("openscreen", 1), "openscreen");
     }
 
     #[test]
     fn project_family_sub_repo_rolls_up() {
-        assert_eq!(project_family("openscreen/quic"), "openscreen");
-        assert_eq!(project_family("chromium/src"), "chromium");
-        assert_eq!(project_family("chromium/third_party/ffmpeg"), "chromium");
-        assert_eq!(project_family("chromium/tools/build"), "chromium");
+        assert_eq!(project_family("openscreen/quic", 1), "openscreen");
+        assert_eq!(project_family("chromium/src", 1), "chromium");
+        assert_eq!(project_family("chromium/third_party/ffmpeg", 1), "chromium");
+        assert_eq!(project_family("chromium/tools/build", 1), "chromium");
     }
 
     #[test]
     fn project_family_multi_host_prefix() {
         // When multiple hosts are queried, main.rs prefixes with "alias::".
-        assert_eq!(project_family("chromium::chromium/src"), "chromium");
-        assert_eq!(project_family("chromium::openscreen/quic"), "chromium");
-        assert_eq!(project_family("go::cmd/go"), "go");
+        assert_eq!(project_family("chromium::chromium/src", 1), "chromium");
+        assert_eq!(project_family("chromium::openscreen/quic", 1), "chromium");
+        assert_eq!(project_family("go::cmd/go", 1), "go");
         assert_eq!(
-            project_family("android::platform/frameworks/base"),
+            project_family("android::platform/frameworks/base", 1),
             "android"
         );
     }
@@ -674,7 +2915,27 @@ mod tests {
     fn project_family_multi_host_prefix_beats_path_split() {
         // The :: prefix must take precedence over the / split.
         // "go::x/tools" family is "go" (the host), not "go::x" or "x".
-        assert_eq!(project_family("go::x/tools"), "go");
+        assert_eq!(project_family("go::x/tools", 1), "go");
+        assert_eq!(project_family("go::x/tools", 2), "go");
+    }
+
+    #[test]
+    fn project_family_depth_one_keeps_top_segment() {
+        assert_eq!(project_family("chromium/third_party/ffmpeg", 1), "chromium");
+        // 0 is treated as 1 rather than an empty family.
+        assert_eq!(project_family("chromium/third_party/ffmpeg", 0), "chromium");
+    }
+
+    #[test]
+    fn project_family_depth_two_splits_sub_repos() {
+        assert_eq!(
+            project_family("chromium/third_party/ffmpeg", 2),
+            "chromium/third_party"
+        );
+        assert_eq!(project_family("chromium/src", 2), "chromium/src");
+        assert_eq!(project_family("chromium/tools/build", 2), "chromium/tools");
+        // Shallower than the depth: the whole project.
+        assert_eq!(project_family("openscreen", 2), "openscreen");
     }
 
     // -----------------------------------------------------------------------
@@ -701,6 +2962,27 @@ mod tests {
         assert!(!last.family_counts.contains_key("chromium/src"));
     }
 
+    #[test]
+    fn family_counts_apply_merge_families() {
+        let now = ts("2024-06-12");
+        let changes = vec![
+            merged_cl("webrtc/src", "2024-06-10", 1, 0),
+            merged_cl("chromium/src", "2024-06-10", 1, 0),
+        ];
+        let opts = ComputeOptions {
+            merge_families: HashMap::from([("webrtc".to_owned(), "chromium".to_owned())]),
+            ..Default::default()
+        };
+        let stats = compute_with(&changes, &[], now, &opts);
+        let last = stats.heatmap.weeks.last().unwrap();
+
+        assert_eq!(last.family_counts.get("chromium").copied(), Some(2));
+        assert!(!last.family_counts.contains_key("webrtc"));
+        assert_eq!(last.dominant_family(), Some("chromium"));
+        // Per-project stats keep the real names.
+        assert!(stats.top_projects.iter().any(|p| p.name == "webrtc/src"));
+    }
+
     #[test]
     fn family_counts_empty_bucket_has_no_entries() {
         let stats = compute(&[], &[], ts("2024-06-12"));